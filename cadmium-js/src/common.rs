@@ -344,6 +344,11 @@ impl Solid {
     pub fn get_step_text(&self) -> String {
         self.0.get_step_text()
     }
+
+    #[wasm_bindgen]
+    pub fn get_stl_text(&self) -> String {
+        self.0.get_stl_text()
+    }
 }
 
 #[wasm_bindgen]
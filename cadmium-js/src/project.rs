@@ -1,4 +1,5 @@
 // use crate::workbench::Workbench;
+use cadmium::file::FileFormat;
 use cadmium::project as cad_project;
 // use cadmium::workbench as cad_workbench;
 use crate::workbench::Workbench;
@@ -21,6 +22,17 @@ pub fn new_project() -> Project {
     Project(project)
 }
 
+#[wasm_bindgen]
+pub fn load_project(bytes: &[u8]) -> Option<Project> {
+    match cad_project::Project::from_bytes(bytes) {
+        Ok(project) => Some(Project(project)),
+        Err(e) => {
+            log!("Error: {}", e);
+            None
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl Project {
     #[wasm_bindgen(getter)]
@@ -28,11 +40,26 @@ impl Project {
         self.0.name.to_owned()
     }
 
+    #[wasm_bindgen]
+    pub fn save(&self, binary: bool) -> Option<Vec<u8>> {
+        let format = match binary {
+            true => FileFormat::Binary,
+            false => FileFormat::Json,
+        };
+        match self.0.to_bytes(format) {
+            Ok(bytes) => Some(bytes),
+            Err(e) => {
+                log!("Error: {}", e);
+                None
+            }
+        }
+    }
+
     #[wasm_bindgen(getter)]
     pub fn workbench_names(&self) -> Array {
         let wbs: Vec<String> = self
             .0
-            .workbenches
+            .workbenches()
             .iter()
             .map(|wb| wb.name.clone())
             .collect();
@@ -87,6 +114,151 @@ impl Project {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn set_units(&mut self, length: &str, angle: &str) {
+        match cadmium::units::Units::parse(length, angle)
+            .map_err(cadmium::Error::InvalidParameter)
+            .and_then(|units| self.0.set_units(units))
+        {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn roll_back(&mut self, workbench_name: &str, position: usize) {
+        match self.0.roll_back(workbench_name, position) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn release_rollback(&mut self, workbench_name: &str) {
+        match self.0.release_rollback(workbench_name) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn undo(&mut self) {
+        match self.0.undo() {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn redo(&mut self) {
+        match self.0.redo() {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_undo(&self) -> bool {
+        self.0.history.can_undo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_redo(&self) -> bool {
+        self.0.history.can_redo()
+    }
+
+    #[wasm_bindgen]
+    pub fn begin_transaction(&mut self) {
+        self.0.begin_transaction();
+    }
+
+    #[wasm_bindgen]
+    pub fn end_transaction(&mut self) {
+        self.0.end_transaction();
+    }
+
+    #[wasm_bindgen]
+    pub fn add_step(&mut self, workbench_name: &str, step: JsValue) {
+        match serde_wasm_bindgen::from_value::<cadmium::workbench::Step>(step)
+            .map_err(|e| cadmium::Error::InvalidParameter(e.to_string()))
+            .and_then(|step| self.0.add_step(workbench_name, step))
+        {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn insert_step(&mut self, workbench_name: &str, index: usize, step: JsValue) {
+        match serde_wasm_bindgen::from_value::<cadmium::workbench::Step>(step)
+            .map_err(|e| cadmium::Error::InvalidParameter(e.to_string()))
+            .and_then(|step| self.0.insert_step(workbench_name, index, step))
+        {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn delete_step(&mut self, workbench_name: &str, step_name: &str) {
+        match self.0.delete_step(workbench_name, step_name) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn move_step(&mut self, workbench_name: &str, step_name: &str, index: usize) {
+        match self.0.move_step(workbench_name, step_name, index) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_step_suppressed(&mut self, workbench_name: &str, step_name: &str, suppressed: bool) {
+        match self
+            .0
+            .set_step_suppressed(workbench_name, step_name, suppressed)
+        {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn rename_step(&mut self, workbench_name: &str, step_name: &str, new_name: &str) {
+        match self.0.rename_step(workbench_name, step_name, new_name) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn set_variable(&mut self, workbench_name: &str, name: &str, expression: &str) {
+        match self.0.set_variable(workbench_name, name, expression) {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn bind_step_parameter(
+        &mut self,
+        workbench_name: &str,
+        step_name: &str,
+        parameter_name: &str,
+        expression: &str,
+    ) {
+        match self
+            .0
+            .bind_step_parameter(workbench_name, step_name, parameter_name, expression)
+        {
+            Ok(_) => (),
+            Err(e) => log!("Error: {}", e),
+        }
+    }
+
     #[wasm_bindgen]
     pub fn add_segment_to_sketch(
         &mut self,
@@ -189,6 +189,11 @@ impl Sketch {
         self.0.segments.push(as_seg);
     }
 
+    #[wasm_bindgen]
+    pub fn add_point(&mut self, x: f64, y: f64, id: &str) {
+        self.0.add_point(cadmium::sketch::Point::new(x, y, id));
+    }
+
     #[wasm_bindgen]
     pub fn add_line(&mut self, line: Line) {
         let as_seg = cadmium::sketch::Segment::Line(line.0.clone());
@@ -15,6 +15,15 @@ impl Workbench {
         self.0.name.to_owned()
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn suppressed_steps(&self) -> Array {
+        self.0
+            .suppressed
+            .iter()
+            .map(|name| JsValue::from_str(name))
+            .collect()
+    }
+
     #[wasm_bindgen]
     pub fn get_steps(&self) -> Array {
         let retval = Array::new();
@@ -35,6 +44,17 @@ impl Workbench {
                     };
                     retval.push(&JsValue::from(new_plane_step));
                 }
+                cad_workbench::Step::Helix { name, helix } => {
+                    let new_helix_step = NewHelixStep {
+                        name: name.to_owned(),
+                        origin: Point::wrap(helix.origin),
+                        radius: helix.radius,
+                        pitch: helix.pitch,
+                        turns: helix.turns,
+                        taper: helix.taper,
+                    };
+                    retval.push(&JsValue::from(new_helix_step));
+                }
                 cad_workbench::Step::Sketch {
                     name,
                     sketch: _,
@@ -50,6 +70,7 @@ impl Workbench {
                     extrusion: ext,
                     sketch,
                     faces,
+                    segments,
                 } => {
                     let new_extrude_step = NewExtrudeStep {
                         name: name.to_owned(),
@@ -58,19 +79,331 @@ impl Workbench {
                         operation: ext.operation.to_string(),
                         faces: faces.to_vec(),
                         sketch: sketch.to_owned(),
+                        thickness: ext.thin.as_ref().map(|t| t.thickness),
+                        side: ext.thin.as_ref().map(|t| t.side.to_string()),
+                        segments: segments.to_vec(),
                     };
                     retval.push(&JsValue::from(new_extrude_step));
                 }
+                cad_workbench::Step::Revolve {
+                    name,
+                    revolve,
+                    sketch,
+                    faces,
+                    segments,
+                } => {
+                    let new_revolve_step = NewRevolveStep {
+                        name: name.to_owned(),
+                        origin: revolve.origin,
+                        axis: revolve.axis,
+                        angle: revolve.angle,
+                        operation: revolve.operation.to_string(),
+                        thickness: revolve.thin.as_ref().map(|t| t.thickness),
+                        side: revolve.thin.as_ref().map(|t| t.side.to_string()),
+                        sketch: sketch.to_owned(),
+                        faces: faces.to_vec(),
+                        segments: segments.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_revolve_step));
+                }
+                cad_workbench::Step::Sweep {
+                    name,
+                    sweep,
+                    sketch,
+                    faces,
+                    path,
+                    segments,
+                } => {
+                    let new_sweep_step = NewSweepStep {
+                        name: name.to_owned(),
+                        orientation: sweep.orientation.to_string(),
+                        operation: sweep.operation.to_string(),
+                        faces: faces.to_vec(),
+                        sketch: sketch.to_owned(),
+                        path: path.to_owned(),
+                        segments: segments.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_sweep_step));
+                }
+                cad_workbench::Step::Loft {
+                    name,
+                    loft,
+                    profiles,
+                    guides,
+                } => {
+                    let new_loft_step = NewLoftStep {
+                        name: name.to_owned(),
+                        operation: loft.operation.to_string(),
+                        profiles: profiles.to_vec(),
+                        guides: guides.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_loft_step));
+                }
+                cad_workbench::Step::Fillet {
+                    name,
+                    fillet,
+                    edges,
+                } => {
+                    let new_fillet_step = NewFilletStep {
+                        name: name.to_owned(),
+                        radius: fillet.radius,
+                        edges: edges.iter().map(|e| e.to_string()).collect(),
+                    };
+                    retval.push(&JsValue::from(new_fillet_step));
+                }
+                cad_workbench::Step::Chamfer {
+                    name,
+                    chamfer,
+                    edges,
+                } => {
+                    let (distance, second) = match chamfer.mode {
+                        cad_workbench::ChamferMode::DistanceDistance(d1, d2) => (d1, d2),
+                        cad_workbench::ChamferMode::DistanceAngle(d, angle) => (d, angle),
+                    };
+                    let new_chamfer_step = NewChamferStep {
+                        name: name.to_owned(),
+                        mode: chamfer.mode.to_string(),
+                        distance,
+                        second,
+                        edges: edges.iter().map(|e| e.to_string()).collect(),
+                    };
+                    retval.push(&JsValue::from(new_chamfer_step));
+                }
+                cad_workbench::Step::Shell {
+                    name,
+                    shell,
+                    solid,
+                    faces,
+                } => {
+                    let new_shell_step = NewShellStep {
+                        name: name.to_owned(),
+                        thickness: shell.thickness,
+                        direction: shell.direction.to_string(),
+                        solid: solid.to_owned(),
+                        faces: faces.iter().map(|f| format!("{:?}", f)).collect(),
+                    };
+                    retval.push(&JsValue::from(new_shell_step));
+                }
+                cad_workbench::Step::LinearPattern {
+                    name,
+                    pattern,
+                    features,
+                } => {
+                    let new_linear_pattern_step = NewLinearPatternStep {
+                        name: name.to_owned(),
+                        operation: pattern.operation.to_string(),
+                        directions: pattern.directions.to_vec(),
+                        features: features.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_linear_pattern_step));
+                }
+                cad_workbench::Step::CircularPattern {
+                    name,
+                    pattern,
+                    features,
+                } => {
+                    let new_circular_pattern_step = NewCircularPatternStep {
+                        name: name.to_owned(),
+                        operation: pattern.operation.to_string(),
+                        origin: Point::wrap(pattern.origin),
+                        axis: pattern.axis,
+                        count: pattern.count,
+                        spacing: pattern.spacing,
+                        features: features.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_circular_pattern_step));
+                }
+                cad_workbench::Step::Mirror {
+                    name,
+                    mirror,
+                    features,
+                } => {
+                    let new_mirror_step = NewMirrorStep {
+                        name: name.to_owned(),
+                        plane: mirror.plane.to_string(),
+                        merge: mirror.merge,
+                        features: features.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_mirror_step));
+                }
+                cad_workbench::Step::Hole {
+                    name,
+                    hole,
+                    solid,
+                    face,
+                    sketch,
+                    points,
+                } => {
+                    let depth = match hole.depth {
+                        cad_workbench::HoleDepth::Blind(depth) => Some(depth),
+                        cad_workbench::HoleDepth::ThroughAll => None,
+                    };
+                    let new_hole_step = NewHoleStep {
+                        name: name.to_owned(),
+                        kind: hole.kind.to_string(),
+                        standard: hole.standard.to_string(),
+                        size: hole.size.to_owned(),
+                        depth,
+                        solid: solid.to_owned(),
+                        face: format!("{:?}", face),
+                        sketch: sketch.to_owned(),
+                        points: points.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_hole_step));
+                }
+                cad_workbench::Step::Draft {
+                    name,
+                    draft,
+                    solid,
+                    faces,
+                } => {
+                    let new_draft_step = NewDraftStep {
+                        name: name.to_owned(),
+                        angle: draft.angle,
+                        neutral: draft.neutral.to_string(),
+                        solid: solid.to_owned(),
+                        faces: faces.iter().map(|f| format!("{:?}", f)).collect(),
+                    };
+                    retval.push(&JsValue::from(new_draft_step));
+                }
+                cad_workbench::Step::Rib {
+                    name,
+                    rib,
+                    sketch,
+                    segments,
+                    solid,
+                } => {
+                    let new_rib_step = NewRibStep {
+                        name: name.to_owned(),
+                        thickness: rib.thickness,
+                        sketch: sketch.to_owned(),
+                        segments: segments.to_vec(),
+                        solid: solid.to_owned(),
+                    };
+                    retval.push(&JsValue::from(new_rib_step));
+                }
+                cad_workbench::Step::CosmeticThread {
+                    name,
+                    thread,
+                    solid,
+                    face,
+                } => {
+                    let new_cosmetic_thread_step = NewCosmeticThreadStep {
+                        name: name.to_owned(),
+                        standard: thread.standard.to_string(),
+                        size: thread.size.to_owned(),
+                        length: thread.length,
+                        solid: solid.to_owned(),
+                        face: *face,
+                    };
+                    retval.push(&JsValue::from(new_cosmetic_thread_step));
+                }
+                cad_workbench::Step::Thread {
+                    name,
+                    thread,
+                    solid,
+                    face,
+                } => {
+                    let new_thread_step = NewThreadStep {
+                        name: name.to_owned(),
+                        standard: thread.standard.to_string(),
+                        size: thread.size.to_owned(),
+                        length: thread.length,
+                        operation: thread.operation.to_string(),
+                        solid: solid.to_owned(),
+                        face: *face,
+                    };
+                    retval.push(&JsValue::from(new_thread_step));
+                }
+                cad_workbench::Step::Transform {
+                    name,
+                    transform,
+                    bodies,
+                } => {
+                    let (distance, angle) = match &transform.motion {
+                        cad_workbench::Motion::Translate { distance, .. } => {
+                            (Some(*distance), None)
+                        }
+                        cad_workbench::Motion::Rotate { angle, .. } => (None, Some(*angle)),
+                        cad_workbench::Motion::PointToPoint { .. } => (None, None),
+                    };
+                    let new_transform_step = NewTransformStep {
+                        name: name.to_owned(),
+                        motion: transform.motion.to_string(),
+                        distance,
+                        angle,
+                        copy: transform.copy,
+                        bodies: bodies.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_transform_step));
+                }
+                cad_workbench::Step::Split { name, plane, solid } => {
+                    let new_split_step = NewSplitStep {
+                        name: name.to_owned(),
+                        plane: plane.to_string(),
+                        solid: solid.to_owned(),
+                    };
+                    retval.push(&JsValue::from(new_split_step));
+                }
+                cad_workbench::Step::DeleteBody { name, bodies, keep } => {
+                    let new_delete_body_step = NewDeleteBodyStep {
+                        name: name.to_owned(),
+                        bodies: bodies.to_vec(),
+                        keep: *keep,
+                    };
+                    retval.push(&JsValue::from(new_delete_body_step));
+                }
+                cad_workbench::Step::BaseFlange {
+                    name,
+                    flange,
+                    sketch,
+                    face,
+                } => {
+                    let new_base_flange_step = NewBaseFlangeStep {
+                        name: name.to_owned(),
+                        thickness: flange.thickness,
+                        bend_radius: flange.bend_radius,
+                        k_factor: flange.k_factor,
+                        sketch: sketch.to_owned(),
+                        face: *face,
+                    };
+                    retval.push(&JsValue::from(new_base_flange_step));
+                }
+                cad_workbench::Step::EdgeFlange {
+                    name,
+                    flange,
+                    solid,
+                    edges,
+                } => {
+                    let new_edge_flange_step = NewEdgeFlangeStep {
+                        name: name.to_owned(),
+                        length: flange.length,
+                        angle: flange.angle,
+                        solid: solid.to_owned(),
+                        edges: edges.to_vec(),
+                    };
+                    retval.push(&JsValue::from(new_edge_flange_step));
+                }
             }
         }
         retval
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn rollback(&self) -> Option<usize> {
+        self.0.rollback
+    }
+
     #[wasm_bindgen]
     pub fn create_view(&self, max_steps: usize) -> WorkbenchView {
         let wbv = self.0.create_view(max_steps);
         WorkbenchView(wbv)
     }
+
+    #[wasm_bindgen]
+    pub fn view(&self) -> WorkbenchView {
+        WorkbenchView(self.0.view())
+    }
 }
 
 impl Workbench {
@@ -106,6 +439,23 @@ impl NewPlaneStep {
     }
 }
 
+#[wasm_bindgen]
+pub struct NewHelixStep {
+    name: String,
+    pub origin: Point,
+    pub radius: f64,
+    pub pitch: f64,
+    pub turns: f64,
+    pub taper: f64,
+}
+#[wasm_bindgen]
+impl NewHelixStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+}
+
 #[wasm_bindgen]
 pub struct NewSketchStep {
     name: String,
@@ -126,6 +476,9 @@ pub struct NewExtrudeStep {
     operation: String,
     faces: Vec<usize>,
     sketch: String,
+    pub thickness: Option<f64>,
+    side: Option<String>,
+    segments: Vec<usize>,
 }
 #[wasm_bindgen]
 impl NewExtrudeStep {
@@ -157,64 +510,820 @@ impl NewExtrudeStep {
         }
         retval
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn side(&self) -> Option<String> {
+        self.side.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn segments(&self) -> Array {
+        let retval = Array::new();
+        for segment_index in self.segments.iter() {
+            retval.push(&JsValue::from(*segment_index));
+        }
+        retval
+    }
 }
 
 #[wasm_bindgen]
-pub struct WorkbenchView(cad_workbench::WorkbenchView);
+pub struct NewRevolveStep {
+    name: String,
+    origin: cadmium::common::Point,
+    axis: cadmium::common::Vector,
+    pub angle: f64,
+    operation: String,
+    pub thickness: Option<f64>,
+    side: Option<String>,
+    sketch: String,
+    faces: Vec<usize>,
+    segments: Vec<usize>,
+}
+#[wasm_bindgen]
+impl NewRevolveStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn origin(&self) -> Point {
+        Point::wrap(self.origin)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn axis(&self) -> crate::common::Vector {
+        crate::common::Vector::wrap(self.axis)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn side(&self) -> Option<String> {
+        self.side.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketch(&self) -> String {
+        self.sketch.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn faces(&self) -> Array {
+        let retval = Array::new();
+        for face_index in self.faces.iter() {
+            retval.push(&JsValue::from(*face_index));
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn segments(&self) -> Array {
+        let retval = Array::new();
+        for segment_index in self.segments.iter() {
+            retval.push(&JsValue::from(*segment_index));
+        }
+        retval
+    }
+}
 
 #[wasm_bindgen]
-impl WorkbenchView {
+pub struct NewSweepStep {
+    name: String,
+    orientation: String,
+    operation: String,
+    faces: Vec<usize>,
+    sketch: String,
+    path: String,
+    segments: Vec<usize>,
+}
+#[wasm_bindgen]
+impl NewSweepStep {
     #[wasm_bindgen(getter)]
-    pub fn points(&self) -> Array {
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn orientation(&self) -> String {
+        self.orientation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketch(&self) -> String {
+        self.sketch.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn path(&self) -> String {
+        self.path.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn faces(&self) -> Array {
         let retval = Array::new();
-        for (name, point) in self.0.points.iter() {
-            let js_map = js_sys::Map::new();
-            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
-            js_map.set(&JsValue::from("point"), &JsValue::from(Point::wrap(*point)));
-            retval.push(&js_map);
+        for face_index in self.faces.iter() {
+            retval.push(&JsValue::from(*face_index));
         }
         retval
     }
 
     #[wasm_bindgen(getter)]
-    pub fn planes(&self) -> Array {
+    pub fn segments(&self) -> Array {
         let retval = Array::new();
-        for (name, plane) in self.0.planes.iter() {
-            let js_map = js_sys::Map::new();
-            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
-            js_map.set(&JsValue::from("plane"), &JsValue::from(Plane::wrap(*plane)));
-            retval.push(&js_map);
+        for segment_index in self.segments.iter() {
+            retval.push(&JsValue::from(*segment_index));
         }
         retval
     }
+}
 
+#[wasm_bindgen]
+pub struct NewLoftStep {
+    name: String,
+    operation: String,
+    profiles: Vec<cad_workbench::Profile>,
+    guides: Vec<cad_workbench::Guide>,
+}
+#[wasm_bindgen]
+impl NewLoftStep {
     #[wasm_bindgen(getter)]
-    pub fn sketches(&self) -> Array {
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn profiles(&self) -> Array {
         let retval = Array::new();
-        for (name, sketch) in self.0.sketches.iter() {
+        for profile in self.profiles.iter() {
             let js_map = js_sys::Map::new();
-            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
             js_map.set(
                 &JsValue::from("sketch"),
-                &JsValue::from(sketch::SketchView::wrap(sketch)),
+                &JsValue::from(profile.sketch.to_owned()),
             );
+            js_map.set(&JsValue::from("face"), &JsValue::from(profile.face));
             retval.push(&js_map);
         }
         retval
     }
 
     #[wasm_bindgen(getter)]
-    pub fn solids(&self) -> Array {
+    pub fn guides(&self) -> Array {
         let retval = Array::new();
-        for (name, solid) in self.0.solids.iter() {
+        for guide in self.guides.iter() {
+            let segments = Array::new();
+            for segment_index in guide.segments.iter() {
+                segments.push(&JsValue::from(*segment_index));
+            }
             let js_map = js_sys::Map::new();
-            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
             js_map.set(
-                &JsValue::from("solid"),
-                &JsValue::from(Solid::wrap(solid.clone())),
+                &JsValue::from("sketch"),
+                &JsValue::from(guide.sketch.to_owned()),
             );
+            js_map.set(&JsValue::from("segments"), &segments);
             retval.push(&js_map);
         }
         retval
     }
 }
+
+#[wasm_bindgen]
+pub struct NewFilletStep {
+    name: String,
+    pub radius: f64,
+    edges: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewFilletStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn edges(&self) -> Array {
+        let retval = Array::new();
+        for edge in self.edges.iter() {
+            retval.push(&JsValue::from(edge.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewChamferStep {
+    name: String,
+    mode: String,
+    pub distance: f64,
+    // the second distance, or the angle for distance-angle chamfers
+    pub second: f64,
+    edges: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewChamferStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mode(&self) -> String {
+        self.mode.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn edges(&self) -> Array {
+        let retval = Array::new();
+        for edge in self.edges.iter() {
+            retval.push(&JsValue::from(edge.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewShellStep {
+    name: String,
+    pub thickness: f64,
+    direction: String,
+    solid: String,
+    faces: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewShellStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn direction(&self) -> String {
+        self.direction.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn faces(&self) -> Array {
+        let retval = Array::new();
+        for face in self.faces.iter() {
+            retval.push(&JsValue::from(face.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewLinearPatternStep {
+    name: String,
+    operation: String,
+    directions: Vec<cad_workbench::PatternDirection>,
+    features: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewLinearPatternStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn directions(&self) -> Array {
+        let retval = Array::new();
+        for direction in self.directions.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(
+                &JsValue::from("direction"),
+                &JsValue::from(crate::common::Vector::wrap(direction.direction)),
+            );
+            js_map.set(&JsValue::from("count"), &JsValue::from(direction.count));
+            js_map.set(&JsValue::from("spacing"), &JsValue::from(direction.spacing));
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn features(&self) -> Array {
+        let retval = Array::new();
+        for feature in self.features.iter() {
+            retval.push(&JsValue::from(feature.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewCircularPatternStep {
+    name: String,
+    operation: String,
+    pub origin: Point,
+    axis: cadmium::common::Vector,
+    pub count: usize,
+    pub spacing: f64,
+    features: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewCircularPatternStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn axis(&self) -> crate::common::Vector {
+        crate::common::Vector::wrap(self.axis)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn features(&self) -> Array {
+        let retval = Array::new();
+        for feature in self.features.iter() {
+            retval.push(&JsValue::from(feature.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewMirrorStep {
+    name: String,
+    plane: String,
+    pub merge: bool,
+    features: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewMirrorStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn plane(&self) -> String {
+        self.plane.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn features(&self) -> Array {
+        let retval = Array::new();
+        for feature in self.features.iter() {
+            retval.push(&JsValue::from(feature.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewHoleStep {
+    name: String,
+    kind: String,
+    standard: String,
+    size: String,
+    // None for holes that go through everything
+    pub depth: Option<f64>,
+    solid: String,
+    face: String,
+    sketch: String,
+    points: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewHoleStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn standard(&self) -> String {
+        self.standard.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> String {
+        self.size.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn face(&self) -> String {
+        self.face.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketch(&self) -> String {
+        self.sketch.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Array {
+        let retval = Array::new();
+        for point in self.points.iter() {
+            retval.push(&JsValue::from(point.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewDraftStep {
+    name: String,
+    pub angle: f64,
+    neutral: String,
+    solid: String,
+    faces: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewDraftStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn neutral(&self) -> String {
+        self.neutral.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn faces(&self) -> Array {
+        let retval = Array::new();
+        for face in self.faces.iter() {
+            retval.push(&JsValue::from(face.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewRibStep {
+    name: String,
+    pub thickness: f64,
+    sketch: String,
+    segments: Vec<usize>,
+    solid: String,
+}
+#[wasm_bindgen]
+impl NewRibStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketch(&self) -> String {
+        self.sketch.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn segments(&self) -> Array {
+        let retval = Array::new();
+        for segment_index in self.segments.iter() {
+            retval.push(&JsValue::from(*segment_index));
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewCosmeticThreadStep {
+    name: String,
+    standard: String,
+    size: String,
+    pub length: Option<f64>,
+    solid: String,
+    pub face: usize,
+}
+#[wasm_bindgen]
+impl NewCosmeticThreadStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn standard(&self) -> String {
+        self.standard.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> String {
+        self.size.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewThreadStep {
+    name: String,
+    standard: String,
+    size: String,
+    pub length: Option<f64>,
+    operation: String,
+    solid: String,
+    pub face: usize,
+}
+#[wasm_bindgen]
+impl NewThreadStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn standard(&self) -> String {
+        self.standard.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> String {
+        self.size.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn operation(&self) -> String {
+        self.operation.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewTransformStep {
+    name: String,
+    motion: String,
+    pub distance: Option<f64>,
+    pub angle: Option<f64>,
+    pub copy: bool,
+    bodies: Vec<String>,
+}
+#[wasm_bindgen]
+impl NewTransformStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn motion(&self) -> String {
+        self.motion.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bodies(&self) -> Array {
+        let retval = Array::new();
+        for body in self.bodies.iter() {
+            retval.push(&JsValue::from(body.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewSplitStep {
+    name: String,
+    plane: String,
+    solid: String,
+}
+#[wasm_bindgen]
+impl NewSplitStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn plane(&self) -> String {
+        self.plane.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewDeleteBodyStep {
+    name: String,
+    bodies: Vec<String>,
+    pub keep: bool,
+}
+#[wasm_bindgen]
+impl NewDeleteBodyStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bodies(&self) -> Array {
+        let retval = Array::new();
+        for body in self.bodies.iter() {
+            retval.push(&JsValue::from(body.to_owned()));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewBaseFlangeStep {
+    name: String,
+    pub thickness: f64,
+    pub bend_radius: f64,
+    pub k_factor: f64,
+    sketch: String,
+    pub face: usize,
+}
+#[wasm_bindgen]
+impl NewBaseFlangeStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketch(&self) -> String {
+        self.sketch.to_owned()
+    }
+}
+
+#[wasm_bindgen]
+pub struct NewEdgeFlangeStep {
+    name: String,
+    pub length: f64,
+    pub angle: f64,
+    solid: String,
+    edges: Vec<usize>,
+}
+#[wasm_bindgen]
+impl NewEdgeFlangeStep {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solid(&self) -> String {
+        self.solid.to_owned()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn edges(&self) -> Array {
+        let retval = Array::new();
+        for edge in self.edges.iter() {
+            retval.push(&JsValue::from(*edge));
+        }
+        retval
+    }
+}
+
+#[wasm_bindgen]
+pub struct WorkbenchView(cad_workbench::WorkbenchView);
+
+#[wasm_bindgen]
+impl WorkbenchView {
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Array {
+        let retval = Array::new();
+        for (name, point) in self.0.points.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
+            js_map.set(&JsValue::from("point"), &JsValue::from(Point::wrap(*point)));
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn planes(&self) -> Array {
+        let retval = Array::new();
+        for (name, plane) in self.0.planes.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
+            js_map.set(&JsValue::from("plane"), &JsValue::from(Plane::wrap(*plane)));
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> Array {
+        let retval = Array::new();
+        for diagnostic in self.0.diagnostics.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(
+                &JsValue::from("step"),
+                &JsValue::from(diagnostic.step.to_owned()),
+            );
+            js_map.set(
+                &JsValue::from("message"),
+                &JsValue::from(diagnostic.error.to_string()),
+            );
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sketches(&self) -> Array {
+        let retval = Array::new();
+        for (name, sketch) in self.0.sketches.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
+            js_map.set(
+                &JsValue::from("sketch"),
+                &JsValue::from(sketch::SketchView::wrap(sketch)),
+            );
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn solids(&self) -> Array {
+        let retval = Array::new();
+        for (name, solid) in self.0.solids.iter() {
+            let js_map = js_sys::Map::new();
+            js_map.set(&JsValue::from("name"), &JsValue::from(name.to_owned()));
+            js_map.set(
+                &JsValue::from("solid"),
+                &JsValue::from(Solid::wrap(solid.clone())),
+            );
+            retval.push(&js_map);
+        }
+        retval
+    }
+
+    #[wasm_bindgen]
+    pub fn flat_pattern_dxf(&self, solid: &str) -> Option<String> {
+        self.0.flat_pattern(solid).ok().map(|flat| flat.to_dxf())
+    }
+
+    #[wasm_bindgen]
+    pub fn face_names(&self, solid: &str) -> Array {
+        // one entry per face of the solid, null where a face has no name
+        let retval = Array::new();
+        if let Some(solid) = self.0.solids.get(solid) {
+            for name in solid.face_names() {
+                match name {
+                    Some(name) => retval.push(&JsValue::from(name.to_string())),
+                    None => retval.push(&JsValue::NULL),
+                };
+            }
+        }
+        retval
+    }
+}
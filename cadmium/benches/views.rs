@@ -0,0 +1,114 @@
+// Times rebuilding the view of a workbench of a hundred steps, from scratch
+// and after an edit. The steps form one chain, each link a copy of the body
+// before it that is then filleted, so an edit to the first step carries all
+// the way down while one to the last step reruns only itself.
+//
+//     cargo bench --bench views
+
+use cadmium::common::Vector;
+use cadmium::sketch::{Point, Segment, Sketch};
+use cadmium::workbench::{BodyTransform, EdgeId, Motion, Operation, Workbench};
+use std::time::{Duration, Instant};
+
+const LINKS: usize = 47;
+
+fn build() -> Workbench {
+    // a block, then a row of copies, each moved along from the one before
+    // and given a fillet of its own
+    let mut wb = Workbench::new("bench");
+    let mut sketch = Sketch::new();
+    sketch.add_segments(Segment::link(
+        vec![
+            Point::new(0.0, 0.0, "A"),
+            Point::new(20.0, 0.0, "B"),
+            Point::new(20.0, 10.0, "C"),
+            Point::new(0.0, 10.0, "D"),
+        ],
+        true,
+    ));
+    wb.add_sketch("sketch0", sketch, "Top");
+    wb.add_extrusion("ext0", "sketch0", 5.0, vec![0], Operation::New);
+
+    let mut previous = "ext0_0".to_owned();
+    for i in 1..=LINKS {
+        let copy = format!("copy{}", i);
+        wb.add_transform(
+            &copy,
+            vec![previous],
+            BodyTransform::new(
+                Motion::Translate {
+                    direction: Vector::new(1.0, 0.0, 0.0),
+                    distance: 30.0,
+                },
+                true,
+            ),
+        );
+        previous = format!("{}_0", copy);
+        wb.add_fillet(
+            &format!("fillet{}", i),
+            vec![EdgeId::new(&previous, 0, i % 4)],
+            2.0,
+        );
+    }
+    wb
+}
+
+fn time(runs: u32, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..runs {
+        f();
+    }
+    start.elapsed() / runs
+}
+
+fn meshes(wb: &Workbench) -> usize {
+    let view = wb.create_view(usize::MAX);
+    view.solids
+        .values()
+        .map(|solid| solid.get_mesh().indices.len())
+        .sum()
+}
+
+fn main() {
+    let runs = 5;
+    let steps = build().steps.len();
+    println!("{} steps in one chain, mean of {} runs", steps, runs);
+
+    let from_scratch = time(runs, || {
+        meshes(&build());
+    });
+    println!("{:<32}{:>12.2?}", "from scratch", from_scratch);
+
+    let wb = build();
+    meshes(&wb);
+    let unchanged = time(runs, || {
+        meshes(&wb);
+    });
+    println!("{:<32}{:>12.2?}", "unchanged", unchanged);
+
+    for (label, step, parameter, start) in [
+        ("edit the first step", "ext0".to_owned(), "depth", 5.0),
+        (
+            "edit the last step",
+            format!("fillet{}", LINKS),
+            "radius",
+            2.0,
+        ),
+    ] {
+        let mut wb = build();
+        meshes(&wb);
+        let mut value = start;
+        let edited = time(runs, || {
+            value += 0.1;
+            wb.set_step_parameters(&step, vec![parameter.to_owned()], vec![value])
+                .unwrap();
+            meshes(&wb);
+        });
+        println!(
+            "{:<32}{:>12.2?}  ({:.0}x)",
+            label,
+            edited,
+            from_scratch.as_secs_f64() / edited.as_secs_f64()
+        );
+    }
+}
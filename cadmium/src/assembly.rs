@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 // This is just a placeholder for now
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Assembly {
     pub age: i64,
 }
@@ -1,10 +1,17 @@
 // #![allow(unused_variables)]
 // #![allow(unused_imports)]
 
+use crate::sheet_metal::{Flange, SheetMetal};
 use crate::sketch::Point as SketchPoint;
+use crate::units::LengthUnit;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
 use truck_meshalgo::prelude::*;
-use truck_modeling::{builder, Edge, Face, Point3, Vector3, Vertex, Wire};
+use truck_modeling::{
+    builder, BSplineCurve, BSplineSurface, Curve, Edge, Face, KnotVec, Matrix4, Point3, Rad, Shell,
+    ShellCondition, Surface, Vector3, Vector4, Vertex, Wire,
+};
 use truck_stepio::out;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -226,6 +233,12 @@ impl LineFace {
         self.interiors.push(r);
     }
 
+    pub fn rings(&self) -> Vec<&LineRing> {
+        let mut rings = vec![&self.exterior];
+        rings.extend(self.interiors.iter());
+        rings
+    }
+
     pub fn to_face(&self) -> Face {
         let mut wires: Vec<Wire> = vec![self.exterior.to_wire()];
         for r in &self.interiors {
@@ -239,9 +252,246 @@ impl LineFace {
         // First we need to build a truck representation of this face.
         let face = self.to_face();
         let truck_solid = builder::tsweep(&face, direction.scale(depth).to_vector3());
-        let solid = Solid::new(truck_solid);
+        let mut solid = Solid::new(truck_solid);
+        solid.prism = Some(Prism::new(self.clone(), direction, depth));
         solid
     }
+
+    pub fn rsweep(&self, origin: Point, axis: Vector, angle: f64) -> Solid {
+        // Revolves the face about an axis; a full turn or more closes up on itself
+        let face = self.to_face();
+        let mut truck_solid = builder::rsweep(
+            &face,
+            origin.to_point3(),
+            axis.normalize().to_vector3(),
+            Rad(angle),
+        );
+
+        // truck leaves the solid inside out when the face points against the way
+        // it turns
+        let points: Vec<Point> = self.exterior.segments.iter().map(|s| s.start).collect();
+        let middle = points
+            .iter()
+            .fold(Vector::new(0.0, 0.0, 0.0), |sum, p| sum.add(p.to_vector()))
+            .scale(1.0 / points.len() as f64);
+        let motion = axis.cross(middle.subtract(origin.to_vector())).scale(angle);
+        if motion.dot(newell_normal(&points)) < 0.0 {
+            truck_solid.not();
+        }
+        Solid::new(truck_solid)
+    }
+
+    pub fn sweep(&self, path: &[PathSegment], follow_path: bool) -> Result<Solid, String> {
+        // The profile is moved along the path a step at a time, arcs in short
+        // steps, and each move joins its outline to where it lands. Every
+        // outline is shared by the faces either side of it, so the whole sweep
+        // closes up into one solid, holes and all.
+        let start = self.to_face();
+        // the profile as carried along, square to the path if it follows it
+        let mut profile = start.clone();
+        // the outline the last move ended on
+        let mut outline = start.boundaries();
+        let mut faces: Vec<Face> = vec![start.inverse()];
+
+        for (i, segment) in path.iter().enumerate() {
+            if i > 0 {
+                let prior = path[i - 1].end_direction();
+                let next = segment.start_direction();
+                let axis = prior.cross(next);
+                if axis.length() <= 1e-9 && prior.dot(next) < 0.0 {
+                    return Err("The path doubles back on itself".to_string());
+                }
+                if follow_path && axis.length() > 1e-9 {
+                    // turn the profile at the corner so it faces along the new segment
+                    let angle = prior.dot(next).clamp(-1.0, 1.0).acos();
+                    profile = builder::rotated(
+                        &profile,
+                        segment.get_start().to_point3(),
+                        axis.normalize().to_vector3(),
+                        Rad(angle),
+                    );
+                }
+            }
+
+            let moves: Vec<Matrix4> = match segment {
+                PathSegment::Line { start, end } => {
+                    let offset = end.to_vector().subtract(start.to_vector());
+                    vec![Matrix4::from_translation(offset.to_vector3())]
+                }
+                PathSegment::Arc {
+                    center,
+                    axis,
+                    angle,
+                    ..
+                } if follow_path => {
+                    let step = Matrix4::from_translation(center.to_vector().to_vector3())
+                        * Matrix4::from_axis_angle(
+                            axis.normalize().to_vector3(),
+                            Rad(*angle / ARC_CHORDS as f64),
+                        )
+                        * Matrix4::from_translation(-center.to_vector().to_vector3());
+                    vec![step; ARC_CHORDS]
+                }
+                PathSegment::Arc { .. } => {
+                    // keeping the profile's orientation fixed means translating it along
+                    // the arc, which we approximate with a run of short chords
+                    segment
+                        .sample(ARC_CHORDS)
+                        .windows(2)
+                        .map(|chord| {
+                            let offset = chord[1].to_vector().subtract(chord[0].to_vector());
+                            Matrix4::from_translation(offset.to_vector3())
+                        })
+                        .collect()
+                }
+            };
+
+            let last = moves.len() - 1;
+            for (k, step) in moves.into_iter().enumerate() {
+                profile = builder::transformed(&profile, step);
+                let along = segment.end_direction();
+                let landing = match path.get(i + 1) {
+                    Some(next)
+                        if follow_path
+                            && k == last
+                            && along.cross(next.start_direction()).length() > 1e-9 =>
+                    {
+                        // a sharp corner is mitred, on the plane halfway between
+                        // the directions either side of it
+                        let normal = along.add(next.start_direction()).normalize();
+                        let mitre = project_onto_plane(along, segment.get_end(), normal);
+                        builder::transformed(&profile, mitre).boundaries()
+                    }
+                    _ => profile.boundaries(),
+                };
+                for (from, to) in outline.iter().zip(landing.iter()) {
+                    let shell = builder::try_wire_homotopy(from, to)
+                        .map_err(|e| format!("Could not sweep the profile: {}", e))?;
+                    faces.extend(shell);
+                }
+                outline = landing;
+            }
+        }
+        faces.push(profile);
+
+        let mut truck_solid = truck_modeling::Solid::try_new(vec![Shell::from(faces)])
+            .map_err(|e| format!("The sweep doesn't close up into a solid: {}", e))?;
+        // truck leaves the solid inside out when the face points against the path
+        let points: Vec<Point> = self.exterior.segments.iter().map(|s| s.start).collect();
+        let heading = path.first().map(|s| s.start_direction());
+        if heading.is_some_and(|h| h.dot(newell_normal(&points)) < 0.0) {
+            truck_solid.not();
+        }
+        Ok(Solid::new(truck_solid))
+    }
+}
+
+fn project_onto_plane(along: Vector, origin: Point, normal: Vector) -> Matrix4 {
+    // slides each point p along a direction onto the plane, to
+    // p + along * (origin - p).normal / along.normal
+    let (d, n) = (along.to_vector3(), normal.to_vector3());
+    let depth = d.dot(n);
+    let shear = |c: f64| (d * (-c / depth)).extend(0.0);
+    Matrix4::from_cols(
+        Vector4::unit_x() + shear(n.x),
+        Vector4::unit_y() + shear(n.y),
+        Vector4::unit_z() + shear(n.z),
+        (d * (origin.to_point3().to_vec().dot(n) / depth)).extend(1.0),
+    )
+}
+
+const ARC_CHORDS: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum PathSegment {
+    Line {
+        start: Point,
+        end: Point,
+    },
+    Arc {
+        start: Point,
+        end: Point,
+        center: Point,
+        axis: Vector,
+        angle: f64,
+    },
+}
+
+impl PathSegment {
+    pub fn get_start(&self) -> Point {
+        match self {
+            PathSegment::Line { start, .. } => *start,
+            PathSegment::Arc { start, .. } => *start,
+        }
+    }
+
+    pub fn get_end(&self) -> Point {
+        match self {
+            PathSegment::Line { end, .. } => *end,
+            PathSegment::Arc { end, .. } => *end,
+        }
+    }
+
+    pub fn start_direction(&self) -> Vector {
+        match self {
+            PathSegment::Line { start, end } => {
+                end.to_vector().subtract(start.to_vector()).normalize()
+            }
+            PathSegment::Arc {
+                start,
+                center,
+                axis,
+                angle,
+                ..
+            } => {
+                let radial = start.to_vector().subtract(center.to_vector());
+                axis.cross(radial).scale(angle.signum()).normalize()
+            }
+        }
+    }
+
+    pub fn end_direction(&self) -> Vector {
+        match self {
+            PathSegment::Line { .. } => self.start_direction(),
+            PathSegment::Arc {
+                end,
+                center,
+                axis,
+                angle,
+                ..
+            } => {
+                let radial = end.to_vector().subtract(center.to_vector());
+                axis.cross(radial).scale(angle.signum()).normalize()
+            }
+        }
+    }
+
+    pub fn sample(&self, n: usize) -> Vec<Point> {
+        match self {
+            PathSegment::Line { start, end } => vec![*start, *end],
+            PathSegment::Arc {
+                start,
+                center,
+                axis,
+                angle,
+                ..
+            } => {
+                // Rodrigues' rotation of the radius vector about the axis
+                let radial = start.to_vector().subtract(center.to_vector());
+                let k = axis.normalize();
+                (0..=n)
+                    .map(|i| {
+                        let theta = angle * i as f64 / n as f64;
+                        let rotated = radial
+                            .scale(theta.cos())
+                            .add(k.cross(radial).scale(theta.sin()))
+                            .add(k.scale(k.dot(radial) * (1.0 - theta.cos())));
+                        center.add_vec(rotated)
+                    })
+                    .collect()
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -280,6 +530,238 @@ impl LineRing {
         let wire = Wire::from_iter(truck_edges.into_iter());
         wire
     }
+
+    pub fn points(&self) -> Vec<Point> {
+        self.segments.iter().map(|s| s.start).collect()
+    }
+}
+
+pub fn centroid(points: &[Point]) -> Point {
+    let sum = points
+        .iter()
+        .fold(Vector::new(0.0, 0.0, 0.0), |acc, p| acc.add(p.to_vector()));
+    sum.scale(1.0 / points.len() as f64).to_point()
+}
+
+pub fn newell_normal(points: &[Point]) -> Vector {
+    // area-weighted normal of a closed polygon, valid even if it is slightly non-planar
+    let mut normal = Vector::new(0.0, 0.0, 0.0);
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        normal = normal.add(Vector::new(
+            (a.y - b.y) * (a.z + b.z),
+            (a.z - b.z) * (a.x + b.x),
+            (a.x - b.x) * (a.y + b.y),
+        ));
+    }
+    normal
+}
+
+pub fn path_polyline(path: &[PathSegment]) -> Vec<Point> {
+    // the path's vertices end to end, with arcs broken into chords
+    let mut polyline: Vec<Point> = vec![];
+    for segment in path.iter() {
+        let points = segment.sample(ARC_CHORDS);
+        let skip = if polyline.is_empty() { 0 } else { 1 };
+        polyline.extend(points.into_iter().skip(skip));
+    }
+    polyline
+}
+
+pub fn sample_path(path: &[PathSegment], n: usize) -> Vec<Point> {
+    // n + 1 points spaced evenly by arc length along the whole path
+    let polyline = path_polyline(path);
+
+    let mut lengths: Vec<f64> = vec![0.0];
+    for pair in polyline.windows(2) {
+        let step = pair[1].to_vector().subtract(pair[0].to_vector()).length();
+        lengths.push(lengths.last().unwrap() + step);
+    }
+    let total = *lengths.last().unwrap();
+
+    (0..=n)
+        .map(|i| {
+            let target = total * i as f64 / n as f64;
+            let j = lengths
+                .windows(2)
+                .position(|w| target <= w[1])
+                .unwrap_or(lengths.len() - 2);
+            let span = lengths[j + 1] - lengths[j];
+            let t = if span > 0.0 {
+                (target - lengths[j]) / span
+            } else {
+                0.0
+            };
+            lerp(polyline[j], polyline[j + 1], t)
+        })
+        .collect()
+}
+
+fn lerp(a: Point, b: Point, t: f64) -> Point {
+    a.add_vec(b.to_vector().subtract(a.to_vector()).scale(t))
+}
+
+fn subdivide_to(points: &[Point], n: usize) -> Vec<Point> {
+    // split the longest edge in half until the ring has n vertices
+    let mut points = points.to_vec();
+    while points.len() < n {
+        let longest = (0..points.len())
+            .max_by(|&i, &j| {
+                let len = |k: usize| {
+                    let next = points[(k + 1) % points.len()];
+                    next.to_vector().subtract(points[k].to_vector()).length()
+                };
+                len(i).partial_cmp(&len(j)).unwrap()
+            })
+            .unwrap();
+        let next = points[(longest + 1) % points.len()];
+        points.insert(longest + 1, lerp(points[longest], next, 0.5));
+    }
+    points
+}
+
+fn align_to(reference: &[Point], points: &[Point]) -> Vec<Point> {
+    // rotate the starting vertex so corresponding vertices sit as close as possible,
+    // comparing shapes about their centroids so offset sections still line up
+    let reference_center = centroid(reference).to_vector();
+    let center = centroid(points).to_vector();
+    let n = points.len();
+    let cost = |shift: usize| -> f64 {
+        (0..n)
+            .map(|i| {
+                let a = reference[i].to_vector().subtract(reference_center);
+                let b = points[(i + shift) % n].to_vector().subtract(center);
+                a.subtract(b).length_squared()
+            })
+            .sum()
+    };
+    let best = (0..n)
+        .min_by(|&i, &j| cost(i).partial_cmp(&cost(j)).unwrap())
+        .unwrap();
+    (0..n).map(|i| points[(i + best) % n]).collect()
+}
+
+pub fn match_sections(rings: &[LineRing]) -> Result<Vec<Vec<Point>>, String> {
+    // Brings every ring to the same vertex count, winding and starting vertex
+    // so that section i can be skinned onto section i + 1 edge for edge
+    let mut sections: Vec<Vec<Point>> = rings.iter().map(|r| r.points()).collect();
+    let (Some(first), Some(last)) = (sections.first(), sections.last()) else {
+        return Err("There are no sections to match".to_owned());
+    };
+    let n = sections.iter().map(|s| s.len()).max().unwrap_or(0);
+    let first = centroid(first);
+    let last = centroid(last);
+    let direction = last.to_vector().subtract(first.to_vector());
+
+    for section in sections.iter_mut() {
+        if newell_normal(section).dot(direction) < 0.0 {
+            section.reverse();
+        }
+        *section = subdivide_to(section, n);
+    }
+    for i in 1..sections.len() {
+        let aligned = align_to(&sections[i - 1], &sections[i]);
+        sections[i] = aligned;
+    }
+    Ok(sections)
+}
+
+const GUIDE_SAMPLES: usize = 64;
+
+fn point_along(samples: &[Point], t: f64) -> Point {
+    let scaled = t.clamp(0.0, 1.0) * (samples.len() - 1) as f64;
+    let i = (scaled.floor() as usize).min(samples.len() - 2);
+    lerp(samples[i], samples[i + 1], scaled - i as f64)
+}
+
+pub fn guided_sections(
+    sections: &[Vec<Point>],
+    guides: &[Vec<PathSegment>],
+    steps_per_span: usize,
+) -> Vec<Vec<Point>> {
+    // Inserts intermediate sections between each pair of profiles. Each one is the
+    // straight blend of its neighbours, pushed off that straight line by however
+    // far the guide curves bow away from it.
+    let guide_samples: Vec<Vec<Point>> = guides
+        .iter()
+        .map(|g| sample_path(g, GUIDE_SAMPLES))
+        .collect();
+
+    let centers: Vec<Point> = sections.iter().map(|s| centroid(s)).collect();
+    let mut stations: Vec<f64> = vec![0.0];
+    for pair in centers.windows(2) {
+        let step = pair[1].to_vector().subtract(pair[0].to_vector()).length();
+        stations.push(stations.last().unwrap() + step);
+    }
+    let total = *stations.last().unwrap();
+    let stations: Vec<f64> = stations.iter().map(|s| s / total).collect();
+
+    let mut result: Vec<Vec<Point>> = vec![sections[0].clone()];
+    for k in 0..sections.len() - 1 {
+        for step in 1..steps_per_span {
+            let s = step as f64 / steps_per_span as f64;
+            let t = stations[k] + s * (stations[k + 1] - stations[k]);
+
+            let mut offset = Vector::new(0.0, 0.0, 0.0);
+            for samples in guide_samples.iter() {
+                let on_guide = point_along(samples, t);
+                let chord = lerp(
+                    point_along(samples, stations[k]),
+                    point_along(samples, stations[k + 1]),
+                    s,
+                );
+                offset = offset.add(on_guide.to_vector().subtract(chord.to_vector()));
+            }
+            offset = offset.scale(1.0 / guide_samples.len() as f64);
+
+            let section = sections[k]
+                .iter()
+                .zip(sections[k + 1].iter())
+                .map(|(a, b)| lerp(*a, *b, s).add_vec(offset))
+                .collect();
+            result.push(section);
+        }
+        result.push(sections[k + 1].clone());
+    }
+    result
+}
+
+pub fn loft(sections: &[Vec<Point>]) -> Result<Solid, String> {
+    if sections.len() < 2 {
+        return Err("A loft needs at least two profiles".to_owned());
+    }
+
+    let wires: Vec<Wire> = sections
+        .iter()
+        .map(|points| {
+            let ring = LineRing {
+                segments: points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| LineSegment::new(*p, points[(i + 1) % points.len()]))
+                    .collect(),
+            };
+            ring.to_wire()
+        })
+        .collect();
+
+    let mut faces: Vec<Face> = vec![];
+    let bottom = builder::try_attach_plane(&[wires[0].inverse()])
+        .map_err(|e| format!("First profile is not planar: {}", e))?;
+    faces.push(bottom);
+    for pair in wires.windows(2) {
+        let sides = builder::try_wire_homotopy(&pair[0], &pair[1])
+            .map_err(|e| format!("Could not skin between profiles: {}", e))?;
+        faces.extend(sides);
+    }
+    let top = builder::try_attach_plane(&[wires.last().unwrap().clone()])
+        .map_err(|e| format!("Last profile is not planar: {}", e))?;
+    faces.push(top);
+
+    let shell: Shell = faces.into();
+    let truck_solid = truck_modeling::Solid::try_new(vec![shell])
+        .map_err(|e| format!("Loft did not produce a closed solid: {}", e))?;
+    Ok(Solid::new(truck_solid))
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -477,12 +959,1946 @@ pub struct Mesh {
     pub indices: Vec<usize>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Corner {
+    Sharp,
+    Fillet(f64),
+    // setbacks along the incoming and outgoing edges
+    Chamfer(f64, f64),
+    // setback along the incoming edge and the angle the cut makes with it
+    ChamferAngle(f64, f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Prism {
+    // A solid made by extruding a planar face. Each lateral edge of the prism is
+    // swept from one corner of the face, so treating that corner in 2D and
+    // extruding again is how we round or cut the edge.
+    pub face: LineFace,
+    pub direction: Vector,
+    pub depth: f64,
+    pub corners: Vec<Vec<Corner>>,
+    pub holes: Vec<HoleCut>,
+    // per side face, like corners: ring then the vertex the face's edge starts at
+    pub drafts: Vec<Vec<Option<FaceDraft>>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct FaceDraft {
+    // radians; positive angles lean the face into the material as it moves
+    // along the extrusion away from the neutral plane
+    pub angle: f64,
+    // where the face keeps its original position, as a distance along the extrusion
+    pub neutral: f64,
+}
+
+impl Prism {
+    pub fn new(face: LineFace, direction: Vector, depth: f64) -> Self {
+        let corners = face
+            .rings()
+            .iter()
+            .map(|r| vec![Corner::Sharp; r.segments.len()])
+            .collect();
+        let drafts = face
+            .rings()
+            .iter()
+            .map(|r| vec![None; r.segments.len()])
+            .collect();
+        Prism {
+            face,
+            direction,
+            depth,
+            corners,
+            holes: vec![],
+            drafts,
+        }
+    }
+
+    pub fn lateral_edge(&self, ring: usize, vertex: usize) -> Option<LineSegment> {
+        let rings = self.face.rings();
+        let start = rings.get(ring)?.segments.get(vertex)?.start;
+        let end = start.add_vec(self.direction.scale(self.depth));
+        Some(LineSegment::new(start, end))
+    }
+
+    pub fn set_corner(&mut self, ring: usize, vertex: usize, corner: Corner) -> Result<(), String> {
+        let slot = self
+            .corners
+            .get_mut(ring)
+            .and_then(|r| r.get_mut(vertex))
+            .ok_or(format!("No edge at ring {} vertex {}", ring, vertex))?;
+        *slot = corner;
+        Ok(())
+    }
+
+    pub fn set_draft(
+        &mut self,
+        ring: usize,
+        vertex: usize,
+        draft: FaceDraft,
+    ) -> Result<(), String> {
+        let slot = self
+            .drafts
+            .get_mut(ring)
+            .and_then(|r| r.get_mut(vertex))
+            .ok_or(format!("No face at ring {} vertex {}", ring, vertex))?;
+        *slot = Some(draft);
+        Ok(())
+    }
+
+    fn section(&self, height: f64) -> Result<Vec<Vec<Point>>, String> {
+        // the profile where the plane at height along the extrusion cuts the side faces
+        let up = self.direction.normalize();
+        let lift = self.direction.scale(height);
+        let mut rings: Vec<Vec<Point>> = vec![];
+        for (i, ring) in self.face.rings().iter().enumerate() {
+            // a hole's interior is outside the material
+            let into_material = if i == 0 { 1.0 } else { -1.0 };
+            let points = ring.points();
+            let offsets: Vec<f64> = self.drafts[i]
+                .iter()
+                .map(|d| {
+                    d.map_or(0.0, |d| {
+                        into_material * (height - d.neutral) * d.angle.tan()
+                    })
+                })
+                .collect();
+            let moved = offset_ring(&points, up, &offsets)?;
+            if let Some(k) = collapsed_edge(&points, &moved) {
+                return Err(format!(
+                    "The draft pinches off the face at ring {} vertex {}",
+                    i, k
+                ));
+            }
+            rings.push(moved.iter().map(|p| p.add_vec(lift)).collect());
+        }
+        Ok(rings)
+    }
+
+    fn drafted_solid(&self) -> Result<truck_modeling::Solid, String> {
+        // the side faces are no longer parallel, so skin between the treated
+        // profiles at each end instead of sweeping one of them
+        let wires = |rings: Vec<Vec<Point>>| -> Result<Vec<Wire>, String> {
+            let mut wires: Vec<Wire> = vec![];
+            for (i, points) in rings.iter().enumerate() {
+                let wire = treated_wire(points, &self.corners[i])?;
+                wires.push(if i == 0 { wire } else { wire.inverse() });
+            }
+            Ok(wires)
+        };
+        let bottom = wires(self.section(0.0)?)?;
+        let top = wires(self.section(self.depth)?)?;
+
+        let inverted: Vec<Wire> = bottom.iter().map(|w| w.inverse()).collect();
+        let mut faces: Vec<Face> =
+            vec![builder::try_attach_plane(&inverted).map_err(|e| e.to_string())?];
+        for (b, t) in bottom.iter().zip(top.iter()) {
+            let sides = builder::try_wire_homotopy(b, t).map_err(|e| e.to_string())?;
+            faces.extend(sides);
+        }
+        faces.push(builder::try_attach_plane(&top).map_err(|e| e.to_string())?);
+        truck_modeling::Solid::try_new(vec![faces.into()])
+            .map_err(|e| format!("Drafted faces did not leave a closed solid: {}", e))
+    }
+
+    pub fn to_truck_solid(&self) -> Result<truck_modeling::Solid, String> {
+        let truck_solid = if self.drafts.iter().flatten().any(|d| d.is_some()) {
+            self.drafted_solid()?
+        } else {
+            let rings = self.face.rings();
+            let mut wires: Vec<Wire> = vec![];
+            for (i, ring) in rings.iter().enumerate() {
+                let wire = treated_wire(&ring.points(), &self.corners[i])?;
+                if i == 0 {
+                    wires.push(wire);
+                } else {
+                    wires.push(wire.inverse());
+                }
+            }
+            let face = builder::try_attach_plane(&wires).map_err(|e| e.to_string())?;
+            builder::tsweep(&face, self.direction.scale(self.depth).to_vector3())
+        };
+        for shell in truck_solid.boundaries() {
+            if shell.shell_condition() != ShellCondition::Closed {
+                return Err("Treated edges did not leave a closed solid".to_owned());
+            }
+        }
+        if self.holes.is_empty() {
+            return Ok(truck_solid);
+        }
+        self.drill(truck_solid)
+    }
+
+    fn drill(&self, solid: truck_modeling::Solid) -> Result<truck_modeling::Solid, String> {
+        // Each hole is a solid of revolution with its end caps knocked out. Its
+        // openings become holes in the prism's caps and its remaining faces are
+        // turned inside out to line the bore, just like the bottle example's neck.
+        let up = self.direction.normalize();
+        let height = self.direction.scale(self.depth).length();
+        let start = self.face.exterior.segments[0].start;
+        let mut outer = solid.into_boundaries().pop().unwrap();
+        let top = outer.len() - 1;
+        let mut linings: Vec<Face> = vec![];
+
+        for hole in self.holes.iter() {
+            let level = hole.entry.to_vector().subtract(start.to_vector()).dot(up);
+            let axis = hole.axis.normalize();
+            let (entry_cap, exit_cap) = if level.abs() < 1e-9 && axis.dot(up) > 1.0 - 1e-9 {
+                (0, top)
+            } else if (level - height).abs() < 1e-9 && axis.dot(up) < -1.0 + 1e-9 {
+                (top, 0)
+            } else {
+                return Err(
+                    "Holes must be drilled straight into an end cap of an extrusion, not its sides"
+                        .to_owned(),
+                );
+            };
+
+            let profile = hole.profile(height)?;
+            let (entry_face, exit_face, lining) = hole.tool(&profile);
+            outer[entry_cap].add_boundary(entry_face);
+            if let Some(exit_face) = exit_face {
+                outer[exit_cap].add_boundary(exit_face);
+            }
+            linings.extend(lining);
+        }
+
+        outer.extend(linings);
+        if outer.shell_condition() != ShellCondition::Closed {
+            return Err("Holes did not leave a closed solid".to_owned());
+        }
+        truck_modeling::Solid::try_new(vec![outer]).map_err(|e| e.to_string())
+    }
+
+    pub fn add_hole(&mut self, hole: HoleCut) -> Result<(), String> {
+        let up = self.direction.normalize();
+        let start = self.face.exterior.segments[0].start;
+        let e1 = self.face.exterior.segments[0]
+            .end
+            .to_vector()
+            .subtract(start.to_vector())
+            .normalize();
+        let e2 = up.cross(e1);
+        let flatten = |p: Point| {
+            let v = p.to_vector().subtract(start.to_vector());
+            geo::Coord {
+                x: v.dot(e1),
+                y: v.dot(e2),
+            }
+        };
+        let ring = |r: &LineRing| geo::LineString(r.points().into_iter().map(flatten).collect());
+        let profile = geo::Polygon::new(
+            ring(&self.face.exterior),
+            self.face.interiors.iter().map(ring).collect(),
+        );
+
+        // the widest part of the hole has to stay clear of the profile's edges
+        // and of every other hole
+        let center = flatten(hole.entry);
+        let radius = hole.outer_radius();
+        let circle: Vec<geo::Coord> = (0..48)
+            .map(|k| {
+                let angle = 2.0 * std::f64::consts::PI * k as f64 / 48.0;
+                geo::Coord {
+                    x: center.x + radius * 1.001 * angle.cos(),
+                    y: center.y + radius * 1.001 * angle.sin(),
+                }
+            })
+            .collect();
+        let circle = geo::Polygon::new(geo::LineString(circle), vec![]);
+        if !geo::Contains::contains(&profile, &circle) {
+            return Err("The hole does not fit inside the face".to_owned());
+        }
+        for other in self.holes.iter() {
+            let c = flatten(other.entry);
+            let gap = ((c.x - center.x).powi(2) + (c.y - center.y).powi(2)).sqrt();
+            if gap <= radius + other.outer_radius() {
+                return Err("The hole overlaps another hole".to_owned());
+            }
+        }
+
+        self.holes.push(hole);
+        Ok(())
+    }
+}
+
+fn treated_wire(points: &[Point], corners: &[Corner]) -> Result<Wire, String> {
+    let n = points.len();
+    let distance = |a: Point, b: Point| b.to_vector().subtract(a.to_vector()).length();
+
+    // where each corner starts and stops eating into its two neighbouring edges
+    let mut entries: Vec<Point> = vec![];
+    let mut exits: Vec<Point> = vec![];
+    let mut transits: Vec<Option<Point>> = vec![];
+    for k in 0..n {
+        let corner = points[k];
+        let prior = points[(k + n - 1) % n];
+        let next = points[(k + 1) % n];
+        let u = prior.to_vector().subtract(corner.to_vector()).normalize();
+        let v = next.to_vector().subtract(corner.to_vector()).normalize();
+        let theta = u.dot(v).clamp(-1.0, 1.0).acos();
+
+        match corners[k] {
+            Corner::Sharp => {
+                entries.push(corner);
+                exits.push(corner);
+                transits.push(None);
+            }
+            Corner::Fillet(radius) => {
+                if radius <= 0.0 {
+                    return Err(format!("Fillet radius must be positive, got {}", radius));
+                }
+                if theta > PI_NEARLY {
+                    return Err(format!("Vertex {} is not a corner", k));
+                }
+                let setback = radius / (theta / 2.0).tan();
+                let bisector = u.add(v).normalize();
+                let center = corner.add_vec(bisector.scale(radius / (theta / 2.0).sin()));
+                entries.push(corner.add_vec(u.scale(setback)));
+                exits.push(corner.add_vec(v.scale(setback)));
+                transits.push(Some(center.add_vec(bisector.scale(-radius))));
+            }
+            Corner::Chamfer(d1, d2) => {
+                if d1 <= 0.0 || d2 <= 0.0 {
+                    return Err(format!(
+                        "Chamfer distances must be positive, got {} and {}",
+                        d1, d2
+                    ));
+                }
+                entries.push(corner.add_vec(u.scale(d1)));
+                exits.push(corner.add_vec(v.scale(d2)));
+                transits.push(None);
+            }
+            Corner::ChamferAngle(d1, angle) => {
+                // law of sines in the triangle cut off the corner
+                let far_angle = std::f64::consts::PI - angle - theta;
+                if d1 <= 0.0 || angle <= 0.0 || far_angle <= 0.0 {
+                    return Err(format!(
+                        "Chamfer of {} at angle {} does not fit vertex {}",
+                        d1, angle, k
+                    ));
+                }
+                let d2 = d1 * angle.sin() / far_angle.sin();
+                entries.push(corner.add_vec(u.scale(d1)));
+                exits.push(corner.add_vec(v.scale(d2)));
+                transits.push(None);
+            }
+        }
+    }
+
+    for k in 0..n {
+        let next = (k + 1) % n;
+        let available = distance(points[k], points[next]);
+        let used = distance(points[k], exits[k]) + distance(points[next], entries[next]);
+        if used > available + 1e-9 {
+            return Err(format!(
+                "Edge from vertex {} to vertex {} is too short for the corners on it",
+                k, next
+            ));
+        }
+    }
+
+    let entry_vertices: Vec<Vertex> = entries.iter().map(|p| p.to_vertex()).collect();
+    let exit_vertices: Vec<Vertex> = (0..n)
+        .map(|k| match corners[k] {
+            Corner::Sharp => entry_vertices[k].clone(),
+            _ => exits[k].to_vertex(),
+        })
+        .collect();
+
+    let mut edges: Vec<Edge> = vec![];
+    for k in 0..n {
+        match (corners[k], transits[k]) {
+            (Corner::Sharp, _) => {}
+            (_, Some(transit)) => edges.push(builder::circle_arc(
+                &entry_vertices[k],
+                &exit_vertices[k],
+                transit.to_point3(),
+            )),
+            (_, None) => edges.push(builder::line(&entry_vertices[k], &exit_vertices[k])),
+        }
+        let next = (k + 1) % n;
+        if distance(exits[k], entries[next]) > 1e-9 {
+            edges.push(builder::line(&exit_vertices[k], &entry_vertices[next]));
+        }
+    }
+    Ok(Wire::from_iter(edges))
+}
+
+const PI_NEARLY: f64 = std::f64::consts::PI - 1e-6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PrismFace {
+    // the face lying on the sketch plane
+    Bottom,
+    // the face at the far end of the extrusion
+    Top,
+    // the face swept from the edge starting at the given ring and vertex
+    Side(usize, usize),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FaceName {
+    // the end of a feature lying on its sketch plane, and the far end
+    Start(String),
+    End(String),
+    // swept from a segment of the feature's sketch, by its index there
+    Side(String, usize),
+}
+
+impl fmt::Display for FaceName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaceName::Start(step) => write!(f, "{} start", step),
+            FaceName::End(step) => write!(f, "{} end", step),
+            FaceName::Side(step, segment) => write!(f, "{} side {}", step, segment),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Naming {
+    // the step that made the solid
+    pub step: String,
+    // the sketch segment behind each edge of the profile, laid out like the
+    // prism's corners. Profiles that weren't drawn directly name no sides.
+    pub segments: Vec<Vec<Option<usize>>>,
+    // the prism was rebuilt from the far end, as mirroring does
+    pub reversed: bool,
+}
+
+impl Naming {
+    pub fn new(step: &str, segments: Vec<Vec<Option<usize>>>) -> Self {
+        Naming {
+            step: step.to_owned(),
+            segments,
+            reversed: false,
+        }
+    }
+
+    pub fn name(&self, face: PrismFace) -> Option<FaceName> {
+        let step = self.step.to_owned();
+        match (face, self.reversed) {
+            (PrismFace::Bottom, false) | (PrismFace::Top, true) => Some(FaceName::Start(step)),
+            (PrismFace::Top, false) | (PrismFace::Bottom, true) => Some(FaceName::End(step)),
+            (PrismFace::Side(ring, vertex), _) => match self.segments.get(ring)?.get(vertex) {
+                Some(Some(segment)) => Some(FaceName::Side(step, segment)),
+                _ => None,
+            },
+        }
+    }
+}
+
+pub fn inset_ring(points: &[Point], normal: Vector, distance: f64) -> Result<Vec<Point>, String> {
+    // Moves every edge of a planar ring toward the ring's own interior by distance
+    // (away from it when negative), mitering the corners
+    let inset = offset_ring(points, normal, &vec![distance; points.len()])?;
+    if collapsed_edge(points, &inset).is_some() {
+        return Err(format!(
+            "Wall thickness {} is too large for this profile",
+            distance.abs()
+        ));
+    }
+    Ok(inset)
+}
+
+pub fn offset_ring(
+    points: &[Point],
+    normal: Vector,
+    offsets: &[f64],
+) -> Result<Vec<Point>, String> {
+    // Moves edge k of a planar ring toward the ring's interior by offsets[k] and
+    // puts each vertex where its two moved edges meet
+    let n = points.len();
+    let winding = newell_normal(points).dot(normal).signum();
+    let directions: Vec<Vector> = (0..n)
+        .map(|k| {
+            let next = points[(k + 1) % n];
+            next.to_vector().subtract(points[k].to_vector()).normalize()
+        })
+        .collect();
+
+    let mut moved: Vec<Point> = vec![];
+    for k in 0..n {
+        let before = (k + n - 1) % n;
+        let u = directions[before];
+        let v = directions[k];
+        let n1 = normal.cross(u).scale(winding);
+        let n2 = normal.cross(v).scale(winding);
+        let on_before = points[k].add_vec(n1.scale(offsets[before]));
+        let on_after = points[k].add_vec(n2.scale(offsets[k]));
+
+        let turn = u.cross(v).dot(normal);
+        if turn.abs() < 1e-9 {
+            // straight through, which only works if both edges moved together
+            if u.dot(v) < 0.0 || (offsets[before] - offsets[k]).abs() > 1e-12 {
+                return Err(format!("Vertex {} folds back on itself", k));
+            }
+            moved.push(on_before);
+            continue;
+        }
+        let gap = on_after.to_vector().subtract(on_before.to_vector());
+        let along = gap.cross(v).dot(normal) / turn;
+        moved.push(on_before.add_vec(u.scale(along)));
+    }
+    Ok(moved)
+}
+
+fn collapsed_edge(before: &[Point], after: &[Point]) -> Option<usize> {
+    // the first edge that shrank to nothing and came out pointing backwards
+    let n = before.len();
+    (0..n).find(|&k| {
+        let next = (k + 1) % n;
+        let old = before[next].to_vector().subtract(before[k].to_vector());
+        let new = after[next].to_vector().subtract(after[k].to_vector());
+        old.dot(new) <= 0.0
+    })
+}
+
+fn ring_contains(outer: &[Point], inner: &[Point], normal: Vector) -> bool {
+    // flatten both rings into the plane and let geo decide
+    let e1 = outer[1]
+        .to_vector()
+        .subtract(outer[0].to_vector())
+        .normalize();
+    let e2 = normal.cross(e1);
+    let flatten = |points: &[Point]| {
+        let coords: Vec<(f64, f64)> = points
+            .iter()
+            .map(|p| (p.to_vector().dot(e1), p.to_vector().dot(e2)))
+            .collect();
+        geo::Polygon::new(geo::LineString::from(coords), vec![])
+    };
+    geo::Contains::contains(&flatten(outer), &flatten(inner))
+}
+
+fn prism_shell(
+    rings: &[Vec<Point>],
+    direction: Vector,
+    start: f64,
+    end: f64,
+) -> Result<Shell, String> {
+    let offset = direction.scale(start);
+    let wires: Vec<Wire> = rings
+        .iter()
+        .enumerate()
+        .map(|(i, points)| {
+            let ring = LineRing {
+                segments: points
+                    .iter()
+                    .enumerate()
+                    .map(|(j, p)| {
+                        LineSegment::new(
+                            p.add_vec(offset),
+                            points[(j + 1) % points.len()].add_vec(offset),
+                        )
+                    })
+                    .collect(),
+            };
+            if i == 0 {
+                ring.to_wire()
+            } else {
+                ring.to_wire().inverse()
+            }
+        })
+        .collect();
+    let face = builder::try_attach_plane(&wires).map_err(|e| e.to_string())?;
+    let solid = builder::tsweep(&face, direction.scale(end - start).to_vector3());
+    Ok(solid.into_boundaries().pop().unwrap())
+}
+
+impl Prism {
+    pub fn shell(
+        &self,
+        thickness: f64,
+        outward: bool,
+        open: &[PrismFace],
+    ) -> Result<truck_modeling::Solid, String> {
+        if thickness <= 0.0 {
+            return Err(format!(
+                "Wall thickness must be positive, got {}",
+                thickness
+            ));
+        }
+        if self.corners.iter().flatten().any(|c| *c != Corner::Sharp) {
+            return Err("Shell the solid before filleting or chamfering its edges".to_owned());
+        }
+        if !self.holes.is_empty() {
+            return Err("Shell the solid before adding holes".to_owned());
+        }
+        if self.drafts.iter().flatten().any(|d| d.is_some()) {
+            return Err("Shell the solid before drafting its faces".to_owned());
+        }
+        if open.iter().any(|f| matches!(f, PrismFace::Side(_, _))) {
+            return Err(
+                "Only the end caps of an extruded solid can be opened, not its sides".to_owned(),
+            );
+        }
+        let open_bottom = open.contains(&PrismFace::Bottom);
+        let open_top = open.contains(&PrismFace::Top);
+        let rings: Vec<Vec<Point>> = self.face.rings().iter().map(|r| r.points()).collect();
+        if rings.len() > 1 && (open_bottom || open_top) {
+            return Err("Opening the caps of a profile with holes is not supported".to_owned());
+        }
+
+        // grow the outside of the material or eat into it, exterior and holes alike
+        let normal = self.direction.normalize();
+        let signed = if outward { -thickness } else { thickness };
+        let mut offset_rings: Vec<Vec<Point>> = vec![];
+        for (i, ring) in rings.iter().enumerate() {
+            let distance = if i == 0 { signed } else { -signed };
+            offset_rings.push(inset_ring(ring, normal, distance)?);
+        }
+        let (outer_rings, cavity_rings) = if outward {
+            (offset_rings, rings)
+        } else {
+            (rings, offset_rings)
+        };
+        for hole in cavity_rings.iter().skip(1) {
+            if !ring_contains(&cavity_rings[0], hole, normal) {
+                return Err(format!(
+                    "Wall thickness {} leaves no room around the holes in this profile",
+                    thickness
+                ));
+            }
+        }
+
+        let bottom_wall = if open_bottom { 0.0 } else { thickness };
+        let top_wall = if open_top { 0.0 } else { thickness };
+        let (outer_range, cavity_range) = if outward {
+            ((-bottom_wall, self.depth + top_wall), (0.0, self.depth))
+        } else {
+            ((0.0, self.depth), (bottom_wall, self.depth - top_wall))
+        };
+        if cavity_range.1 - cavity_range.0 <= 0.0 {
+            return Err(format!(
+                "Wall thickness {} is too large for an extrusion {} deep",
+                thickness, self.depth
+            ));
+        }
+
+        let mut outer = prism_shell(&outer_rings, normal, outer_range.0, outer_range.1)?;
+        let mut cavity = prism_shell(&cavity_rings, normal, cavity_range.0, cavity_range.1)?;
+
+        if !open_bottom && !open_top {
+            let cavity: Shell = cavity.face_iter().map(|f| f.inverse()).collect();
+            return truck_modeling::Solid::try_new(vec![outer, cavity]).map_err(|e| e.to_string());
+        }
+
+        // like the bottle example: each open cap of the cavity becomes a hole in the
+        // matching cap of the outer shell, and the rest of the cavity is turned inside out
+        if open_top {
+            let hat = cavity.pop().unwrap();
+            let wire = hat.into_boundaries()[0].inverse();
+            outer.last_mut().unwrap().add_boundary(wire);
+        }
+        if open_bottom {
+            let floor = cavity.remove(0);
+            let wire = floor.into_boundaries()[0].inverse();
+            outer[0].add_boundary(wire);
+        }
+        outer.extend(cavity.face_iter().map(|f| f.inverse()));
+        truck_modeling::Solid::try_new(vec![outer]).map_err(|e| e.to_string())
+    }
+}
+
+impl Prism {
+    pub fn face_plane(&self, face: PrismFace) -> Option<(Point, Vector)> {
+        // a point on the face and its outward normal
+        let rings = self.face.rings();
+        let start = rings[0].segments[0].start;
+        let up = self.direction.normalize();
+        match face {
+            PrismFace::Bottom => Some((start, up.negate())),
+            PrismFace::Top => Some((start.add_vec(up.scale(self.depth)), up)),
+            PrismFace::Side(ring, vertex) => {
+                let points = rings.get(ring)?.points();
+                let segment = rings[ring].segments.get(vertex)?;
+                let edge = segment.end.to_vector().subtract(segment.start.to_vector());
+                let mut winding = newell_normal(&points).dot(up).signum();
+                if ring > 0 {
+                    winding = -winding;
+                }
+                Some((segment.start, edge.cross(up).scale(winding).normalize()))
+            }
+        }
+    }
+
+    pub fn classify(&self, face: &Face) -> Option<PrismFace> {
+        // Which face of the prism a face of its truck solid is, judged by where
+        // it lies rather than by the order truck builds faces in, so that treated
+        // corners and holes don't throw the count off. Faces a treatment or a hole
+        // added aren't any of them.
+        let rings = self.face.rings();
+        let start = rings[0].segments[0].start;
+        let up = self.direction.normalize();
+        let top = self.direction.scale(self.depth).dot(up);
+        let boundary: Vec<Point> = face.boundaries()[0]
+            .vertex_iter()
+            .map(|v| Point::from_point3(v.get_point()))
+            .collect();
+        let level = |p: &Point| p.to_vector().subtract(start.to_vector()).dot(up);
+        if boundary.iter().all(|p| level(p).abs() < 1e-9) {
+            return Some(PrismFace::Bottom);
+        }
+        if boundary.iter().all(|p| (level(p) - top).abs() < 1e-9) {
+            return Some(PrismFace::Top);
+        }
+
+        let normal = newell_normal(&boundary);
+        if normal.length() < 1e-12 {
+            return None;
+        }
+        let normal = normal.normalize();
+        for (ring, points) in rings.iter().map(|r| r.points()).enumerate() {
+            let mut winding = newell_normal(&points).dot(up).signum();
+            if ring > 0 {
+                winding = -winding;
+            }
+            for (vertex, &a) in points.iter().enumerate() {
+                let b = points[(vertex + 1) % points.len()];
+                let run = b.to_vector().subtract(a.to_vector());
+                let along = run.normalize();
+                let outward = run.cross(up).scale(winding);
+                // a draft tilts the face about the edge where it crosses the
+                // neutral plane, but it still faces out square to the edge
+                let neutral = match self.drafts[ring].get(vertex) {
+                    Some(Some(draft)) => draft.neutral,
+                    _ => 0.0,
+                };
+                let pivot = a.add_vec(up.scale(neutral));
+                let offset = boundary[0].to_vector().subtract(pivot.to_vector());
+                if normal.dot(along).abs() > 1e-6
+                    || normal.dot(outward) <= 0.0
+                    || offset.dot(normal).abs() > 1e-6
+                {
+                    continue;
+                }
+                // and it has to cover some of the edge, not just its line
+                let spans: Vec<f64> = boundary
+                    .iter()
+                    .map(|p| p.to_vector().subtract(a.to_vector()).dot(along))
+                    .collect();
+                let low = spans.iter().cloned().fold(f64::MAX, f64::min);
+                let high = spans.iter().cloned().fold(f64::MIN, f64::max);
+                if low < run.length() - 1e-9 && high > 1e-9 {
+                    return Some(PrismFace::Side(ring, vertex));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn merge_mirror(&self, origin: Point, normal: Vector) -> Result<Prism, String> {
+        // Union of the prism and its mirror image, which is still a prism when the
+        // mirror plane runs along the extrusion or lies on one of its end caps
+        if !self.holes.is_empty() {
+            return Err("Merge the mirror image before adding holes".to_owned());
+        }
+        if self.drafts.iter().flatten().any(|d| d.is_some()) {
+            return Err("Merge the mirror image before drafting".to_owned());
+        }
+        let n = normal.normalize();
+        let up = self.direction.normalize();
+        let start = self.face.exterior.segments[0].start;
+        let along = n.dot(up);
+
+        if along.abs() > 1.0 - 1e-9 {
+            let height = origin.to_vector().subtract(start.to_vector()).dot(up);
+            let face = if (height - self.depth).abs() < 1e-9 {
+                self.face.clone()
+            } else if height.abs() < 1e-9 {
+                Transform::Translate(up.scale(-self.depth)).apply_face(&self.face)
+            } else {
+                return Err("The mirror plane must lie on one of the end caps to merge".to_owned());
+            };
+            return Ok(Prism {
+                face,
+                direction: self.direction,
+                depth: self.depth * 2.0,
+                corners: self.corners.clone(),
+                holes: vec![],
+                drafts: self.drafts.clone(),
+            });
+        }
+        if along.abs() > 1e-9 {
+            return Err(
+                "Can only merge across a plane along the extrusion or on an end cap".to_owned(),
+            );
+        }
+
+        if self.corners.iter().flatten().any(|c| *c != Corner::Sharp) {
+            return Err("Merge the mirror image before filleting or chamfering".to_owned());
+        }
+
+        // flatten the profile so the mirror plane becomes the line x = offset
+        let (e1, e2) = (n, up.cross(n));
+        let offset = origin.to_vector().subtract(start.to_vector()).dot(e1);
+        let original = self.flat_profile(e1, e2);
+        let mirrored = geo::MapCoords::map_coords(&original, |c| geo::Coord {
+            x: 2.0 * offset - c.x,
+            y: c.y,
+        });
+        let union = geo::BooleanOps::union(&original, &mirrored);
+        if union.0.len() != 1 {
+            return Err("The mirrored copy does not touch the original".to_owned());
+        }
+        let face = self.lift_profile(&union.0[0], e1, e2);
+        Ok(Prism::new(face, self.direction, self.depth))
+    }
+
+    pub fn split(&self, origin: Point, normal: Vector) -> Result<Vec<Prism>, String> {
+        // Cuts the prism in two with a plane square to the extrusion or along it,
+        // giving the pieces behind the plane before those in front of it
+        if !self.holes.is_empty() {
+            return Err("Split the solid before adding holes".to_owned());
+        }
+        if self.drafts.iter().flatten().any(|d| d.is_some()) {
+            return Err("Split the solid before drafting".to_owned());
+        }
+        let n = normal.normalize();
+        let up = self.direction.normalize();
+        let start = self.face.exterior.segments[0].start;
+        let along = n.dot(up);
+
+        if along.abs() > 1.0 - 1e-9 {
+            let height = origin.to_vector().subtract(start.to_vector()).dot(up);
+            if height <= 1e-9 || height >= self.depth - 1e-9 {
+                return Err("The plane does not cut through the solid".to_owned());
+            }
+            // treated edges run along the extrusion, so both pieces keep them
+            let below = Prism {
+                depth: height,
+                holes: vec![],
+                ..self.clone()
+            };
+            let above = Prism {
+                face: Transform::Translate(self.direction.scale(height)).apply_face(&self.face),
+                depth: self.depth - height,
+                holes: vec![],
+                ..self.clone()
+            };
+            return Ok(if along > 0.0 {
+                vec![below, above]
+            } else {
+                vec![above, below]
+            });
+        }
+        if along.abs() > 1e-9 {
+            return Err("The plane must be square to the extrusion or run along it".to_owned());
+        }
+        if self.corners.iter().flatten().any(|c| *c != Corner::Sharp) {
+            return Err("Split the solid before filleting or chamfering".to_owned());
+        }
+
+        // flatten the profile so the plane becomes the line x = offset, then clip
+        // it against a box reaching past the profile on either side
+        let (e1, e2) = (n, up.cross(n));
+        let offset = origin.to_vector().subtract(start.to_vector()).dot(e1);
+        let profile = self.flat_profile(e1, e2);
+        let Some(bounds) = geo::BoundingRect::bounding_rect(&profile) else {
+            return Err("The profile is empty".to_owned());
+        };
+        let (low, high) = (bounds.min().y - 1.0, bounds.max().y + 1.0);
+        let slab = |from: f64, to: f64| {
+            geo::Polygon::new(
+                geo::LineString::from(vec![(from, low), (to, low), (to, high), (from, high)]),
+                vec![],
+            )
+        };
+        let behind = geo::BooleanOps::intersection(&profile, &slab(bounds.min().x - 1.0, offset));
+        let ahead = geo::BooleanOps::intersection(&profile, &slab(offset, bounds.max().x + 1.0));
+        let area = |pieces: &geo::MultiPolygon<f64>| geo::Area::unsigned_area(pieces);
+        if area(&behind) < 1e-9 || area(&ahead) < 1e-9 {
+            return Err("The plane does not cut through the solid".to_owned());
+        }
+
+        Ok(behind
+            .iter()
+            .chain(ahead.iter())
+            .map(|piece| Prism::new(self.lift_profile(piece, e1, e2), self.direction, self.depth))
+            .collect())
+    }
+
+    fn flat_profile(&self, e1: Vector, e2: Vector) -> geo::Polygon<f64> {
+        // the profile in 2D, measured from its first point along e1 and e2
+        let start = self.face.exterior.segments[0].start;
+        let flatten = |ring: &LineRing| {
+            let coords: Vec<(f64, f64)> = ring
+                .points()
+                .iter()
+                .map(|p| {
+                    let v = p.to_vector().subtract(start.to_vector());
+                    (v.dot(e1), v.dot(e2))
+                })
+                .collect();
+            geo::LineString::from(coords)
+        };
+        geo::Polygon::new(
+            flatten(&self.face.exterior),
+            self.face.interiors.iter().map(flatten).collect(),
+        )
+    }
+
+    fn lift_profile(&self, polygon: &geo::Polygon<f64>, e1: Vector, e2: Vector) -> LineFace {
+        // the inverse of flat_profile, winding every ring like the original profile
+        let start = self.face.exterior.segments[0].start;
+        let up = self.direction.normalize();
+        let winding = newell_normal(&self.face.exterior.points()).dot(up);
+        let lift = |line: &geo::LineString<f64>| {
+            let mut points: Vec<Point> = line
+                .coords()
+                .map(|c| start.add_vec(e1.scale(c.x)).add_vec(e2.scale(c.y)))
+                .collect();
+            points.pop();
+            let mut points = drop_collinear(&points);
+            if newell_normal(&points).dot(up) * winding < 0.0 {
+                points.reverse();
+            }
+            LineRing {
+                segments: (0..points.len())
+                    .map(|k| LineSegment::new(points[k], points[(k + 1) % points.len()]))
+                    .collect(),
+            }
+        };
+        LineFace {
+            exterior: lift(polygon.exterior()),
+            interiors: polygon.interiors().iter().map(lift).collect(),
+        }
+    }
+}
+
+fn drop_collinear(points: &[Point]) -> Vec<Point> {
+    let n = points.len();
+    (0..n)
+        .filter(|&k| {
+            let before = points[k]
+                .to_vector()
+                .subtract(points[(k + n - 1) % n].to_vector());
+            let after = points[(k + 1) % n]
+                .to_vector()
+                .subtract(points[k].to_vector());
+            before.cross(after).length() > 1e-9 * before.length() * after.length()
+        })
+        .map(|k| points[k])
+        .collect()
+}
+
+// A planar slice through a solid: each entry is an outer ring and its holes.
+pub type Section = Vec<(Vec<Point>, Vec<Vec<Point>>)>;
+
+impl Prism {
+    pub fn cross_section(&self, origin: Point, normal: Vector) -> Result<Section, String> {
+        // Slices the prism with a plane that is either square to the extrusion
+        // or runs along it
+        let n = normal.normalize();
+        let up = self.direction.normalize();
+        let start = self.face.exterior.segments[0].start;
+
+        if n.dot(up).abs() > 1.0 - 1e-9 {
+            let height = origin.to_vector().subtract(start.to_vector()).dot(up);
+            if height <= 1e-9 || height >= self.depth - 1e-9 {
+                return Err("The plane does not cut through the solid".to_owned());
+            }
+            let mut rings = self.section(height)?;
+            let exterior = rings.remove(0);
+            return Ok(vec![(exterior, rings)]);
+        }
+        if n.dot(up).abs() > 1e-9 {
+            return Err("The plane must be square to the extrusion or run along it".to_owned());
+        }
+
+        // where the plane crosses the profile's edges, as distances along the
+        // line it cuts; every other gap between crossings is inside the profile
+        let along = n.cross(up);
+        let mut crossings: Vec<f64> = vec![];
+        for ring in self.face.rings() {
+            for segment in ring.segments.iter() {
+                let side = |p: Point| p.to_vector().subtract(origin.to_vector()).dot(n);
+                let (a, b) = (side(segment.start), side(segment.end));
+                if (a < 0.0) != (b < 0.0) {
+                    let t = a / (a - b);
+                    let edge = segment.end.to_vector().subtract(segment.start.to_vector());
+                    let hit = segment.start.add_vec(edge.scale(t));
+                    crossings.push(hit.to_vector().subtract(start.to_vector()).dot(along));
+                }
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        if crossings.is_empty() {
+            return Err("The plane does not cut through the solid".to_owned());
+        }
+
+        let base = start.add_vec(n.scale(origin.to_vector().subtract(start.to_vector()).dot(n)));
+        let rise = self.direction.scale(self.depth);
+        Ok(crossings
+            .chunks(2)
+            .map(|gap| {
+                let a = base.add_vec(along.scale(gap[0]));
+                let b = base.add_vec(along.scale(gap[1]));
+                (vec![a, b, b.add_vec(rise), a.add_vec(rise)], vec![])
+            })
+            .collect())
+    }
+}
+
+pub fn rib(
+    chain: &[Point],
+    section: &Section,
+    frame: &CoordinateFrame,
+    thickness: f64,
+) -> Result<Solid, String> {
+    // Closes an open chain against the boundary of a section through a body and
+    // thickens the enclosed web evenly to both sides of the chain's plane
+    if thickness <= 0.0 {
+        return Err(format!("Rib thickness must be positive, got {}", thickness));
+    }
+    if chain.len() < 2 {
+        return Err("A rib needs at least one segment".to_owned());
+    }
+    let flatten = |p: &Point| {
+        let v = p.to_vector().subtract(frame.origin.to_vector());
+        geo::Coord {
+            x: v.dot(frame.x_axis),
+            y: v.dot(frame.y_axis),
+        }
+    };
+    let chain: Vec<geo::Coord> = chain.iter().map(flatten).collect();
+    let rings: Vec<Vec<geo::Coord>> = section
+        .iter()
+        .flat_map(|(exterior, interiors)| std::iter::once(exterior).chain(interiors.iter()))
+        .map(|ring| ring.iter().map(flatten).collect())
+        .collect();
+    let body = geo::MultiPolygon(
+        section
+            .iter()
+            .map(|(exterior, interiors)| {
+                let ring = |r: &Vec<Point>| geo::LineString(r.iter().map(flatten).collect());
+                geo::Polygon::new(ring(exterior), interiors.iter().map(ring).collect())
+            })
+            .collect(),
+    );
+
+    // carry each open end straight on until it meets the body
+    let last = chain.len() - 1;
+    let (ring_a, edge_a, at_a) = cast(&rings, chain[0], chain[0] - chain[1])
+        .ok_or("The start of the rib never reaches the body".to_owned())?;
+    let (ring_b, edge_b, at_b) = cast(&rings, chain[last], chain[last] - chain[last - 1])
+        .ok_or("The end of the rib never reaches the body".to_owned())?;
+    if ring_a != ring_b {
+        return Err("Both ends of the rib must land on the same boundary".to_owned());
+    }
+
+    // walk the boundary back from the end to the start both ways round and keep
+    // whichever web stays outside the body
+    let ring = &rings[ring_a];
+    let n = ring.len();
+    let position = |edge: usize, at: geo::Coord| {
+        let (p, q) = (ring[edge], ring[(edge + 1) % n]);
+        let d = q - p;
+        edge as f64 + ((at - p).x * d.x + (at - p).y * d.y) / (d.x * d.x + d.y * d.y)
+    };
+    let (from, to) = (position(edge_b, at_b), position(edge_a, at_a));
+    let forward: Vec<geo::Coord> = (1..=n)
+        .map(|k| (edge_b + k) % n)
+        .map(|k| {
+            (
+                k,
+                if k as f64 > from {
+                    k as f64
+                } else {
+                    k as f64 + n as f64
+                },
+            )
+        })
+        .filter(|(_, key)| *key < if to > from { to } else { to + n as f64 })
+        .map(|(k, _)| ring[k])
+        .collect();
+    let backward: Vec<geo::Coord> = (0..n)
+        .map(|k| (edge_b + n - k) % n)
+        .map(|k| {
+            (
+                k,
+                if (k as f64) < from {
+                    k as f64
+                } else {
+                    k as f64 - n as f64
+                },
+            )
+        })
+        .filter(|(_, key)| *key > if to < from { to } else { to - n as f64 })
+        .map(|(k, _)| ring[k])
+        .collect();
+
+    let mut best: Option<(f64, geo::Polygon)> = None;
+    for path in [forward, backward] {
+        let mut outline = vec![at_a];
+        outline.extend(chain.iter().cloned());
+        outline.push(at_b);
+        outline.extend(path);
+        outline.dedup_by(|a, b| (*a - *b).x.hypot((*a - *b).y) < 1e-9);
+        let web = geo::Polygon::new(geo::LineString(outline), vec![]);
+        let area = geo::Area::unsigned_area(&web);
+        let overlap = geo::Area::unsigned_area(&geo::BooleanOps::intersection(
+            &geo::MultiPolygon(vec![web.clone()]),
+            &body,
+        ));
+        if area > 1e-9 && overlap < 1e-6 * area && best.as_ref().is_none_or(|b| area < b.0) {
+            best = Some((area, web));
+        }
+    }
+    let (_, mut web) =
+        best.ok_or("The rib does not close off a region against the body".to_owned())?;
+    if geo::Area::signed_area(&web) < 0.0 {
+        web.exterior_mut(|e| e.0.reverse());
+    }
+
+    let offset = frame.normal.scale(-thickness / 2.0);
+    let mut points: Vec<Point> = web
+        .exterior()
+        .coords()
+        .map(|c| {
+            frame
+                .origin
+                .add_vec(frame.x_axis.scale(c.x))
+                .add_vec(frame.y_axis.scale(c.y))
+                .add_vec(offset)
+        })
+        .collect();
+    points.pop();
+    let mut face = LineFace::new();
+    for k in 0..points.len() {
+        face.exterior
+            .add_segment(LineSegment::new(points[k], points[(k + 1) % points.len()]));
+    }
+    Ok(face.tsweep(frame.normal, thickness))
+}
+
+pub fn thicken(chain: &[Point], normal: Vector, offsets: (f64, f64)) -> Result<LineFace, String> {
+    // Turns a planar chain into a face covering the band between two offsets of
+    // it, measured to the chain's left looking down the normal. A chain whose ends
+    // meet gives a ring-shaped face; any other gives a single strip.
+    let closed = chain.len() > 3
+        && chain[0]
+            .to_vector()
+            .subtract(chain[chain.len() - 1].to_vector())
+            .length()
+            < 1e-9;
+    let points = if closed {
+        &chain[..chain.len() - 1]
+    } else {
+        chain
+    };
+    if points.len() < 2 {
+        return Err("Cannot thicken a chain with no length".to_owned());
+    }
+    if (offsets.1 - offsets.0).abs() < 1e-12 {
+        return Err("Wall thickness must be positive".to_owned());
+    }
+    let a = offset_chain(points, normal, offsets.0, closed)?;
+    let b = offset_chain(points, normal, offsets.1, closed)?;
+
+    let ring = |mut points: Vec<Point>| {
+        if newell_normal(&points).dot(normal) < 0.0 {
+            points.reverse();
+        }
+        let mut ring = LineRing::new();
+        for k in 0..points.len() {
+            ring.add_segment(LineSegment::new(points[k], points[(k + 1) % points.len()]));
+        }
+        ring
+    };
+    let mut face = LineFace::new();
+    if closed {
+        // the larger of the two offsets wraps around the smaller
+        let area = |p: &[Point]| newell_normal(p).length();
+        let (outer, inner) = if area(&a) > area(&b) { (a, b) } else { (b, a) };
+        face.exterior = ring(outer);
+        face.add_interior(ring(inner));
+    } else {
+        let mut outline = a;
+        outline.extend(b.into_iter().rev());
+        face.exterior = ring(outline);
+    }
+    Ok(face)
+}
+
+fn offset_chain(
+    points: &[Point],
+    normal: Vector,
+    distance: f64,
+    closed: bool,
+) -> Result<Vec<Point>, String> {
+    // every edge moved to its left by distance, with the corners mitered
+    if distance.abs() < 1e-12 {
+        return Ok(points.to_vec());
+    }
+    let moved = if closed {
+        let winding = newell_normal(points).dot(normal).signum();
+        offset_ring(points, normal, &vec![distance * winding; points.len()])?
+    } else {
+        let n = points.len();
+        let direction = |k: usize| {
+            points[k + 1]
+                .to_vector()
+                .subtract(points[k].to_vector())
+                .normalize()
+        };
+        let mut moved = vec![points[0].add_vec(normal.cross(direction(0)).scale(distance))];
+        for (k, corner) in points.windows(3).enumerate() {
+            let (u, v) = (direction(k), direction(k + 1));
+            let on_before = corner[1].add_vec(normal.cross(u).scale(distance));
+            let on_after = corner[1].add_vec(normal.cross(v).scale(distance));
+            let turn = u.cross(v).dot(normal);
+            if turn.abs() < 1e-9 {
+                if u.dot(v) < 0.0 {
+                    return Err(format!("Vertex {} folds back on itself", k + 1));
+                }
+                moved.push(on_before);
+                continue;
+            }
+            let gap = on_after.to_vector().subtract(on_before.to_vector());
+            moved.push(on_before.add_vec(u.scale(gap.cross(v).dot(normal) / turn)));
+        }
+        moved.push(points[n - 1].add_vec(normal.cross(direction(n - 2)).scale(distance)));
+        moved
+    };
+
+    let edges = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let next = |k: usize| (k + 1) % points.len();
+    let folded = (0..edges).any(|k| {
+        let old = points[next(k)].to_vector().subtract(points[k].to_vector());
+        let new = moved[next(k)].to_vector().subtract(moved[k].to_vector());
+        old.dot(new) <= 0.0
+    });
+    if folded {
+        return Err(format!(
+            "Wall thickness {} is too large for this chain",
+            distance.abs()
+        ));
+    }
+    Ok(moved)
+}
+
+fn cast(
+    rings: &[Vec<geo::Coord>],
+    from: geo::Coord,
+    direction: geo::Coord,
+) -> Option<(usize, usize, geo::Coord)> {
+    // the nearest boundary point straight ahead of from: (ring, edge, point)
+    let cross = |a: geo::Coord, b: geo::Coord| a.x * b.y - a.y * b.x;
+    let mut nearest: Option<(f64, usize, usize, geo::Coord)> = None;
+    for (r, ring) in rings.iter().enumerate() {
+        for k in 0..ring.len() {
+            let (p, q) = (ring[k], ring[(k + 1) % ring.len()]);
+            let edge = q - p;
+            let turn = cross(direction, edge);
+            if turn.abs() < 1e-12 {
+                continue;
+            }
+            let t = cross(p - from, edge) / turn;
+            let u = cross(p - from, direction) / turn;
+            if t >= -1e-9 && (-1e-9..=1.0 + 1e-9).contains(&u) && nearest.is_none_or(|n| t < n.0) {
+                nearest = Some((t, r, k, from + direction * t));
+            }
+        }
+    }
+    nearest.map(|(_, r, k, at)| (r, k, at))
+}
+
+const DRILL_POINT_ANGLE: f64 = 118.0 * std::f64::consts::PI / 180.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum HoleShape {
+    Simple,
+    Counterbore { diameter: f64, depth: f64 },
+    // angle is the included angle of the cone, in radians
+    Countersink { diameter: f64, angle: f64 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct HoleCut {
+    // where the hole enters the solid and the direction it is drilled in
+    pub entry: Point,
+    pub axis: Vector,
+    pub diameter: f64,
+    // to the shoulder of the drill point, or None to go through everything
+    pub depth: Option<f64>,
+    pub shape: HoleShape,
+}
+
+impl HoleCut {
+    pub fn outer_radius(&self) -> f64 {
+        match self.shape {
+            HoleShape::Simple => self.diameter / 2.0,
+            HoleShape::Counterbore { diameter, .. } | HoleShape::Countersink { diameter, .. } => {
+                diameter.max(self.diameter) / 2.0
+            }
+        }
+    }
+
+    fn profile(&self, through: f64) -> Result<Vec<(f64, f64)>, String> {
+        // (radius, distance along the axis) from the center of the opening
+        // round the outline of the hole and back to the axis
+        let r = self.diameter / 2.0;
+        if r <= 0.0 {
+            return Err(format!(
+                "Hole diameter must be positive, got {}",
+                self.diameter
+            ));
+        }
+        let mut profile = vec![(0.0, 0.0)];
+        match self.shape {
+            HoleShape::Simple => profile.push((r, 0.0)),
+            HoleShape::Counterbore { diameter, depth } => {
+                if diameter <= self.diameter || depth <= 0.0 {
+                    return Err(
+                        "A counterbore must be wider than its hole and have depth".to_owned()
+                    );
+                }
+                profile.extend([(diameter / 2.0, 0.0), (diameter / 2.0, depth), (r, depth)]);
+            }
+            HoleShape::Countersink { diameter, angle } => {
+                if diameter <= self.diameter || angle <= 0.0 || angle >= std::f64::consts::PI {
+                    return Err("A countersink must be wider than its hole".to_owned());
+                }
+                let sink = (diameter / 2.0 - r) / (angle / 2.0).tan();
+                profile.extend([(diameter / 2.0, 0.0), (r, sink)]);
+            }
+        }
+
+        let shoulder = profile.last().unwrap().1;
+        match self.depth {
+            Some(depth) => {
+                let tip = depth + r / (DRILL_POINT_ANGLE / 2.0).tan();
+                if depth <= shoulder {
+                    return Err(format!("Hole depth {} does not reach past its head", depth));
+                }
+                if tip >= through {
+                    return Err(format!(
+                        "Hole depth {} breaks through a solid {} thick, drill through all instead",
+                        depth, through
+                    ));
+                }
+                profile.extend([(r, depth), (0.0, tip)]);
+            }
+            None => {
+                if shoulder >= through {
+                    return Err("The hole's head is deeper than the solid".to_owned());
+                }
+                profile.extend([(r, through), (0.0, through)]);
+            }
+        }
+        Ok(profile)
+    }
+
+    fn tool(&self, profile: &[(f64, f64)]) -> (Wire, Option<Wire>, Vec<Face>) {
+        // Revolves the profile into a closed shell, then splits off the disk at the
+        // opening (and at the exit of a through hole). Returns the wire to cut into
+        // each cap and the faces that line the bore, facing into the material.
+        let axis = self.axis.normalize();
+        let helper = if axis.x.abs() < 0.9 {
+            Vector::new(1.0, 0.0, 0.0)
+        } else {
+            Vector::new(0.0, 1.0, 0.0)
+        };
+        let radial = axis.cross(helper).normalize();
+        let vertices: Vec<Vertex> = profile
+            .iter()
+            .map(|(r, a)| {
+                self.entry
+                    .add_vec(axis.scale(*a))
+                    .add_vec(radial.scale(*r))
+                    .to_vertex()
+            })
+            .collect();
+        let wire: Wire = vertices
+            .windows(2)
+            .map(|pair| builder::line(&pair[0], &pair[1]))
+            .collect();
+        let shell = builder::cone(&wire, axis.to_vector3(), Rad(2.0 * std::f64::consts::PI));
+
+        let per_turn = wire.len();
+        let through = self.depth.is_none();
+        let mut entry_disk: Vec<Face> = vec![];
+        let mut exit_disk: Vec<Face> = vec![];
+        let mut lining: Vec<Face> = vec![];
+        for (i, face) in shell.face_iter().enumerate() {
+            if i % per_turn == 0 {
+                entry_disk.push(face.clone());
+            } else if through && i % per_turn == per_turn - 1 {
+                exit_disk.push(face.clone());
+            } else {
+                lining.push(face.clone());
+            }
+        }
+
+        // the opening faces back out of the hole when the tool faces outward;
+        // if it came out the other way round, the lining is already inside out
+        let entry = opening(&entry_disk, self.entry);
+        let samples: Vec<Point> = entry
+            .edge_iter()
+            .flat_map(|e| {
+                let curve = e.oriented_curve();
+                let (t0, t1) = curve.parameter_range();
+                [curve.subs(t0), curve.subs((t0 + t1) / 2.0)]
+            })
+            .map(Point::from_point3)
+            .collect();
+        let outward = newell_normal(&samples).dot(axis) < 0.0;
+
+        let orient = |w: Wire| if outward { w.inverse() } else { w };
+        let lining = if outward {
+            lining.iter().map(|f| f.inverse()).collect()
+        } else {
+            lining
+        };
+        let exit = if through {
+            let exit = self.entry.add_vec(axis.scale(profile.last().unwrap().1));
+            Some(orient(opening(&exit_disk, exit)))
+        } else {
+            None
+        };
+        (orient(entry), exit, lining)
+    }
+}
+
+fn opening(disk: &[Face], center: Point) -> Wire {
+    // the rim of a fan of faces around center, chained up. The spokes are left
+    // out rather than matched in pairs, as the seam of a whole turn cone doesn't
+    // share its spoke edge.
+    let on_center = |v: &Vertex| {
+        let p = Point::from_point3(v.get_point());
+        p.to_vector().subtract(center.to_vector()).length() < 1e-9
+    };
+    let mut rim: Vec<Edge> = disk
+        .iter()
+        .flat_map(|f| f.boundaries()[0].clone())
+        .filter(|e| !on_center(e.front()) && !on_center(e.back()))
+        .collect();
+    let mut wire = Wire::new();
+    wire.push_back(rim.remove(0));
+    while !rim.is_empty() {
+        let back = wire.back_vertex().unwrap().clone();
+        let next = rim.iter().position(|e| e.front() == &back).unwrap();
+        wire.push_back(rim.remove(next));
+    }
+    wire
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Cylinder {
+    // the start of the face on its axis, with the face running length along axis
+    pub origin: Point,
+    pub axis: Vector,
+    pub radius: f64,
+    pub length: f64,
+    // a bore rather than a shaft, with its material on the outside
+    pub internal: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Thread {
+    // e.g. M6x1 or 1/4-20 UNC
+    pub designation: String,
+    pub major_diameter: f64,
+    pub pitch: f64,
+    pub length: f64,
+    pub internal: bool,
+    // where the thread starts on its axis and the direction it runs in
+    pub origin: Point,
+    pub axis: Vector,
+}
+
+impl Thread {
+    pub fn callout(&self) -> String {
+        // the note a drawing puts on the thread, e.g. "M6x1 - 12 DEEP"
+        if self.internal {
+            format!("{} - {} DEEP", self.designation, self.length)
+        } else {
+            format!("{} x {}", self.designation, self.length)
+        }
+    }
+
+    pub fn model(&self, add: bool) -> Result<Solid, String> {
+        // The true ISO profile swept round the axis. Material added to a shaft is
+        // the ridge of the thread and material cut from it is the groove between
+        // ridges; in a bore the two swap over.
+        let h = 3f64.sqrt() / 2.0 * self.pitch;
+        let major = self.major_diameter / 2.0;
+        let minor = major - 5.0 * h / 8.0;
+        let (at_major, at_minor) = if add != self.internal {
+            (self.pitch / 8.0, 3.0 * self.pitch / 4.0)
+        } else {
+            (7.0 * self.pitch / 8.0, self.pitch / 4.0)
+        };
+        let width = at_major.max(at_minor);
+        let turns = (self.length - width) / self.pitch;
+        if turns <= 0.0 {
+            return Err(format!(
+                "{} is too short to hold a full turn",
+                self.callout()
+            ));
+        }
+
+        let axis = self.axis.normalize();
+        let reference = perpendicular(axis);
+        let at = |radius: f64, along: f64| {
+            self.origin
+                .add_vec(reference.scale(radius))
+                .add_vec(axis.scale(along))
+        };
+        let points = [
+            at(minor, (width - at_minor) / 2.0),
+            at(major, (width - at_major) / 2.0),
+            at(major, (width + at_major) / 2.0),
+            at(minor, (width + at_minor) / 2.0),
+        ];
+        let profile = LineFace {
+            exterior: LineRing {
+                segments: (0..points.len())
+                    .map(|k| LineSegment::new(points[k], points[(k + 1) % points.len()]))
+                    .collect(),
+            },
+            interiors: vec![],
+        };
+        let helix = Helix::new(self.origin, axis, reference, major, self.pitch, turns, 0.0)?;
+        helix.sweep(&profile, true)
+    }
+}
+
+// stations per turn that each helical edge is fitted through
+const HELIX_SEGMENTS: f64 = 16.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Helix {
+    // the start of the helix on its axis, winding right handed along axis
+    pub origin: Point,
+    pub axis: Vector,
+    // from the axis toward where the helix starts
+    pub reference: Vector,
+    pub radius: f64,
+    pub pitch: f64,
+    pub turns: f64,
+    // half angle of the cone it winds on, in radians. Positive narrows the helix.
+    pub taper: f64,
+}
+
+impl Helix {
+    pub fn new(
+        origin: Point,
+        axis: Vector,
+        reference: Vector,
+        radius: f64,
+        pitch: f64,
+        turns: f64,
+        taper: f64,
+    ) -> Result<Self, String> {
+        if axis.length() < 1e-12 {
+            return Err("The helix axis has no direction".to_string());
+        }
+        let axis = axis.normalize();
+        let reference = reference.subtract(axis.scale(reference.dot(axis)));
+        if reference.length() < 1e-12 {
+            return Err("The helix reference runs along its axis".to_string());
+        }
+        if radius <= 0.0 || pitch <= 0.0 || turns <= 0.0 {
+            return Err(format!(
+                "A helix needs a positive radius, pitch and turns, not {}, {} and {}",
+                radius, pitch, turns
+            ));
+        }
+        if taper.abs() >= std::f64::consts::FRAC_PI_2 {
+            return Err(format!("A taper of {} rad leaves no helix", taper));
+        }
+        let helix = Helix {
+            origin,
+            axis,
+            reference: reference.normalize(),
+            radius,
+            pitch,
+            turns,
+            taper,
+        };
+        if helix.radius_at(radius, 1.0) <= 0.0 {
+            return Err("The taper closes the helix onto its axis".to_string());
+        }
+        Ok(helix)
+    }
+
+    pub fn start(&self) -> Point {
+        self.origin.add_vec(self.reference.scale(self.radius))
+    }
+
+    pub fn point(&self, t: f64) -> Point {
+        // t runs from 0 at the start to 1 after the last turn
+        self.place(self.start(), t).0
+    }
+
+    fn radius_at(&self, radius: f64, t: f64) -> f64 {
+        radius - self.taper.tan() * self.pitch * self.turns * t
+    }
+
+    fn place(&self, p: Point, t: f64) -> (Point, Vector) {
+        // where p is carried to at t, turning with the helix, and how fast it moves
+        let relative = p.to_vector().subtract(self.origin.to_vector());
+        let along = relative.dot(self.axis);
+        let radial = relative.subtract(self.axis.scale(along));
+        let side = self.axis.cross(self.reference);
+        let start = radial.dot(side).atan2(radial.dot(self.reference));
+
+        let rise = self.pitch * self.turns;
+        let spin = 2.0 * std::f64::consts::PI * self.turns;
+        let radius = self.radius_at(radial.length(), t);
+        let angle = start + spin * t;
+        let out = self
+            .reference
+            .scale(angle.cos())
+            .add(side.scale(angle.sin()));
+        let around = side
+            .scale(angle.cos())
+            .subtract(self.reference.scale(angle.sin()));
+        let position = self
+            .origin
+            .add_vec(self.axis.scale(along + rise * t))
+            .add_vec(out.scale(radius));
+        let velocity = self
+            .axis
+            .scale(rise)
+            .subtract(out.scale(self.taper.tan() * rise))
+            .add(around.scale(radius * spin));
+        (position, velocity)
+    }
+
+    pub fn sweep(&self, face: &LineFace, follow_path: bool) -> Result<Solid, String> {
+        // Following the path turns the profile about the axis as it climbs, which
+        // is how a spring or a thread is made. Otherwise it is carried along the
+        // helix without turning.
+        if !face.interiors.is_empty() {
+            return Err("Only a profile without holes can be swept along a helix".to_string());
+        }
+        let points = face.exterior.points();
+        let start = self.start();
+        let place = |p: Point, t: f64| {
+            if follow_path {
+                self.place(p, t)
+            } else {
+                let (at, velocity) = self.place(start, t);
+                let offset = at.to_vector().subtract(start.to_vector());
+                (p.add_vec(offset), velocity)
+            }
+        };
+        if follow_path {
+            for p in points.iter() {
+                let relative = p.to_vector().subtract(self.origin.to_vector());
+                let radial = relative.subtract(self.axis.scale(relative.dot(self.axis)));
+                if self.radius_at(radial.length(), 1.0) < -1e-9 {
+                    return Err("The taper carries the profile across the axis".to_string());
+                }
+            }
+        }
+
+        let segments = (self.turns * HELIX_SEGMENTS).ceil().max(4.0) as usize;
+        let mut truck_solid = helical_solid(&points, segments, place)?;
+        let (_, motion) = place(centroid(&points), 0.0);
+        if motion.dot(newell_normal(&points)) > 0.0 {
+            truck_solid.not();
+        }
+        Ok(Solid::new(truck_solid))
+    }
+}
+
+fn perpendicular(axis: Vector) -> Vector {
+    // any direction square to axis, picked the same way every time
+    let a = axis.normalize();
+    let other = if a.x.abs() < 0.9 {
+        Vector::new(1.0, 0.0, 0.0)
+    } else {
+        Vector::new(0.0, 1.0, 0.0)
+    };
+    other.subtract(a.scale(other.dot(a))).normalize()
+}
+
+fn helical_solid(
+    points: &[Point],
+    segments: usize,
+    place: impl Fn(Point, f64) -> (Point, Vector),
+) -> Result<truck_modeling::Solid, String> {
+    // Each corner of the profile traces a chain of cubics through the stations,
+    // matching the path and its direction at each one. Neighbouring chains are
+    // joined by a ruled face per span, as truck meshes a long face far too
+    // finely, and the ends are closed with the profile at each end.
+    let step = 1.0 / segments as f64;
+    // trig leaves crumbs where a coordinate should be zero, and truck's mesher
+    // gives up on the denormal parameters they turn into
+    let clean = |c: f64| if c.abs() < 1e-12 { 0.0 } else { c };
+    let stations: Vec<Vec<(Point, Vector)>> = points
+        .iter()
+        .map(|&p| {
+            (0..=segments)
+                .map(|k| {
+                    let (at, velocity) = place(p, k as f64 * step);
+                    (Point::new(clean(at.x), clean(at.y), clean(at.z)), velocity)
+                })
+                .collect()
+        })
+        .collect();
+    let spans: Vec<Vec<BSplineCurve<Point3>>> = stations
+        .iter()
+        .map(|path| {
+            path.windows(2)
+                .map(|pair| {
+                    let ((a, da), (b, db)) = (pair[0], pair[1]);
+                    let control = vec![
+                        a.to_point3(),
+                        a.add_vec(da.scale(step / 3.0)).to_point3(),
+                        b.add_vec(db.scale(-step / 3.0)).to_point3(),
+                        b.to_point3(),
+                    ];
+                    BSplineCurve::new(KnotVec::bezier_knot(3), control)
+                })
+                .collect()
+        })
+        .collect();
+
+    let vertices: Vec<Vec<Vertex>> = stations
+        .iter()
+        .map(|path| path.iter().map(|(p, _)| p.to_vertex()).collect())
+        .collect();
+    let paths: Vec<Vec<Edge>> = spans
+        .iter()
+        .zip(vertices.iter())
+        .map(|(curves, at)| {
+            curves
+                .iter()
+                .enumerate()
+                .map(|(k, c)| Edge::new(&at[k], &at[k + 1], Curve::BSplineCurve(c.clone())))
+                .collect()
+        })
+        .collect();
+    let n = points.len();
+    // the profile's edges at each station
+    let rungs: Vec<Vec<Edge>> = (0..=segments)
+        .map(|k| {
+            (0..n)
+                .map(|i| builder::line(&vertices[i][k], &vertices[(i + 1) % n][k]))
+                .collect()
+        })
+        .collect();
+
+    let mut faces = vec![];
+    for i in 0..n {
+        let j = (i + 1) % n;
+        for k in 0..segments {
+            let wire = Wire::from(vec![
+                paths[i][k].clone(),
+                rungs[k + 1][i].clone(),
+                paths[j][k].inverse(),
+                rungs[k][i].inverse(),
+            ]);
+            let surface = BSplineSurface::homotopy(spans[i][k].clone(), spans[j][k].clone());
+            faces.push(Face::new(vec![wire], Surface::BSplineSurface(surface)));
+        }
+    }
+    let cap = |wire: Wire| {
+        builder::try_attach_plane(&[wire])
+            .map_err(|e| format!("The profile doesn't stay flat along the helix: {}", e))
+    };
+    faces.push(cap(Wire::from(rungs[0].clone()))?);
+    faces.push(cap(rungs[segments]
+        .iter()
+        .rev()
+        .map(|e| e.inverse())
+        .collect())?);
+
+    truck_modeling::Solid::try_new(vec![Shell::from(faces)])
+        .map_err(|e| format!("Could not close the helical solid: {}", e))
+}
+
+fn last_entity(step: &str) -> usize {
+    step.split('#')
+        .skip(1)
+        .filter_map(|rest| {
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+fn declare_length_unit(step: String, unit: LengthUnit) -> String {
+    // truck always says millimetres; the coordinates are in the solid's own
+    // unit, so that declaration is swapped for the right one
+    let millimetre = "( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.) )";
+    let Some(end) = step.rfind("ENDSEC;") else {
+        return step;
+    };
+    let next = last_entity(&step[..end]) + 1;
+    let (declaration, extra) = match unit {
+        LengthUnit::Millimeter => return step,
+        LengthUnit::Centimeter => (
+            "( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.CENTI.,.METRE.) )".to_owned(),
+            String::new(),
+        ),
+        LengthUnit::Meter => (
+            "( LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT($,.METRE.) )".to_owned(),
+            String::new(),
+        ),
+        LengthUnit::Inch | LengthUnit::Foot => (
+            format!(
+                "( CONVERSION_BASED_UNIT('{}', #{}) LENGTH_UNIT() NAMED_UNIT(#{}) )",
+                if unit == LengthUnit::Inch { "INCH" } else { "FOOT" },
+                next,
+                next + 2
+            ),
+            format!(
+                "#{} = LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE({:.1}), #{});\n#{} = {};\n#{} = DIMENSIONAL_EXPONENTS(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);\n",
+                next,
+                unit.millimeters(),
+                next + 1,
+                next + 1,
+                millimetre,
+                next + 2
+            ),
+        ),
+    };
+    format!(
+        "{}{}{}",
+        step[..end].replacen(millimetre, &declaration, 1),
+        extra,
+        &step[end..]
+    )
+}
+
+fn annotate_threads(step: String, threads: &[Thread]) -> String {
+    // Threads aren't modelled, so they ride along in the STEP file as descriptive
+    // items after the geometry, numbered on from its last entity
+    if threads.is_empty() {
+        return step;
+    }
+    let Some(end) = step.rfind("ENDSEC;") else {
+        return step;
+    };
+    let last = last_entity(&step[..end]);
+    let mut items = String::new();
+    for (k, thread) in threads.iter().enumerate() {
+        items.push_str(&format!(
+            "#{} = DESCRIPTIVE_REPRESENTATION_ITEM('{}', '{} thread, pitch {}, length {}, from ({}, {}, {}) along ({}, {}, {})');\n",
+            last + 1 + k,
+            thread.callout(),
+            if thread.internal { "internal" } else { "external" },
+            thread.pitch,
+            thread.length,
+            thread.origin.x,
+            thread.origin.y,
+            thread.origin.z,
+            thread.axis.x,
+            thread.axis.y,
+            thread.axis.z,
+        ));
+    }
+    format!("{}{}{}", &step[..end], items, &step[end..])
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum Transform {
+    Translate(Vector),
+    // right-handed rotation about the axis through origin, angle in radians
+    Rotate {
+        origin: Point,
+        axis: Vector,
+        angle: f64,
+    },
+    // reflection across the plane through origin with the given normal
+    Mirror {
+        origin: Point,
+        normal: Vector,
+    },
+}
+
+impl Transform {
+    pub fn apply_vector(&self, v: Vector) -> Vector {
+        match self {
+            Transform::Translate(_) => v,
+            Transform::Rotate { axis, angle, .. } => {
+                // Rodrigues' rotation formula
+                let k = axis.normalize();
+                let (sin, cos) = angle.sin_cos();
+                v.scale(cos)
+                    .add(k.cross(v).scale(sin))
+                    .add(k.scale(k.dot(v) * (1.0 - cos)))
+            }
+            Transform::Mirror { normal, .. } => {
+                let n = normal.normalize();
+                v.subtract(n.scale(2.0 * n.dot(v)))
+            }
+        }
+    }
+
+    pub fn is_reflection(&self) -> bool {
+        matches!(self, Transform::Mirror { .. })
+    }
+
+    fn matrix(&self) -> Matrix4 {
+        let x = self.apply_vector(Vector::new(1.0, 0.0, 0.0));
+        let y = self.apply_vector(Vector::new(0.0, 1.0, 0.0));
+        let z = self.apply_vector(Vector::new(0.0, 0.0, 1.0));
+        let t = self.apply_point(Point::new(0.0, 0.0, 0.0));
+        Matrix4::from_cols(
+            Vector4::new(x.x, x.y, x.z, 0.0),
+            Vector4::new(y.x, y.y, y.z, 0.0),
+            Vector4::new(z.x, z.y, z.z, 0.0),
+            Vector4::new(t.x, t.y, t.z, 1.0),
+        )
+    }
+
+    pub fn apply_point(&self, p: Point) -> Point {
+        match self {
+            Transform::Translate(offset) => p.add_vec(*offset),
+            Transform::Rotate { origin, .. } | Transform::Mirror { origin, .. } => {
+                let relative = p.to_vector().subtract(origin.to_vector());
+                origin.add_vec(self.apply_vector(relative))
+            }
+        }
+    }
+
+    fn apply_face(&self, face: &LineFace) -> LineFace {
+        let ring = |r: &LineRing| LineRing {
+            segments: r
+                .segments
+                .iter()
+                .map(|s| LineSegment::new(self.apply_point(s.start), self.apply_point(s.end)))
+                .collect(),
+        };
+        LineFace {
+            exterior: ring(&face.exterior),
+            interiors: face.interiors.iter().map(ring).collect(),
+        }
+    }
+}
+
+// how near two surfaces have to be for a boolean to treat them as touching
+const BOOLEAN_TOLERANCE: f64 = 0.05;
+
 #[derive(Debug, Clone)]
 pub struct Solid {
     pub name: String,
     pub mesh: Mesh,
     pub color: Color,
     pub truck_solid: truck_modeling::Solid,
+    pub prism: Option<Prism>,
+    pub threads: Vec<Thread>,
+    pub sheet: Option<SheetMetal>,
+    pub naming: Option<Naming>,
+    // what the solid's coordinates are measured in
+    pub unit: LengthUnit,
+    // The last mesh made and the shape it was made from, shared with copies of
+    // the solid. Holding on to the shape keeps its face ids from being reused.
+    triangulated: Arc<Mutex<Option<(truck_modeling::Solid, Mesh)>>>,
 }
 
 impl Solid {
@@ -502,10 +2918,334 @@ impl Solid {
                 a: 1.0,
             },
             truck_solid: truck_solid,
+            prism: None,
+            threads: vec![],
+            sheet: None,
+            naming: None,
+            unit: LengthUnit::Millimeter,
+            triangulated: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn split(&self, origin: Point, normal: Vector) -> Result<Vec<Solid>, String> {
+        let prism = self.prism.as_ref().ok_or(
+            "Only plain extrusions can be split, not revolves, sweeps, lofts or bodies that \
+                 have been joined, cut or reshaped"
+                .to_owned(),
+        )?;
+        prism
+            .split(origin, normal)?
+            .into_iter()
+            .map(|piece| {
+                let mut solid = Solid::new(piece.to_truck_solid()?);
+                solid.prism = Some(piece);
+                Ok(solid)
+            })
+            .collect()
+    }
+
+    pub fn add_flange(&mut self, flange: Flange) -> Result<(), String> {
+        let sheet = self
+            .sheet
+            .as_mut()
+            .ok_or("Flanges can only be added to sheet metal parts".to_owned())?;
+        let mut bent = sheet.clone();
+        bent.add_flange(flange)?;
+        self.truck_solid = bent.to_truck_solid()?;
+        *sheet = bent;
+        Ok(())
+    }
+
+    pub fn set_corner(&mut self, ring: usize, vertex: usize, corner: Corner) -> Result<(), String> {
+        let prism = self
+            .prism
+            .as_mut()
+            .ok_or("Only the edges of extruded solids can be treated".to_owned())?;
+        let mut treated = prism.clone();
+        treated.set_corner(ring, vertex, corner)?;
+        self.truck_solid = treated.to_truck_solid()?;
+        *prism = treated;
+        Ok(())
+    }
+
+    pub fn shell(
+        &mut self,
+        thickness: f64,
+        outward: bool,
+        open: &[PrismFace],
+    ) -> Result<(), String> {
+        let prism = self.prism.as_ref().ok_or(
+            "Only plain extrusions can be shelled, not revolves, sweeps, lofts or bodies that \
+                 have been joined, cut or reshaped"
+                .to_owned(),
+        )?;
+        self.truck_solid = prism.shell(thickness, outward, open)?;
+        // the hollow solid can no longer be rebuilt from its profile alone
+        self.prism = None;
+        Ok(())
+    }
+
+    pub fn combine(&mut self, tool: &Solid, add: bool) -> Result<(), String> {
+        // Joins the tool to this solid, or cuts it away. What's left is no
+        // longer a single extruded profile, so the prism and face names go.
+        let mut tool_solid = tool.truck_solid.clone();
+        let combined = match add {
+            true => truck_shapeops::or(&self.truck_solid, &tool_solid, BOOLEAN_TOLERANCE),
+            false => {
+                tool_solid.not();
+                truck_shapeops::and(&self.truck_solid, &tool_solid, BOOLEAN_TOLERANCE)
+            }
+        };
+        self.truck_solid = combined.ok_or(format!(
+            "Could not {} {}",
+            if add { "join" } else { "cut" },
+            self.name
+        ))?;
+        self.prism = None;
+        self.naming = None;
+        self.sheet = None;
+        if add {
+            self.threads.extend(tool.threads.iter().cloned());
+        }
+        Ok(())
+    }
+
+    pub fn transformed(&self, transform: &Transform) -> Solid {
+        let truck_solid = match transform {
+            Transform::Translate(offset) => {
+                builder::translated(&self.truck_solid, offset.to_vector3())
+            }
+            Transform::Rotate {
+                origin,
+                axis,
+                angle,
+            } => builder::rotated(
+                &self.truck_solid,
+                origin.to_point3(),
+                axis.normalize().to_vector3(),
+                Rad(*angle),
+            ),
+            Transform::Mirror { .. } => {
+                // a reflection turns the solid inside out, so flip it back
+                let mut mirrored = builder::transformed(&self.truck_solid, transform.matrix());
+                mirrored.not();
+                mirrored
+            }
+        };
+        let prism = self.prism.as_ref().map(|p| {
+            let face = transform.apply_face(&p.face);
+            let direction = transform.apply_vector(p.direction);
+            let holes = p
+                .holes
+                .iter()
+                .map(|h| HoleCut {
+                    entry: transform.apply_point(h.entry),
+                    axis: transform.apply_vector(h.axis),
+                    ..*h
+                })
+                .collect();
+            if transform.is_reflection() {
+                // the mirrored profile winds the other way, so extrude it back from
+                // the far end to keep the same corners facing the same way
+                let far_end = Transform::Translate(direction.scale(p.depth));
+                // heights are now measured from the other end, flipping each draft
+                let drafts = p
+                    .drafts
+                    .iter()
+                    .map(|ring| {
+                        ring.iter()
+                            .map(|d| {
+                                d.map(|d| FaceDraft {
+                                    angle: -d.angle,
+                                    neutral: p.depth - d.neutral,
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect();
+                Prism {
+                    face: far_end.apply_face(&face),
+                    direction: direction.negate(),
+                    depth: p.depth,
+                    corners: p.corners.clone(),
+                    holes,
+                    drafts,
+                }
+            } else {
+                Prism {
+                    face,
+                    direction,
+                    depth: p.depth,
+                    corners: p.corners.clone(),
+                    holes,
+                    drafts: p.drafts.clone(),
+                }
+            }
+        });
+        let threads = self
+            .threads
+            .iter()
+            .map(|t| Thread {
+                origin: transform.apply_point(t.origin),
+                axis: transform.apply_vector(t.axis),
+                ..t.clone()
+            })
+            .collect();
+        // a mirrored prism starts from what was the far end
+        let naming = self.naming.as_ref().map(|n| Naming {
+            reversed: n.reversed != transform.is_reflection(),
+            ..n.clone()
+        });
+        // copies of sheet metal parts are plain solids; unfold the original.
+        // The copy sits alongside the original, so it keeps a mesh of its own
+        // rather than the two taking turns to overwrite a shared one.
+        Solid {
+            truck_solid,
+            prism,
+            threads,
+            sheet: None,
+            naming,
+            triangulated: Default::default(),
+            ..self.clone()
+        }
+    }
+
+    pub fn set_draft(
+        &mut self,
+        ring: usize,
+        vertex: usize,
+        draft: FaceDraft,
+    ) -> Result<(), String> {
+        let prism = self.prism.as_mut().ok_or(
+            "Only the side faces of plain extrusions can be drafted, not revolves, sweeps, \
+                 lofts or bodies that have been joined, cut or reshaped"
+                .to_owned(),
+        )?;
+        let mut drafted = prism.clone();
+        drafted.set_draft(ring, vertex, draft)?;
+        self.truck_solid = drafted.to_truck_solid()?;
+        *prism = drafted;
+        Ok(())
+    }
+
+    pub fn add_hole(&mut self, hole: HoleCut) -> Result<(), String> {
+        let prism = self.prism.as_mut().ok_or(
+            "Holes can only be drilled into plain extrusions, not revolves, sweeps, lofts or \
+                 bodies that have been joined, cut or reshaped"
+                .to_owned(),
+        )?;
+        let mut drilled = prism.clone();
+        drilled.add_hole(hole)?;
+        self.truck_solid = drilled.to_truck_solid()?;
+        *prism = drilled;
+        Ok(())
+    }
+
+    pub fn merge_mirror(&mut self, origin: Point, normal: Vector) -> Result<(), String> {
+        let prism = self
+            .prism
+            .as_ref()
+            .ok_or("Only extruded solids can be merged with their mirror image".to_owned())?;
+        let merged = prism.merge_mirror(origin, normal)?;
+        self.truck_solid = merged.to_truck_solid()?;
+        self.prism = Some(merged);
+        // the merged profile no longer follows the sketch
+        self.naming = None;
+        Ok(())
+    }
+
+    pub fn face_names(&self) -> Vec<Option<FaceName>> {
+        // The name of each face in the order truck keeps them. They are worked
+        // out afresh each time, so they follow the solid through edits that
+        // rebuild it.
+        let faces = self
+            .truck_solid
+            .boundaries()
+            .iter()
+            .flat_map(|shell| shell.face_iter());
+        let (Some(prism), Some(naming)) = (&self.prism, &self.naming) else {
+            return faces.map(|_| None).collect();
+        };
+        faces
+            .map(|face| naming.name(prism.classify(face)?))
+            .collect()
+    }
+
+    pub fn find_face(&self, name: &FaceName) -> Option<Face> {
+        let names = self.face_names();
+        self.truck_solid
+            .boundaries()
+            .iter()
+            .flat_map(|shell| shell.face_iter())
+            .zip(names)
+            .find(|(_, n)| n.as_ref() == Some(name))
+            .map(|(face, _)| face.clone())
+    }
+
+    pub fn find_edge(&self, a: &FaceName, b: &FaceName) -> Option<Edge> {
+        // the edge where two named faces meet
+        let (a, b) = (self.find_face(a)?, self.find_face(b)?);
+        let ids: Vec<_> = b.edge_iter().map(|e| e.id()).collect();
+        let edge = a.edge_iter().find(|e| ids.contains(&e.id()));
+        edge
+    }
+
+    pub fn find_vertex(&self, names: &[FaceName]) -> Option<Vertex> {
+        // the corner where all of the named faces meet
+        let faces = names
+            .iter()
+            .map(|n| self.find_face(n))
+            .collect::<Option<Vec<Face>>>()?;
+        let (first, others) = faces.split_first()?;
+        let vertex = first.vertex_iter().find(|v| {
+            others
+                .iter()
+                .all(|f| f.vertex_iter().any(|w| w.id() == v.id()))
+        });
+        vertex
+    }
+
+    pub fn edge_name(&self, edge: &Edge) -> Option<(FaceName, FaceName)> {
+        // the names of the two faces that meet at an edge, when both have one
+        let names = self.face_names();
+        let mut meeting = self
+            .truck_solid
+            .boundaries()
+            .iter()
+            .flat_map(|shell| shell.face_iter())
+            .zip(names)
+            .filter(|(face, _)| face.edge_iter().any(|e| e.id() == edge.id()))
+            .map(|(_, name)| name);
+        match (meeting.next(), meeting.next()) {
+            (Some(Some(a)), Some(Some(b))) => Some((a, b)),
+            _ => None,
         }
     }
 
     pub fn get_mesh(&self) -> Mesh {
+        // triangulating is slow, so it is only done again once the shape changes
+        let faces = |solid: &truck_modeling::Solid| {
+            solid
+                .boundaries()
+                .iter()
+                .flat_map(|shell| shell.face_iter().map(|f| (f.id(), f.orientation())))
+                .collect::<Vec<_>>()
+        };
+        let mut triangulated = self
+            .triangulated
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some((shape, mesh)) = triangulated.as_ref() {
+            if faces(shape) == faces(&self.truck_solid) {
+                return mesh.clone();
+            }
+        }
+        let mesh = self.triangulate();
+        *triangulated = Some((self.truck_solid.clone(), mesh.clone()));
+        mesh
+    }
+
+    fn triangulate(&self) -> Mesh {
         let mut mesh = self.truck_solid.triangulation(0.001).to_polygon();
         mesh.put_together_same_attrs();
 
@@ -569,38 +3309,37 @@ impl Solid {
     }
 
     pub fn save_as_obj(&self, filename: &str) {
-        let mut mesh = self.truck_solid.triangulation(0.001).to_polygon();
-        mesh.put_together_same_attrs();
-        let file = std::fs::File::create(filename).unwrap();
-        obj::write(&mesh, file).unwrap();
+        let mut file = std::fs::File::create(filename).unwrap();
+        std::io::Write::write_all(&mut file, self.get_obj_text().as_ref()).unwrap();
     }
 
     pub fn get_obj_text(&self) -> String {
+        // OBJ has no unit field, so the unit goes in a comment up top
         let mut mesh = self.truck_solid.triangulation(0.001).to_polygon();
         mesh.put_together_same_attrs();
-        // let mut text = String::new();
         let mut buf = Vec::new();
         obj::write(&mesh, &mut buf).unwrap();
-        let string = String::from_utf8(buf).unwrap();
-        string
-        // fn scale_vertices(line: &str) -> String {
-        //     if line.starts_with("v ") {
-        //         let mut parts = line.split(" ");
-        //         let _ = parts.next();
-        //         let x = parts.next().unwrap().parse::<f64>().unwrap();
-        //         let y = parts.next().unwrap().parse::<f64>().unwrap();
-        //         let z = parts.next().unwrap().parse::<f64>().unwrap();
-        //         let pt = Point::new(x, y, z);
-        //         let scaled = pt.scale(100.0);
-        //         return format!("v {} {} {}\n", scaled.x, scaled.y, scaled.z);
-        //     } else {
-        //         return format!("{}\n", line);
-        //     }
-        // }
-
-        // let scaled: String = string.split("\n").map(scale_vertices).collect();
+        format!(
+            "# units: {}\n{}",
+            self.unit.symbol(),
+            String::from_utf8(buf).unwrap()
+        )
+    }
 
-        // scaled
+    pub fn get_stl_text(&self) -> String {
+        // STL has no units at all and is read as millimetres by convention, so
+        // the solid is scaled to suit
+        let scale = self.unit.millimeters();
+        let scaled = builder::transformed(&self.truck_solid, Matrix4::from_scale(scale));
+        let mesh = scaled.triangulation(0.001 * scale).to_polygon();
+        let mut buf = Vec::new();
+        stl::write(&mesh, &mut buf, stl::STLType::ASCII).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        format!(
+            "solid {} units=mm\n{}",
+            self.name.replace(char::is_whitespace, "_"),
+            text.strip_prefix("solid\n").unwrap_or(&text)
+        )
     }
 
     pub fn get_step_text(&self) -> String {
@@ -613,7 +3352,67 @@ impl Solid {
             },
         )
         .to_string();
-        step_string
+        annotate_threads(declare_length_unit(step_string, self.unit), &self.threads)
+    }
+
+    pub fn cylinders(&self) -> Vec<Cylinder> {
+        // Every face that is a straight line swept around a parallel axis, in the
+        // order truck keeps the faces. These are hole bores and the round faces
+        // of revolved solids.
+        let mut cylinders = vec![];
+        for face in self
+            .truck_solid
+            .boundaries()
+            .iter()
+            .flat_map(|shell| shell.face_iter())
+        {
+            let Surface::RevolutedCurve(processor) = face.oriented_surface() else {
+                continue;
+            };
+            let revolved = processor.entity();
+            let Curve::Line(line) = revolved.entity_curve() else {
+                continue;
+            };
+            let transform = processor.transform();
+            let to_point = |p: Point3| {
+                Point::from_point3(truck_modeling::Transform::transform_point(transform, p))
+            };
+            let origin = to_point(revolved.origin());
+            let axis = Vector::from_vector3(truck_modeling::Transform::transform_vector(
+                transform,
+                revolved.axis(),
+            ))
+            .normalize();
+            let (a, b) = (to_point(line.0), to_point(line.1));
+            let run = b.to_vector().subtract(a.to_vector());
+            if run.cross(axis).length() > 1e-9 * run.length() {
+                continue;
+            }
+
+            let offset = a.to_vector().subtract(origin.to_vector());
+            let radial = offset.subtract(axis.scale(offset.dot(axis)));
+            let radius = radial.length();
+            if radius < 1e-9 {
+                continue;
+            }
+            let (from, to) = (
+                offset.dot(axis),
+                b.to_vector().subtract(origin.to_vector()).dot(axis),
+            );
+            // a bore's face points in toward its axis
+            let mid = Point::from_point3(processor.subs(0.5, 0.0));
+            let normal = Vector::from_vector3(processor.normal(0.5, 0.0));
+            let outward = mid.to_vector().subtract(origin.to_vector());
+            let outward = outward.subtract(axis.scale(outward.dot(axis)));
+            cylinders.push(Cylinder {
+                origin: origin.add_vec(axis.scale(from.min(to))),
+                axis,
+                radius,
+                length: (to - from).abs(),
+                internal: normal.dot(outward) < 0.0,
+            });
+        }
+        cylinders
     }
 
     pub fn save_as_step(&self, filename: &str) {
@@ -0,0 +1,54 @@
+use std::fmt;
+
+// What the workbench and project APIs report when an edit or a step can't be
+// carried out. The geometry code underneath gives plain messages, which come
+// through as GeometryFailure.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    // kind is what was being looked for: a workbench, a step, a variable..
+    NotFound { kind: &'static str, name: String },
+    AlreadyExists { kind: &'static str, name: String },
+    InvalidParameter(String),
+    InvalidExpression(String),
+    // an edit that would leave steps or variables reading from something
+    // missing, later, or themselves
+    Dependency(String),
+    GeometryFailure(String),
+    // a project file that couldn't be written, read or brought up to date
+    File(String),
+}
+
+impl Error {
+    pub fn not_found(kind: &'static str, name: &str) -> Error {
+        Error::NotFound {
+            kind,
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn already_exists(kind: &'static str, name: &str) -> Error {
+        Error::AlreadyExists {
+            kind,
+            name: name.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound { kind, name } => write!(f, "No {} named {}", kind, name),
+            Error::AlreadyExists { kind, name } => {
+                write!(f, "There is already a {} named {}", kind, name)
+            }
+            Error::InvalidParameter(message)
+            | Error::InvalidExpression(message)
+            | Error::Dependency(message)
+            | Error::GeometryFailure(message)
+            | Error::File(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
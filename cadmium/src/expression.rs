@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::units::{Unit, Units};
+
+// Formulas for step values, like "3 * wall + 40". They are kept as text in the
+// workbench and parsed whenever they are evaluated.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    // a number written with a unit, as in "2 mm" or "30 deg"
+    Measure(f64, Unit),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(String, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Value {
+    pub number: f64,
+    // set when the number is an angle in radians, so a bare number can still be
+    // read in the workbench's angle unit
+    pub angle: bool,
+}
+
+impl Value {
+    pub fn plain(number: f64) -> Value {
+        Value {
+            number,
+            angle: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+const CONSTANTS: [(&str, f64); 2] = [("pi", std::f64::consts::PI), ("tau", std::f64::consts::TAU)];
+
+const FUNCTIONS: [&str; 11] = [
+    "sin", "cos", "tan", "asin", "acos", "atan", "sqrt", "abs", "round", "min", "max",
+];
+
+pub fn is_reserved(name: &str) -> bool {
+    CONSTANTS.iter().any(|(c, _)| *c == name) || FUNCTIONS.contains(&name)
+}
+
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // an exponent, as in 1e-3, but not the start of a name like "e2"
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let value = literal
+                .parse::<f64>()
+                .map_err(|_| format!("{} is not a number", literal))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Name(chars[start..i].iter().collect()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        } else {
+            return Err(format!("Unexpected '{}' in {}", c, text));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        let mut left = self.product()?;
+        loop {
+            let operator = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                return Ok(left);
+            };
+            let right = self.product()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unit(&mut self) -> Option<Unit> {
+        // a unit name after a value, as long as it isn't being called
+        let Some(Token::Name(name)) = self.peek() else {
+            return None;
+        };
+        if self.tokens.get(self.position + 1) == Some(&Token::Symbol('(')) {
+            return None;
+        }
+        let unit = Unit::parse(name)?;
+        self.position += 1;
+        Some(unit)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        // a unit applies to the whole product before it, so "1/4 in" is a
+        // quarter inch rather than one over four inches
+        let mut left = self.unary()?;
+        loop {
+            if let Some(unit) = self.unit() {
+                left = Expression::Binary(
+                    Operator::Multiply,
+                    Box::new(left),
+                    Box::new(Expression::Measure(1.0, unit)),
+                );
+            }
+            let operator = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else {
+                return Ok(left);
+            };
+            let right = self.unary()?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat('-') {
+            return Ok(Expression::Negate(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        // right associative, and binding tighter than a leading minus: -2^2 is -4
+        let base = self.atom()?;
+        if self.eat('^') {
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expression, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Number(value)),
+            Some(Token::Name(name)) => {
+                if !self.eat('(') {
+                    return Ok(Expression::Variable(name));
+                }
+                let mut arguments = vec![];
+                if !self.eat(')') {
+                    loop {
+                        arguments.push(self.sum()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        if !self.eat(',') {
+                            return Err(format!("Expected ',' or ')' in call to {}", name));
+                        }
+                    }
+                }
+                Ok(Expression::Call(name, arguments))
+            }
+            Some(Token::Symbol('(')) => {
+                let inner = self.sum()?;
+                if !self.eat(')') {
+                    return Err("Missing ')'".to_owned());
+                }
+                Ok(inner)
+            }
+            Some(Token::Symbol(c)) => Err(format!("Unexpected '{}'", c)),
+            None => Err("Unexpected end of expression".to_owned()),
+        }
+    }
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err("Empty expression".to_owned());
+        }
+        let expression = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {:?} after the expression", token));
+        }
+        Ok(expression)
+    }
+
+    pub fn variables(&self) -> Vec<String> {
+        // every name it reads, other than the built in constants, in order of
+        // first use
+        let mut names: Vec<String> = vec![];
+        self.collect_variables(&mut names);
+        names
+    }
+
+    fn collect_variables(&self, names: &mut Vec<String>) {
+        match self {
+            Expression::Number(_) | Expression::Measure(..) => {}
+            Expression::Variable(name) => {
+                if !is_reserved(name) && !names.contains(name) {
+                    names.push(name.to_owned());
+                }
+            }
+            Expression::Negate(inner) => inner.collect_variables(names),
+            Expression::Binary(_, left, right) => {
+                left.collect_variables(names);
+                right.collect_variables(names);
+            }
+            Expression::Call(_, arguments) => {
+                for argument in arguments {
+                    argument.collect_variables(names);
+                }
+            }
+        }
+    }
+
+    pub fn has_units(&self) -> bool {
+        match self {
+            Expression::Measure(..) => true,
+            Expression::Number(_) | Expression::Variable(_) => false,
+            Expression::Negate(inner) => inner.has_units(),
+            Expression::Binary(_, left, right) => left.has_units() || right.has_units(),
+            Expression::Call(_, arguments) => arguments.iter().any(|a| a.has_units()),
+        }
+    }
+
+    pub fn evaluate(
+        &self,
+        variables: &HashMap<String, Value>,
+        units: &Units,
+    ) -> Result<Value, String> {
+        let value = match self {
+            Expression::Number(value) => Value::plain(*value),
+            Expression::Measure(value, unit) => Value {
+                number: units.convert(*value, *unit),
+                angle: matches!(unit, Unit::Angle(_)),
+            },
+            Expression::Variable(name) => match variables.get(name) {
+                Some(value) => *value,
+                None => CONSTANTS
+                    .iter()
+                    .find(|(c, _)| c == name)
+                    .map(|(_, value)| Value::plain(*value))
+                    .ok_or(format!("No variable named {}", name))?,
+            },
+            Expression::Negate(inner) => {
+                let value = inner.evaluate(variables, units)?;
+                Value {
+                    number: -value.number,
+                    ..value
+                }
+            }
+            Expression::Binary(operator, left, right) => {
+                let (a, b) = (
+                    left.evaluate(variables, units)?,
+                    right.evaluate(variables, units)?,
+                );
+                let angle = a.angle || b.angle;
+                match operator {
+                    Operator::Add => Value {
+                        number: a.number + b.number,
+                        angle,
+                    },
+                    Operator::Subtract => Value {
+                        number: a.number - b.number,
+                        angle,
+                    },
+                    Operator::Multiply => Value {
+                        number: a.number * b.number,
+                        angle,
+                    },
+                    Operator::Divide => {
+                        if b.number == 0.0 {
+                            return Err(format!("Division by zero in {}", self));
+                        }
+                        // an angle over an angle is a plain ratio
+                        Value {
+                            number: a.number / b.number,
+                            angle: a.angle && !b.angle,
+                        }
+                    }
+                    Operator::Power => Value::plain(a.number.powf(b.number)),
+                }
+            }
+            Expression::Call(name, arguments) => {
+                let values = arguments
+                    .iter()
+                    .map(|a| a.evaluate(variables, units))
+                    .collect::<Result<Vec<Value>, String>>()?;
+                call(name, &values)?
+            }
+        };
+        if !value.number.is_finite() {
+            return Err(format!("{} is not a finite number", self));
+        }
+        Ok(value)
+    }
+}
+
+fn call(name: &str, values: &[Value]) -> Result<Value, String> {
+    // the inverse trig functions give angles, and the rest of the one argument
+    // functions give plain numbers
+    let one = |f: fn(f64) -> f64, angle: bool| match values {
+        [x] => Ok(Value {
+            number: f(x.number),
+            angle,
+        }),
+        _ => Err(format!("{} takes one argument", name)),
+    };
+    match name {
+        "sin" => one(f64::sin, false),
+        "cos" => one(f64::cos, false),
+        "tan" => one(f64::tan, false),
+        "asin" => one(f64::asin, true),
+        "acos" => one(f64::acos, true),
+        "atan" => one(f64::atan, true),
+        "sqrt" => one(f64::sqrt, false),
+        "abs" => one(f64::abs, values.iter().any(|v| v.angle)),
+        "round" => one(f64::round, values.iter().any(|v| v.angle)),
+        "min" | "max" => {
+            let first = values
+                .first()
+                .ok_or(format!("{} needs at least one argument", name))?
+                .number;
+            let pick = if name == "min" { f64::min } else { f64::max };
+            Ok(Value {
+                number: values.iter().fold(first, |acc, v| pick(acc, v.number)),
+                angle: values.iter().any(|v| v.angle),
+            })
+        }
+        _ => Err(format!("No function named {}", name)),
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::Measure(value, unit) => write!(f, "{} {}", value, unit.symbol()),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Negate(inner) => write!(f, "-({})", inner),
+            Expression::Binary(operator, left, right) => {
+                let symbol = match operator {
+                    Operator::Add => "+",
+                    Operator::Subtract => "-",
+                    Operator::Multiply => "*",
+                    Operator::Divide => "/",
+                    Operator::Power => "^",
+                };
+                write!(f, "({} {} {})", left, symbol, right)
+            }
+            Expression::Call(name, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", name, arguments.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> f64 {
+        Expression::parse(text)
+            .unwrap()
+            .evaluate(&HashMap::new(), &Units::default())
+            .unwrap()
+            .number
+    }
+
+    #[test]
+    fn precedence_and_functions() {
+        assert_eq!(value("1 + 2 * 3"), 7.0);
+        assert_eq!(value("(1 + 2) * 3"), 9.0);
+        assert_eq!(value("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(value("-2 ^ 2"), -4.0);
+        assert_eq!(value("10 - 4 - 3"), 3.0);
+        assert_eq!(value("1.5e1 / 3"), 5.0);
+        assert_eq!(value("max(1, 7, 3) + min(2, -1)"), 6.0);
+        assert!((value("2 * cos(pi)") + 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn variables_and_errors() {
+        let expression = Expression::parse("3 * wall + 40 + wall / gap - pi").unwrap();
+        assert_eq!(expression.variables(), vec!["wall", "gap"]);
+
+        let mut variables = HashMap::new();
+        variables.insert("wall".to_owned(), Value::plain(2.0));
+        let units = Units::default();
+        assert!(expression.evaluate(&variables, &units).is_err());
+        variables.insert("gap".to_owned(), Value::plain(4.0));
+        let width = expression.evaluate(&variables, &units).unwrap().number;
+        assert!((width - (46.5 - std::f64::consts::PI)).abs() < 1e-12);
+
+        assert!(Expression::parse("").is_err());
+        assert!(Expression::parse("2 +").is_err());
+        assert!(Expression::parse("(1 + 2").is_err());
+        assert!(Expression::parse("1 2").is_err());
+        assert!(Expression::parse("3 $ 4").is_err());
+        assert!(Expression::parse("1 / (2 - 2)")
+            .unwrap()
+            .evaluate(&variables, &units)
+            .is_err());
+        assert!(Expression::parse("nope(1)")
+            .unwrap()
+            .evaluate(&variables, &units)
+            .is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::error::Error;
+use crate::project::Project;
+use crate::units::Units;
+
+// A .cadmium file is a project with the format version it was written in,
+// either as JSON or, more compactly, as MessagePack after a short marker.
+// Both keep field names, so older files can be brought up to date as plain
+// documents before they are read in.
+
+pub const VERSION: u64 = 1;
+
+const MARKER: &[u8] = b"CADMIUM\0";
+
+// MIGRATIONS[n] takes a version n document to version n + 1, which it is
+// given to write into the document
+type Migration = fn(Value, u64) -> Result<Value, Error>;
+const MIGRATIONS: [Migration; VERSION as usize] = [workbench_to_project];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Json,
+    Binary,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    format: &'static str,
+    version: u64,
+    project: &'a Project,
+}
+
+pub fn write_project(project: &Project, format: FileFormat) -> Result<Vec<u8>, Error> {
+    let document = Document {
+        format: "cadmium",
+        version: VERSION,
+        project,
+    };
+    let written = match format {
+        FileFormat::Json => serde_json::to_vec_pretty(&document).map_err(|e| e.to_string()),
+        FileFormat::Binary => {
+            let mut bytes = MARKER.to_vec();
+            rmp_serde::encode::write_named(&mut bytes, &document)
+                .map(|_| bytes)
+                .map_err(|e| e.to_string())
+        }
+    };
+    written.map_err(Error::File)
+}
+
+pub fn read_project(bytes: &[u8]) -> Result<Project, Error> {
+    let document: Result<Value, String> = match bytes.strip_prefix(MARKER) {
+        Some(packed) => rmp_serde::from_slice(packed).map_err(|e| e.to_string()),
+        None => serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+    };
+    let mut document = migrate(document.map_err(Error::File)?)?;
+    let project = document
+        .get_mut("project")
+        .map(Value::take)
+        .ok_or(Error::File("The file has no project in it".to_owned()))?;
+    serde_json::from_value(project).map_err(|e| Error::File(e.to_string()))
+}
+
+fn migrate(mut document: Value) -> Result<Value, Error> {
+    let mut version = match document.get("version") {
+        Some(version) => version
+            .as_u64()
+            .ok_or(Error::File(format!("{} is not a format version", version)))?,
+        // before there was a project file, a workbench was written out alone
+        None if document.get("steps").is_some() => 0,
+        None => return Err(Error::File("Not a cadmium project".to_owned())),
+    };
+    if version > VERSION {
+        return Err(Error::File(format!(
+            "The file is in format version {}, which is newer than this cadmium reads",
+            version
+        )));
+    }
+    while version < VERSION {
+        document = MIGRATIONS[version as usize](document, version + 1)?;
+        version += 1;
+    }
+    Ok(document)
+}
+
+fn workbench_to_project(workbench: Value, version: u64) -> Result<Value, Error> {
+    let name = workbench
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or(Error::File("The workbench has no name".to_owned()))?
+        .to_owned();
+    let units = match workbench.get("units") {
+        Some(units) => units.clone(),
+        None => json!(Units::default()),
+    };
+    Ok(json!({
+        "format": "cadmium",
+        "version": version,
+        "project": {
+            "name": name,
+            "workbenches": [workbench],
+            "assemblies": [],
+            "units": units,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::LengthUnit;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut project = Project::new("Bracket", true);
+        project
+            .set_variable("Workbench 1", "depth", "3/8 in")
+            .unwrap();
+        project
+            .bind_step_parameter("Workbench 1", "Extrude 1", "depth", "depth * 2")
+            .unwrap();
+        project
+            .set_units(Units::new(LengthUnit::Centimeter, Default::default()))
+            .unwrap();
+        let steps = |p: &Project| format!("{:?}", p.workbenches()[0].steps);
+
+        let json = project.to_bytes(FileFormat::Json).unwrap();
+        let binary = project.to_bytes(FileFormat::Binary).unwrap();
+        assert!(binary.len() < json.len());
+        for bytes in [json, binary] {
+            let loaded = Project::from_bytes(&bytes).unwrap();
+            assert_eq!(loaded.name, "Bracket");
+            assert_eq!(loaded.units, project.units);
+            assert_eq!(steps(&loaded), steps(&project));
+            assert_eq!(
+                loaded.workbenches()[0].bindings,
+                project.workbenches()[0].bindings
+            );
+            let view = loaded.workbenches()[0].create_view(100);
+            assert!(view.solids.contains_key("Extrude 1_0"));
+        }
+
+        // a workbench written out on its own, from before there were project
+        // files, comes in as a project of one
+        let workbench = serde_json::to_vec(&project.workbenches()[0]).unwrap();
+        let loaded = Project::from_bytes(&workbench).unwrap();
+        assert_eq!(loaded.name, "Workbench 1");
+        assert_eq!(steps(&loaded), steps(&project));
+
+        let newer = br#"{"format": "cadmium", "version": 99, "project": {}}"#;
+        assert!(matches!(Project::from_bytes(newer), Err(Error::File(_))));
+        assert!(Project::from_bytes(b"solid cube").is_err());
+        assert!(Project::from_bytes(b"CADMIUM\0\xc1").is_err());
+    }
+}
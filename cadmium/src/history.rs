@@ -0,0 +1,200 @@
+use crate::units::Units;
+use crate::workbench::{Binding, Step};
+
+// Every edit to a project is made as a Command, so it can be kept and played
+// back. Carrying one out gives back the Commands that undo it.
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    SetStepParameters {
+        workbench: String,
+        step: String,
+        parameters: Vec<String>,
+        values: Vec<f64>,
+    },
+    SetSelectedForOperation {
+        workbench: String,
+        step: String,
+        parameter: String,
+        sketch: String,
+        selection: Vec<u64>,
+    },
+    AddSegmentToSketch {
+        workbench: String,
+        sketch: String,
+        start: (f64, f64),
+        end: (f64, f64),
+    },
+    SetUnits(Units),
+    // puts back the project's units and those of the workbenches named
+    RestoreUnits {
+        project: Units,
+        workbenches: Vec<(String, Units)>,
+    },
+    RollBack {
+        workbench: String,
+        position: usize,
+    },
+    ReleaseRollback {
+        workbench: String,
+    },
+    // a new step at the rollback bar
+    AddStep {
+        workbench: String,
+        step: Box<Step>,
+    },
+    InsertStep {
+        workbench: String,
+        index: usize,
+        step: Box<Step>,
+    },
+    DeleteStep {
+        workbench: String,
+        step: String,
+    },
+    MoveStep {
+        workbench: String,
+        step: String,
+        index: usize,
+    },
+    SetStepSuppressed {
+        workbench: String,
+        step: String,
+        suppressed: bool,
+    },
+    RenameStep {
+        workbench: String,
+        step: String,
+        new_name: String,
+    },
+    SetVariable {
+        workbench: String,
+        name: String,
+        expression: String,
+    },
+    BindStepParameter {
+        workbench: String,
+        step: String,
+        parameter: String,
+        expression: String,
+    },
+    RemoveVariable {
+        workbench: String,
+        name: String,
+    },
+    // Puts a step back as it was, with the formulas its parameters had. Edits
+    // to what a step holds are undone this way.
+    RestoreStep {
+        workbench: String,
+        step: Box<Step>,
+        bindings: Vec<Binding>,
+    },
+}
+
+impl Command {
+    pub fn workbench(&self) -> Option<&str> {
+        match self {
+            Command::SetStepParameters { workbench, .. }
+            | Command::SetSelectedForOperation { workbench, .. }
+            | Command::AddSegmentToSketch { workbench, .. }
+            | Command::RollBack { workbench, .. }
+            | Command::ReleaseRollback { workbench }
+            | Command::AddStep { workbench, .. }
+            | Command::InsertStep { workbench, .. }
+            | Command::DeleteStep { workbench, .. }
+            | Command::MoveStep { workbench, .. }
+            | Command::SetStepSuppressed { workbench, .. }
+            | Command::RenameStep { workbench, .. }
+            | Command::SetVariable { workbench, .. }
+            | Command::BindStepParameter { workbench, .. }
+            | Command::RemoveVariable { workbench, .. }
+            | Command::RestoreStep { workbench, .. } => Some(workbench),
+            Command::SetUnits(_) | Command::RestoreUnits { .. } => None,
+        }
+    }
+}
+
+// Commands that are undone and redone as one, like all the small edits made
+// while dragging a point
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    pub commands: Vec<Command>,
+    // in the order they were made, so they are undone from the back
+    pub inverses: Vec<Command>,
+}
+
+impl Transaction {
+    pub fn record(&mut self, command: Command, inverses: Vec<Command>) {
+        // A step edited over and over, as while dragging, only needs putting
+        // back to the state it had before the first edit
+        let repeated = match (self.inverses.last(), &inverses[..]) {
+            (
+                Some(Command::RestoreStep {
+                    workbench, step, ..
+                }),
+                [Command::RestoreStep {
+                    workbench: w,
+                    step: s,
+                    ..
+                }],
+            ) => workbench == w && step.name() == s.name(),
+            _ => false,
+        };
+        self.commands.push(command);
+        if !repeated {
+            // inverses are given in the order they run, and undone from the back
+            self.inverses.extend(inverses.into_iter().rev());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub undo: Vec<Transaction>,
+    pub redo: Vec<Transaction>,
+    // the transaction edits are going into, if one has been begun
+    pub open: Option<Transaction>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    pub fn record(&mut self, command: Command, inverses: Vec<Command>) {
+        self.redo.clear();
+        match self.open.as_mut() {
+            Some(transaction) => transaction.record(command, inverses),
+            None => {
+                let mut transaction = Transaction::default();
+                transaction.record(command, inverses);
+                self.undo.push(transaction);
+            }
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.end();
+        self.open = Some(Transaction::default());
+    }
+
+    pub fn end(&mut self) {
+        if let Some(transaction) = self.open.take() {
+            if !transaction.is_empty() {
+                self.undo.push(transaction);
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.open.as_ref().is_some_and(|t| !t.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
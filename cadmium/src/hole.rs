@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fmt;
+
+const INCH: f64 = 25.4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ThreadStandard {
+    IsoMetric,
+    Unc,
+    Unf,
+}
+
+impl fmt::Display for ThreadStandard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreadStandard::IsoMetric => write!(f, "ISO Metric"),
+            ThreadStandard::Unc => write!(f, "UNC"),
+            ThreadStandard::Unf => write!(f, "UNF"),
+        }
+    }
+}
+
+// One row of the hole table. All lengths are in millimeters and the
+// countersink angle is the included angle in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoleSize {
+    pub standard: ThreadStandard,
+    pub size: &'static str,
+    pub major_diameter: f64,
+    pub pitch: f64,
+    pub tap_drill: f64,
+    pub clearance: f64,
+    pub counterbore_diameter: f64,
+    pub counterbore_depth: f64,
+    pub countersink_diameter: f64,
+    pub countersink_angle: f64,
+}
+
+impl HoleSize {
+    pub fn designation(&self) -> String {
+        match self.standard {
+            ThreadStandard::IsoMetric => format!("{}x{}", self.size, self.pitch),
+            ThreadStandard::Unc | ThreadStandard::Unf => {
+                let per_inch = (INCH / self.pitch).round();
+                format!("{}-{} {}", self.size, per_inch, self.standard)
+            }
+        }
+    }
+}
+
+const fn metric(
+    size: &'static str,
+    major: f64,
+    pitch: f64,
+    tap_drill: f64,
+    clearance: f64,
+    counterbore: f64,
+    countersink: f64,
+) -> HoleSize {
+    HoleSize {
+        standard: ThreadStandard::IsoMetric,
+        size,
+        major_diameter: major,
+        pitch,
+        tap_drill,
+        clearance,
+        counterbore_diameter: counterbore,
+        // deep enough to sink a socket head cap screw, whose head is as tall as its shank is wide
+        counterbore_depth: major,
+        countersink_diameter: countersink,
+        countersink_angle: PI / 2.0,
+    }
+}
+
+// inch sizes are given in inches, with the thread as (major diameter, threads per inch)
+const fn unified(
+    standard: ThreadStandard,
+    size: &'static str,
+    (major, tpi): (f64, f64),
+    tap_drill: f64,
+    clearance: f64,
+    counterbore: f64,
+    countersink: f64,
+) -> HoleSize {
+    HoleSize {
+        standard,
+        size,
+        major_diameter: major * INCH,
+        pitch: INCH / tpi,
+        tap_drill: tap_drill * INCH,
+        clearance: clearance * INCH,
+        counterbore_diameter: counterbore * INCH,
+        counterbore_depth: major * INCH,
+        countersink_diameter: countersink * INCH,
+        countersink_angle: 82.0 * PI / 180.0,
+    }
+}
+
+// Coarse pitch metric sizes with medium clearance holes (ISO 273), socket head
+// counterbores and flat head countersinks, followed by the common unified sizes
+// with normal fit clearance holes.
+pub const HOLE_SIZES: &[HoleSize] = &[
+    metric("M1.6", 1.6, 0.35, 1.25, 1.8, 3.5, 3.6),
+    metric("M2", 2.0, 0.4, 1.6, 2.4, 4.4, 4.4),
+    metric("M2.5", 2.5, 0.45, 2.05, 2.9, 5.5, 5.5),
+    metric("M3", 3.0, 0.5, 2.5, 3.4, 6.5, 6.72),
+    metric("M4", 4.0, 0.7, 3.3, 4.5, 8.0, 8.96),
+    metric("M5", 5.0, 0.8, 4.2, 5.5, 10.0, 11.2),
+    metric("M6", 6.0, 1.0, 5.0, 6.6, 11.0, 13.44),
+    metric("M8", 8.0, 1.25, 6.8, 9.0, 15.0, 17.92),
+    metric("M10", 10.0, 1.5, 8.5, 11.0, 18.0, 22.4),
+    metric("M12", 12.0, 1.75, 10.2, 13.5, 20.0, 26.88),
+    metric("M14", 14.0, 2.0, 12.0, 15.5, 24.0, 30.8),
+    metric("M16", 16.0, 2.0, 14.0, 17.5, 26.0, 33.6),
+    metric("M20", 20.0, 2.5, 17.5, 22.0, 33.0, 40.32),
+    metric("M24", 24.0, 3.0, 21.0, 26.0, 40.0, 48.0),
+    unified(
+        ThreadStandard::Unc,
+        "#4",
+        (0.112, 40.0),
+        0.089,
+        0.120,
+        0.219,
+        0.255,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "#6",
+        (0.138, 32.0),
+        0.1065,
+        0.1495,
+        0.281,
+        0.307,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "#8",
+        (0.164, 32.0),
+        0.136,
+        0.177,
+        0.312,
+        0.359,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "#10",
+        (0.190, 24.0),
+        0.1495,
+        0.201,
+        0.375,
+        0.411,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "1/4",
+        (0.250, 20.0),
+        0.201,
+        0.266,
+        0.438,
+        0.531,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "5/16",
+        (0.3125, 18.0),
+        0.257,
+        0.332,
+        0.531,
+        0.656,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "3/8",
+        (0.375, 16.0),
+        0.3125,
+        0.397,
+        0.625,
+        0.781,
+    ),
+    unified(
+        ThreadStandard::Unc,
+        "1/2",
+        (0.500, 13.0),
+        0.4219,
+        0.531,
+        0.812,
+        1.031,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "#4",
+        (0.112, 48.0),
+        0.0935,
+        0.120,
+        0.219,
+        0.255,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "#6",
+        (0.138, 40.0),
+        0.113,
+        0.1495,
+        0.281,
+        0.307,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "#8",
+        (0.164, 36.0),
+        0.136,
+        0.177,
+        0.312,
+        0.359,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "#10",
+        (0.190, 32.0),
+        0.159,
+        0.201,
+        0.375,
+        0.411,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "1/4",
+        (0.250, 28.0),
+        0.213,
+        0.266,
+        0.438,
+        0.531,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "5/16",
+        (0.3125, 24.0),
+        0.272,
+        0.332,
+        0.531,
+        0.656,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "3/8",
+        (0.375, 24.0),
+        0.332,
+        0.397,
+        0.625,
+        0.781,
+    ),
+    unified(
+        ThreadStandard::Unf,
+        "1/2",
+        (0.500, 20.0),
+        0.4531,
+        0.531,
+        0.812,
+        1.031,
+    ),
+];
+
+pub fn find_size(standard: ThreadStandard, size: &str) -> Option<&'static HoleSize> {
+    HOLE_SIZES
+        .iter()
+        .find(|s| s.standard == standard && s.size == size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_lookups() {
+        let m6 = find_size(ThreadStandard::IsoMetric, "M6").unwrap();
+        assert_eq!(m6.designation(), "M6x1");
+        assert!(m6.tap_drill < m6.major_diameter && m6.major_diameter < m6.clearance);
+
+        let quarter = find_size(ThreadStandard::Unc, "1/4").unwrap();
+        assert!((quarter.major_diameter - 6.35).abs() < 1e-9);
+        assert_eq!(quarter.designation(), "1/4-20 UNC");
+        assert_eq!(
+            find_size(ThreadStandard::Unf, "1/4").unwrap().designation(),
+            "1/4-28 UNF"
+        );
+        assert!(find_size(ThreadStandard::IsoMetric, "M7").is_none());
+
+        for size in HOLE_SIZES.iter() {
+            assert!(size.tap_drill < size.major_diameter, "{}", size.size);
+            assert!(size.clearance > size.major_diameter, "{}", size.size);
+            assert!(size.counterbore_diameter > size.clearance, "{}", size.size);
+            assert!(size.countersink_diameter > size.clearance, "{}", size.size);
+        }
+    }
+}
@@ -6,10 +6,19 @@ use truck_modeling::*;
 
 pub mod assembly;
 pub mod common;
+pub mod error;
+pub mod expression;
+pub mod file;
+pub mod history;
+pub mod hole;
 pub mod project;
+pub mod sheet_metal;
 pub mod sketch;
+pub mod units;
 pub mod workbench;
 
+pub use error::Error;
+
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
@@ -1,13 +1,26 @@
+use serde::{Deserialize, Serialize};
+
 use crate::assembly::Assembly;
-use crate::workbench::Workbench;
+use crate::error::Error;
+use crate::file::{read_project, write_project, FileFormat};
+use crate::history::{Command, History, Transaction};
+use crate::units::Units;
+use crate::workbench::{check_parameter_values, Step, Workbench};
 
 // A Project is the overall thing. It contains many workbenches and assemblies
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Project {
     pub name: String,
-    pub workbenches: Vec<Workbench>,
+    // only changed through commands, so every edit can be undone
+    workbenches: Vec<Workbench>,
     pub assemblies: Vec<Assembly>,
+    // the units new workbenches start out in
+    #[serde(default)]
+    pub units: Units,
+    // edits made since the project was opened, which aren't saved with it
+    #[serde(skip)]
+    pub history: History,
 }
 
 impl Project {
@@ -16,9 +29,12 @@ impl Project {
             name: name.to_owned(),
             workbenches: vec![],
             assemblies: vec![],
+            units: Units::default(),
+            history: History::new(),
         };
 
         let mut bench0 = Workbench::new("Workbench 1");
+        bench0.units = p.units;
 
         if add_dummy_data {
             bench0.add_sketch_and_extrusion();
@@ -29,6 +45,28 @@ impl Project {
         p
     }
 
+    pub fn to_bytes(&self, format: FileFormat) -> Result<Vec<u8>, Error> {
+        write_project(self, format)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Project, Error> {
+        // either format, and any earlier version of it
+        read_project(bytes)
+    }
+
+    pub fn save(&self, filename: &str, format: FileFormat) -> Result<(), Error> {
+        std::fs::write(filename, self.to_bytes(format)?).map_err(|e| Error::File(e.to_string()))
+    }
+
+    pub fn load(filename: &str) -> Result<Project, Error> {
+        let bytes = std::fs::read(filename).map_err(|e| Error::File(e.to_string()))?;
+        Project::from_bytes(&bytes)
+    }
+
+    pub fn workbenches(&self) -> &[Workbench] {
+        &self.workbenches
+    }
+
     pub fn get_workbench(&self, name: &str) -> Option<&Workbench> {
         for wb in self.workbenches.iter() {
             if wb.name == name {
@@ -38,23 +76,144 @@ impl Project {
         None
     }
 
+    fn workbench_mut(&mut self, name: &str) -> Result<&mut Workbench, Error> {
+        self.workbenches
+            .iter_mut()
+            .find(|wb| wb.name == name)
+            .ok_or(Error::not_found("workbench", name))
+    }
+
     pub fn set_step_parameters(
         &mut self,
         workbench_name: &str,
         step_name: &str,
         parameter_names: Vec<String>,
         parameter_values: Vec<f64>,
-    ) -> Result<(), String> {
-        // let wb = self
-        //     .get_workbench(workbench_name)
-        //     .ok_or(format!("No workbench named {}", workbench_name))?;
-        for wb in self.workbenches.iter_mut() {
-            if wb.name == workbench_name {
-                return wb.set_step_parameters(step_name, parameter_names, parameter_values);
-            }
-        }
+    ) -> Result<(), Error> {
+        self.apply(Command::SetStepParameters {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            parameters: parameter_names,
+            values: parameter_values,
+        })
+    }
+
+    pub fn set_units(&mut self, units: Units) -> Result<(), Error> {
+        self.apply(Command::SetUnits(units))
+    }
 
-        return Ok(());
+    pub fn roll_back(&mut self, workbench_name: &str, position: usize) -> Result<(), Error> {
+        self.apply(Command::RollBack {
+            workbench: workbench_name.to_owned(),
+            position,
+        })
+    }
+
+    pub fn release_rollback(&mut self, workbench_name: &str) -> Result<(), Error> {
+        self.apply(Command::ReleaseRollback {
+            workbench: workbench_name.to_owned(),
+        })
+    }
+
+    pub fn add_step(&mut self, workbench_name: &str, step: Step) -> Result<(), Error> {
+        self.apply(Command::AddStep {
+            workbench: workbench_name.to_owned(),
+            step: Box::new(step),
+        })
+    }
+
+    pub fn insert_step(
+        &mut self,
+        workbench_name: &str,
+        index: usize,
+        step: Step,
+    ) -> Result<(), Error> {
+        self.apply(Command::InsertStep {
+            workbench: workbench_name.to_owned(),
+            index,
+            step: Box::new(step),
+        })
+    }
+
+    pub fn delete_step(&mut self, workbench_name: &str, step_name: &str) -> Result<(), Error> {
+        self.apply(Command::DeleteStep {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+        })
+    }
+
+    pub fn move_step(
+        &mut self,
+        workbench_name: &str,
+        step_name: &str,
+        index: usize,
+    ) -> Result<(), Error> {
+        self.apply(Command::MoveStep {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            index,
+        })
+    }
+
+    pub fn set_step_suppressed(
+        &mut self,
+        workbench_name: &str,
+        step_name: &str,
+        suppressed: bool,
+    ) -> Result<(), Error> {
+        self.apply(Command::SetStepSuppressed {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            suppressed,
+        })
+    }
+
+    pub fn rename_step(
+        &mut self,
+        workbench_name: &str,
+        step_name: &str,
+        new_name: &str,
+    ) -> Result<(), Error> {
+        self.apply(Command::RenameStep {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            new_name: new_name.to_owned(),
+        })
+    }
+
+    pub fn set_variable(
+        &mut self,
+        workbench_name: &str,
+        name: &str,
+        expression: &str,
+    ) -> Result<(), Error> {
+        self.apply(Command::SetVariable {
+            workbench: workbench_name.to_owned(),
+            name: name.to_owned(),
+            expression: expression.to_owned(),
+        })
+    }
+
+    pub fn remove_variable(&mut self, workbench_name: &str, name: &str) -> Result<(), Error> {
+        self.apply(Command::RemoveVariable {
+            workbench: workbench_name.to_owned(),
+            name: name.to_owned(),
+        })
+    }
+
+    pub fn bind_step_parameter(
+        &mut self,
+        workbench_name: &str,
+        step_name: &str,
+        parameter_name: &str,
+        expression: &str,
+    ) -> Result<(), Error> {
+        self.apply(Command::BindStepParameter {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            parameter: parameter_name.to_owned(),
+            expression: expression.to_owned(),
+        })
     }
 
     pub fn set_selected_for_operation(
@@ -64,19 +223,14 @@ impl Project {
         parameter_name: &str,
         sketch_name: &str,
         selection: Vec<u64>,
-    ) -> Result<(), String> {
-        for wb in self.workbenches.iter_mut() {
-            if wb.name == workbench_name {
-                return wb.set_selected_for_operation(
-                    step_name,
-                    parameter_name,
-                    sketch_name,
-                    selection,
-                );
-            }
-        }
-
-        return Err(format!("No workbench named {}", workbench_name));
+    ) -> Result<(), Error> {
+        self.apply(Command::SetSelectedForOperation {
+            workbench: workbench_name.to_owned(),
+            step: step_name.to_owned(),
+            parameter: parameter_name.to_owned(),
+            sketch: sketch_name.to_owned(),
+            selection,
+        })
     }
 
     pub fn add_segment_to_sketch(
@@ -87,19 +241,304 @@ impl Project {
         y1: f64,
         x2: f64,
         y2: f64,
-    ) -> Result<(), String> {
-        for wb in self.workbenches.iter_mut() {
-            if wb.name == workbench_name {
-                return wb.add_segment_to_sketch(sketch_name, x1, y1, x2, y2);
+    ) -> Result<(), Error> {
+        self.apply(Command::AddSegmentToSketch {
+            workbench: workbench_name.to_owned(),
+            sketch: sketch_name.to_owned(),
+            start: (x1, y1),
+            end: (x2, y2),
+        })
+    }
+
+    pub fn apply(&mut self, command: Command) -> Result<(), Error> {
+        // A failed edit changes nothing and isn't kept. A successful one
+        // clears whatever could have been redone.
+        let inverses = self.execute(&command)?;
+        self.history.record(command, inverses);
+        Ok(())
+    }
+
+    pub fn begin_transaction(&mut self) {
+        // edits until end_transaction are undone in one go
+        self.history.begin();
+    }
+
+    pub fn end_transaction(&mut self) {
+        self.history.end();
+    }
+
+    pub fn undo(&mut self) -> Result<(), Error> {
+        // Carried out all or nothing, so an undo that fails part way leaves
+        // the project as it was and stays on the stack
+        self.history.end();
+        let Some(transaction) = self.history.undo.pop() else {
+            return Ok(());
+        };
+        let prior = (self.units, self.workbenches.clone());
+        for inverse in transaction.inverses.iter().rev() {
+            if let Err(e) = self.execute(inverse) {
+                (self.units, self.workbenches) = prior;
+                self.history.undo.push(transaction);
+                return Err(e);
             }
         }
+        self.history.redo.push(transaction);
+        Ok(())
+    }
 
-        return Ok(());
+    pub fn redo(&mut self) -> Result<(), Error> {
+        self.history.end();
+        let Some(transaction) = self.history.redo.pop() else {
+            return Ok(());
+        };
+        let prior = (self.units, self.workbenches.clone());
+        let mut redone = Transaction::default();
+        for command in transaction.commands.iter() {
+            match self.execute(command) {
+                Ok(inverses) => redone.record(command.clone(), inverses),
+                Err(e) => {
+                    (self.units, self.workbenches) = prior;
+                    self.history.redo.push(transaction);
+                    return Err(e);
+                }
+            }
+        }
+        self.history.undo.push(redone);
+        Ok(())
+    }
+
+    fn execute(&mut self, command: &Command) -> Result<Vec<Command>, Error> {
+        // carries out the command and gives back the ones that undo it, in
+        // the order they are to run
+        let Some(workbench_name) = command.workbench() else {
+            let (project, workbenches) = match command {
+                Command::SetUnits(units) => (
+                    *units,
+                    self.workbenches
+                        .iter()
+                        .map(|wb| (wb.name.to_owned(), *units))
+                        .collect(),
+                ),
+                Command::RestoreUnits {
+                    project,
+                    workbenches,
+                } => (*project, workbenches.clone()),
+                _ => unreachable!("only unit changes reach past a single workbench"),
+            };
+            return self.restore_units(project, workbenches).map(|c| vec![c]);
+        };
+
+        let wb = self.workbench_mut(workbench_name)?;
+        let inverses = inverses(wb, command)?;
+        let edited = match command {
+            Command::SetStepParameters {
+                step,
+                parameters,
+                values,
+                ..
+            } => wb.set_step_parameters(step, parameters.clone(), values.clone()),
+            Command::SetSelectedForOperation {
+                step,
+                parameter,
+                sketch,
+                selection,
+                ..
+            } => wb.set_selected_for_operation(step, parameter, sketch, selection.clone()),
+            Command::AddSegmentToSketch {
+                sketch, start, end, ..
+            } => wb.add_segment_to_sketch(sketch, start.0, start.1, end.0, end.1),
+            Command::RollBack { position, .. } => wb.roll_back(*position),
+            Command::ReleaseRollback { .. } => {
+                wb.release_rollback();
+                Ok(())
+            }
+            Command::AddStep { step, .. } => wb.add_step((**step).clone()),
+            Command::InsertStep { index, step, .. } => wb.insert_step(*index, (**step).clone()),
+            Command::DeleteStep { step, .. } => wb.delete_step(step),
+            Command::MoveStep { step, index, .. } => wb.move_step(step, *index),
+            Command::SetStepSuppressed {
+                step, suppressed, ..
+            } => wb.set_step_suppressed(step, *suppressed),
+            Command::RenameStep { step, new_name, .. } => wb.rename_step(step, new_name),
+            Command::SetVariable {
+                name, expression, ..
+            } => wb.set_variable(name, expression),
+            Command::RemoveVariable { name, .. } => wb.remove_variable(name),
+            Command::BindStepParameter {
+                step,
+                parameter,
+                expression,
+                ..
+            } => wb.bind_step_parameter(step, parameter, expression),
+            Command::RestoreStep { step, bindings, .. } => {
+                wb.restore_step((**step).clone(), bindings.clone())
+            }
+            Command::SetUnits(_) | Command::RestoreUnits { .. } => unreachable!(),
+        };
+        if let Err(e) = edited {
+            // the edits to a single step can fail part way through, so the
+            // step is put back whole
+            if let [Command::RestoreStep { step, bindings, .. }] = &inverses[..] {
+                wb.restore_step((**step).clone(), bindings.clone())?;
+            }
+            return Err(e);
+        }
+        Ok(inverses)
+    }
 
-        // .ok_or(format!("No workbench named {}", workbench_name))?;
+    fn restore_units(
+        &mut self,
+        project: Units,
+        workbenches: Vec<(String, Units)>,
+    ) -> Result<Command, Error> {
+        // Gives back the command that puts back the project default and the
+        // units of just the workbenches that changed. Those already changed
+        // are set back if one fails.
+        let mut changed = vec![];
+        for (name, units) in workbenches {
+            let wb = self.workbench_mut(&name);
+            let edited = wb.and_then(|wb| {
+                let prior = wb.units;
+                wb.set_units(units).map(|_| prior)
+            });
+            match edited {
+                Ok(prior) if prior != units => changed.push((name, prior)),
+                Ok(_) => {}
+                Err(e) => {
+                    for (name, prior) in changed {
+                        self.workbench_mut(&name)?.set_units(prior)?;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        let prior = self.units;
+        self.units = project;
+        Ok(Command::RestoreUnits {
+            project: prior,
+            workbenches: changed,
+        })
     }
 }
 
+fn inverses(wb: &Workbench, command: &Command) -> Result<Vec<Command>, Error> {
+    // the commands that undo one about to be carried out on wb, worked out
+    // from how it stands beforehand
+    let workbench = wb.name.to_owned();
+    let restore_step = |name: &str| {
+        let index = wb.step_index(name).ok_or(Error::not_found("step", name))?;
+        Ok(Command::RestoreStep {
+            workbench: workbench.to_owned(),
+            step: Box::new(wb.steps[index].clone()),
+            bindings: wb
+                .bindings
+                .iter()
+                .filter(|b| b.step == name)
+                .cloned()
+                .collect(),
+        })
+    };
+    // steps going in and out move the bar, which is then put back exactly
+    let restore_rollback = || match wb.rollback {
+        Some(position) => Command::RollBack {
+            workbench: workbench.to_owned(),
+            position,
+        },
+        None => Command::ReleaseRollback {
+            workbench: workbench.to_owned(),
+        },
+    };
+    let inverses = match command {
+        Command::SetStepParameters {
+            step,
+            parameters,
+            values,
+            ..
+        } => {
+            // a bad edit is turned away before anything is worked out from it
+            check_parameter_values(parameters, values)?;
+            vec![restore_step(step)?]
+        }
+        Command::SetSelectedForOperation { step, .. } | Command::BindStepParameter { step, .. } => {
+            vec![restore_step(step)?]
+        }
+        Command::AddSegmentToSketch { sketch, .. } => vec![restore_step(sketch)?],
+        Command::RestoreStep { step, .. } => vec![restore_step(step.name())?],
+        Command::RollBack { .. } | Command::ReleaseRollback { .. } => vec![restore_rollback()],
+        Command::AddStep { step, .. } | Command::InsertStep { step, .. } => {
+            vec![Command::DeleteStep {
+                workbench,
+                step: step.name().to_owned(),
+            }]
+        }
+        Command::DeleteStep { step, .. } => {
+            let Some(index) = wb.step_index(step) else {
+                return Err(Error::not_found("step", step));
+            };
+            let mut inverses = vec![Command::InsertStep {
+                workbench: workbench.to_owned(),
+                index,
+                step: Box::new(wb.steps[index].clone()),
+            }];
+            for binding in wb.bindings.iter().filter(|b| &b.step == step) {
+                inverses.push(Command::BindStepParameter {
+                    workbench: workbench.to_owned(),
+                    step: step.to_owned(),
+                    parameter: binding.parameter.to_owned(),
+                    expression: binding.expression.to_owned(),
+                });
+            }
+            if wb.suppressed.contains(step) {
+                inverses.push(Command::SetStepSuppressed {
+                    workbench: workbench.to_owned(),
+                    step: step.to_owned(),
+                    suppressed: true,
+                });
+            }
+            inverses.push(restore_rollback());
+            inverses
+        }
+        Command::MoveStep { step, .. } => {
+            let Some(index) = wb.step_index(step) else {
+                return Err(Error::not_found("step", step));
+            };
+            vec![
+                Command::MoveStep {
+                    workbench: workbench.to_owned(),
+                    step: step.to_owned(),
+                    index,
+                },
+                restore_rollback(),
+            ]
+        }
+        Command::SetStepSuppressed { step, .. } => vec![Command::SetStepSuppressed {
+            workbench: workbench.to_owned(),
+            step: step.to_owned(),
+            suppressed: wb.suppressed.contains(step),
+        }],
+        Command::RenameStep { step, new_name, .. } => vec![Command::RenameStep {
+            workbench,
+            step: new_name.to_owned(),
+            new_name: step.to_owned(),
+        }],
+        Command::SetVariable { name, .. } | Command::RemoveVariable { name, .. } => {
+            match wb.variables.iter().find(|v| &v.name == name) {
+                Some(variable) => vec![Command::SetVariable {
+                    workbench,
+                    name: name.to_owned(),
+                    expression: variable.expression.to_owned(),
+                }],
+                None => vec![Command::RemoveVariable {
+                    workbench,
+                    name: name.to_owned(),
+                }],
+            }
+        }
+        Command::SetUnits(_) | Command::RestoreUnits { .. } => unreachable!(),
+    };
+    Ok(inverses)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -150,4 +589,284 @@ mod tests {
         assert_eq!(res.is_ok(), true);
         assert_eq!(found, true);
     }
+
+    #[test]
+    fn test_add_and_insert_steps() {
+        use crate::common::Point;
+        use crate::workbench::Operation;
+
+        let mut p = Project::new("Project 1", true);
+        // steps are made with a workbench's builders, then added as commands
+        let mut scratch = p.workbenches()[0].clone();
+        scratch.add_extrusion("Extrude 2", "Sketch 1", 2.0, vec![0], Operation::New);
+        scratch.add_point("Datum", Point::new(0.0, 0.0, 0.0));
+        let datum = scratch.steps.pop().unwrap();
+        let extrusion = scratch.steps.pop().unwrap();
+        let index = |p: &Project, name: &str| p.workbenches()[0].step_index(name);
+        let count = p.workbenches()[0].steps.len();
+
+        p.add_step("Workbench 1", extrusion.clone()).unwrap();
+        assert_eq!(index(&p, "Extrude 2"), Some(count));
+        p.insert_step("Workbench 1", 0, datum).unwrap();
+        assert_eq!(index(&p, "Datum"), Some(0));
+        let view = p.workbenches()[0].create_view(100);
+        assert!(view.solids.contains_key("Extrude 2_0"));
+
+        // a step can't be added twice, nor ahead of the sketch it reads
+        assert!(matches!(
+            p.add_step("Workbench 1", extrusion.clone()),
+            Err(Error::AlreadyExists { .. })
+        ));
+        p.delete_step("Workbench 1", "Extrude 2").unwrap();
+        assert!(matches!(
+            p.insert_step("Workbench 1", 1, extrusion),
+            Err(Error::Dependency(_))
+        ));
+        assert!(p.add_step("Nowhere", scratch.steps[0].clone()).is_err());
+
+        // and adding them comes off with undo like any other edit
+        p.undo().unwrap();
+        assert_eq!(index(&p, "Extrude 2"), Some(count + 1));
+        p.undo().unwrap();
+        assert_eq!(index(&p, "Datum"), None);
+        p.undo().unwrap();
+        assert_eq!(index(&p, "Extrude 2"), None);
+        assert_eq!(p.workbenches()[0].steps.len(), count);
+        p.redo().unwrap();
+        assert_eq!(index(&p, "Extrude 2"), Some(count));
+    }
+
+    #[test]
+    fn test_undo_puts_back_each_edit() {
+        let mut p = Project::new("Project 1", true);
+        let wb = "Workbench 1";
+        let state = |p: &Project| {
+            let w = &p.workbenches()[0];
+            format!(
+                "{:?}",
+                (
+                    &w.steps,
+                    &w.variables,
+                    &w.bindings,
+                    &w.suppressed,
+                    w.rollback
+                )
+            )
+        };
+        let last = |p: &Project| p.history.undo.last().unwrap().inverses.clone();
+        p.set_variable(wb, "depth", "3 mm").unwrap();
+        p.bind_step_parameter(wb, "Extrude 1", "depth", "depth * 2")
+            .unwrap();
+        p.set_step_suppressed(wb, "Extrude 1", true).unwrap();
+        p.roll_back(wb, 5).unwrap();
+        let before = state(&p);
+
+        // a step at the bar goes back with its formula, its suppression, and
+        // the bar where it was
+        p.delete_step(wb, "Extrude 1").unwrap();
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+
+        p.roll_back(wb, 3).unwrap();
+        let before = state(&p);
+        p.move_step(wb, "Front", 4).unwrap();
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+
+        // each edit keeps only what it takes to undo it
+        p.rename_step(wb, "Sketch 1", "Profile").unwrap();
+        assert!(matches!(last(&p)[..], [Command::RenameStep { .. }]));
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+        p.set_step_suppressed(wb, "Sketch 1", true).unwrap();
+        assert!(matches!(last(&p)[..], [Command::SetStepSuppressed { .. }]));
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+        p.set_variable(wb, "depth", "4 mm").unwrap();
+        p.set_variable(wb, "width", "1 mm").unwrap();
+        p.undo().unwrap();
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+        p.set_step_parameters(wb, "Extrude 1", vec!["depth".to_owned()], vec![9.0])
+            .unwrap();
+        assert!(matches!(last(&p)[..], [Command::RestoreStep { .. }]));
+        assert!(p.workbenches()[0].bindings.is_empty());
+        p.undo().unwrap();
+        assert_eq!(state(&p), before);
+
+        // a mismatched edit is refused before it reaches the history
+        let stacked = p.history.undo.len();
+        for values in [vec![], vec![1.0, 2.0]] {
+            let names = vec!["depth".to_owned()];
+            assert!(matches!(
+                p.set_step_parameters(wb, "Extrude 1", names, values),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        assert_eq!(p.history.undo.len(), stacked);
+        assert_eq!(state(&p), before);
+
+        // an undo that can't be carried through leaves everything as it was,
+        // ready to be tried again
+        p.history.undo.push(Transaction {
+            commands: vec![],
+            inverses: vec![
+                Command::DeleteStep {
+                    workbench: wb.to_owned(),
+                    step: "Missing".to_owned(),
+                },
+                Command::SetStepSuppressed {
+                    workbench: wb.to_owned(),
+                    step: "Sketch 1".to_owned(),
+                    suppressed: true,
+                },
+            ],
+        });
+        let stacked = (p.history.undo.len(), p.history.redo.len());
+        assert!(p.undo().is_err());
+        assert_eq!(state(&p), before);
+        assert_eq!((p.history.undo.len(), p.history.redo.len()), stacked);
+    }
+
+    #[test]
+    fn test_wrong_sized_input_is_reported() {
+        use crate::workbench::{Operation, Profile};
+
+        let mut p = Project::new("Project 1", true);
+        let wb = "Workbench 1";
+        for (names, values) in [
+            (vec!["depth"], vec![]),
+            (vec!["depth"], vec![1.0, 2.0]),
+            (vec![], vec![1.0]),
+        ] {
+            let names = names.into_iter().map(str::to_owned).collect();
+            assert!(matches!(
+                p.set_step_parameters(wb, "Extrude 1", names, values),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        assert!(!p.history.can_undo());
+
+        // lofts with too few profiles are kept, and reported when the view is
+        // built rather than bringing it down
+        let mut scratch = p.workbenches()[0].clone();
+        scratch.add_loft("Loft 1", vec![], vec![], Operation::New);
+        let profiles = vec![Profile::new("Sketch 1", 0)];
+        scratch.add_loft("Loft 2", profiles, vec![], Operation::New);
+        for step in scratch.steps.split_off(p.workbenches()[0].steps.len()) {
+            p.add_step(wb, step).unwrap();
+        }
+        let view = p.workbenches()[0].create_view(100);
+        for step in ["Loft 1", "Loft 2"] {
+            assert!(matches!(
+                view.errors(step)[..],
+                [Error::InvalidParameter(_)]
+            ));
+        }
+        assert!(view.solids.contains_key("Extrude 1_0"));
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut p = Project::new("Project 1", true);
+        let depth = |p: &Project| {
+            p.get_workbench("Workbench 1")
+                .unwrap()
+                .steps
+                .iter()
+                .find_map(|step| match step {
+                    crate::workbench::Step::Extrusion { extrusion, .. } => Some(extrusion.depth),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let steps = |p: &Project| p.get_workbench("Workbench 1").unwrap().steps.len();
+        let start = depth(&p);
+
+        p.set_step_parameters(
+            "Workbench 1",
+            "Extrude 1",
+            vec!["depth".to_owned()],
+            vec![5.0],
+        )
+        .unwrap();
+        p.rename_step("Workbench 1", "Extrude 1", "Body").unwrap();
+        assert!(p.history.can_undo());
+        p.undo().unwrap();
+        assert!(p
+            .get_workbench("Workbench 1")
+            .unwrap()
+            .step_index("Extrude 1")
+            .is_some());
+        assert_eq!(depth(&p), 5.0);
+        p.undo().unwrap();
+        assert_eq!(depth(&p), start);
+        assert!(!p.history.can_undo());
+        p.redo().unwrap();
+        assert_eq!(depth(&p), 5.0);
+
+        // the little edits of a drag come off in one undo
+        p.begin_transaction();
+        for i in 1..=10 {
+            let value = 5.0 + i as f64 / 10.0;
+            p.set_step_parameters(
+                "Workbench 1",
+                "Extrude 1",
+                vec!["depth".to_owned()],
+                vec![value],
+            )
+            .unwrap();
+        }
+        p.end_transaction();
+        assert_eq!(depth(&p), 6.0);
+        assert_eq!(p.history.undo.last().unwrap().inverses.len(), 1);
+        p.undo().unwrap();
+        assert_eq!(depth(&p), 5.0);
+        p.redo().unwrap();
+        assert_eq!(depth(&p), 6.0);
+
+        // a failed edit isn't kept, and a new one drops what could be redone
+        assert!(p.delete_step("Workbench 1", "Missing").is_err());
+        assert!(p
+            .add_segment_to_sketch("Nowhere", "Sketch 1", 0.0, 0.0, 1.0, 1.0)
+            .is_err());
+        p.undo().unwrap();
+        assert_eq!(depth(&p), 5.0);
+        let count = steps(&p);
+        p.set_units(Units::parse("in", "deg").unwrap()).unwrap();
+        assert!(!p.history.can_redo());
+        p.undo().unwrap();
+        assert_eq!(p.units, Units::default());
+        assert_eq!(
+            p.get_workbench("Workbench 1").unwrap().units,
+            Units::default()
+        );
+        assert_eq!(steps(&p), count);
+
+        // only the workbenches the change reached are put back
+        let inches = Units::parse("in", "deg").unwrap();
+        let mut second = Workbench::new("Workbench 2");
+        second.units = inches;
+        p.workbenches.push(second);
+        p.set_units(inches).unwrap();
+        match &p.history.undo.last().unwrap().inverses[..] {
+            [Command::RestoreUnits {
+                project,
+                workbenches,
+            }] => {
+                assert_eq!(*project, Units::default());
+                assert_eq!(
+                    workbenches[..],
+                    [("Workbench 1".to_owned(), Units::default())]
+                );
+            }
+            inverses => panic!("{:?}", inverses),
+        }
+        p.undo().unwrap();
+        assert_eq!(p.units, Units::default());
+        assert_eq!(p.workbenches()[0].units, Units::default());
+        assert_eq!(p.workbenches()[1].units, inches);
+        p.redo().unwrap();
+        assert_eq!(p.workbenches()[0].units, inches);
+    }
 }
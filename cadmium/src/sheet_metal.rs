@@ -0,0 +1,356 @@
+use crate::common::{newell_normal, CoordinateFrame, LineFace, Point, Transform, Vector};
+use crate::sketch::{Point as SketchPoint, Segment, Sketch};
+use geo::{Area, BooleanOps};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+// The usual K-factor for air bending mild steel with a die about 8 thicknesses wide
+pub const DEFAULT_K_FACTOR: f64 = 0.44;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Flange {
+    // which edge of the base outline to bend up from
+    pub edge: usize,
+    // the straight run after the bend
+    pub length: f64,
+    // radians, measured from flat
+    pub angle: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SheetMetal {
+    // the base flange's profile sits in the frame's plane and the sheet grows
+    // along its normal
+    pub frame: CoordinateFrame,
+    pub base: LineFace,
+    pub thickness: f64,
+    // inside radius of every bend
+    pub bend_radius: f64,
+    // where the neutral axis sits through the thickness, from 0 (inside) to 0.5
+    pub k_factor: f64,
+    pub flanges: Vec<Flange>,
+}
+
+impl SheetMetal {
+    pub fn new(
+        frame: CoordinateFrame,
+        base: LineFace,
+        thickness: f64,
+        bend_radius: f64,
+        k_factor: f64,
+    ) -> Result<SheetMetal, String> {
+        if thickness <= 0.0 {
+            return Err(format!("Thickness must be positive, got {}", thickness));
+        }
+        if bend_radius <= 0.0 {
+            return Err(format!("Bend radius must be positive, got {}", bend_radius));
+        }
+        if !(0.0..=0.5).contains(&k_factor) {
+            return Err(format!(
+                "K-factor must be between 0 and 0.5, got {}",
+                k_factor
+            ));
+        }
+        Ok(SheetMetal {
+            frame,
+            base,
+            thickness,
+            bend_radius,
+            k_factor,
+            flanges: vec![],
+        })
+    }
+
+    pub fn add_flange(&mut self, flange: Flange) -> Result<(), String> {
+        if flange.edge >= self.base.exterior.segments.len() {
+            return Err(format!("No edge {} on the base flange", flange.edge));
+        }
+        if self.flanges.iter().any(|f| f.edge == flange.edge) {
+            return Err(format!("Edge {} already has a flange", flange.edge));
+        }
+        if flange.length <= 0.0 {
+            return Err(format!(
+                "Flange length must be positive, got {}",
+                flange.length
+            ));
+        }
+        if flange.angle <= 0.0 || flange.angle >= PI {
+            return Err(format!(
+                "Flange angle must be between 0 and 180 degrees, got {}",
+                flange.angle.to_degrees()
+            ));
+        }
+
+        // the flat pattern catches flanges that would run into the base or each other
+        let mut bent = self.clone();
+        bent.flanges.push(flange);
+        bent.unfold()?;
+        *self = bent;
+        Ok(())
+    }
+
+    pub fn bend_allowance(&self, angle: f64) -> f64 {
+        // length of the neutral axis through a bend, which is what it flattens to
+        angle * (self.bend_radius + self.k_factor * self.thickness)
+    }
+
+    pub fn to_truck_solid(&self) -> Result<truck_modeling::Solid, String> {
+        let normal = self.frame.normal;
+        let t = self.thickness;
+        let mut shells = self.base.tsweep(normal, t).truck_solid.into_boundaries();
+
+        for flange in self.flanges.iter() {
+            let segment = &self.base.exterior.segments[flange.edge];
+            let (p, q) = (segment.start, segment.end);
+            let along = q.to_vector().subtract(p.to_vector()).normalize();
+            let outward = along.cross(normal);
+
+            // the base's edge face, rolled up about an axis one bend radius above
+            // the top of the sheet
+            let end = face(
+                vec![p, q, q.add_vec(normal.scale(t)), p.add_vec(normal.scale(t))],
+                outward,
+            );
+            let origin = p.add_vec(normal.scale(t + self.bend_radius));
+            let axis = along.scale(-1.0);
+            let bend = end.rsweep(origin, axis, flange.angle);
+            shells.extend(bend.truck_solid.into_boundaries());
+
+            let roll = Transform::Rotate {
+                origin,
+                axis,
+                angle: flange.angle,
+            };
+            let rolled: Vec<Point> = end
+                .exterior
+                .segments
+                .iter()
+                .map(|s| roll.apply_point(s.start))
+                .collect();
+            let direction = roll.apply_vector(outward);
+            let plate = face(rolled, direction).tsweep(direction, flange.length);
+            shells.extend(plate.truck_solid.into_boundaries());
+        }
+
+        Ok(truck_modeling::Solid::new(shells))
+    }
+
+    pub fn unfold(&self) -> Result<FlatPattern, String> {
+        // Lays the part out flat in the base sketch's coordinates. Each flange
+        // becomes a strip off its edge: the bend allowance, then the flange itself.
+        let flatten = |p: &Point| {
+            let v = p.to_vector().subtract(self.frame.origin.to_vector());
+            geo::Coord {
+                x: v.dot(self.frame.x_axis),
+                y: v.dot(self.frame.y_axis),
+            }
+        };
+        let ring = |r: &crate::common::LineRing| {
+            let mut coords: Vec<geo::Coord> =
+                r.segments.iter().map(|s| flatten(&s.start)).collect();
+            coords.push(coords[0]);
+            geo::LineString(coords)
+        };
+        let base = geo::Polygon::new(
+            ring(&self.base.exterior),
+            self.base.interiors.iter().map(ring).collect(),
+        );
+
+        let mut strips: Vec<geo::Polygon> = vec![];
+        let mut bends: Vec<BendLine> = vec![];
+        for flange in self.flanges.iter() {
+            let segment = &self.base.exterior.segments[flange.edge];
+            let (p, q) = (flatten(&segment.start), flatten(&segment.end));
+            let d = q - p;
+            let outward = geo::Coord { x: d.y, y: -d.x } / d.x.hypot(d.y);
+            let allowance = self.bend_allowance(flange.angle);
+            let reach = outward * (allowance + flange.length);
+            strips.push(geo::Polygon::new(
+                geo::LineString(vec![p, q, q + reach, p + reach, p]),
+                vec![],
+            ));
+            let middle = outward * (allowance / 2.0);
+            bends.push(BendLine {
+                start: (p + middle).x_y(),
+                end: (q + middle).x_y(),
+                angle: flange.angle,
+                radius: self.bend_radius,
+            });
+        }
+
+        for (i, strip) in strips.iter().enumerate() {
+            let overlap = strip.intersection(&base).unsigned_area()
+                + strips[i + 1..]
+                    .iter()
+                    .map(|other| strip.intersection(other).unsigned_area())
+                    .sum::<f64>();
+            if overlap > 1e-9 {
+                return Err(format!(
+                    "The flange on edge {} runs into the rest of the part",
+                    self.flanges[i].edge
+                ));
+            }
+        }
+
+        let mut flat = geo::MultiPolygon(vec![base]);
+        for strip in strips.iter() {
+            flat = flat.union(&geo::MultiPolygon(vec![strip.clone()]));
+        }
+        let outline: Vec<Vec<(f64, f64)>> = flat
+            .iter()
+            .flat_map(|polygon| std::iter::once(polygon.exterior()).chain(polygon.interiors()))
+            .map(|r| {
+                let mut points: Vec<(f64, f64)> = r.coords().map(|c| c.x_y()).collect();
+                points.pop();
+                points
+            })
+            .collect();
+
+        Ok(FlatPattern { outline, bends })
+    }
+}
+
+fn face(mut points: Vec<Point>, facing: Vector) -> LineFace {
+    // a single ring face wound counterclockwise about facing
+    if newell_normal(&points).dot(facing) < 0.0 {
+        points.reverse();
+    }
+    let mut face = LineFace::new();
+    for k in 0..points.len() {
+        face.exterior.add_segment(crate::common::LineSegment::new(
+            points[k],
+            points[(k + 1) % points.len()],
+        ));
+    }
+    face
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BendLine {
+    // the middle of the bend zone, where the press brake should meet the sheet
+    pub start: (f64, f64),
+    pub end: (f64, f64),
+    pub angle: f64,
+    pub radius: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FlatPattern {
+    // the outer boundary first, then any cutouts
+    pub outline: Vec<Vec<(f64, f64)>>,
+    pub bends: Vec<BendLine>,
+}
+
+impl FlatPattern {
+    pub fn to_sketch(&self) -> Sketch {
+        // the outline as closed rings and each bend line as a lone open segment
+        let mut sketch = Sketch::new();
+        for (i, ring) in self.outline.iter().enumerate() {
+            let points: Vec<SketchPoint> = ring
+                .iter()
+                .enumerate()
+                .map(|(j, (x, y))| SketchPoint::new(*x, *y, &format!("outline{}_{}", i, j)))
+                .collect();
+            sketch.add_segments(Segment::link(points, true));
+        }
+        for (i, bend) in self.bends.iter().enumerate() {
+            let start = SketchPoint::new(bend.start.0, bend.start.1, &format!("bend{}_a", i));
+            let end = SketchPoint::new(bend.end.0, bend.end.1, &format!("bend{}_b", i));
+            sketch.add_segments(Segment::link(vec![start, end], false));
+        }
+        sketch
+    }
+
+    pub fn to_dxf(&self) -> String {
+        // ASCII DXF with just an ENTITIES section, which every cutter's software
+        // reads. Outlines go on the OUTLINE layer and bend lines on BEND.
+        let line = |layer: &str, a: (f64, f64), b: (f64, f64)| {
+            format!(
+                "0\nLINE\n8\n{}\n10\n{}\n20\n{}\n30\n0.0\n11\n{}\n21\n{}\n31\n0.0\n",
+                layer, a.0, a.1, b.0, b.1
+            )
+        };
+        let mut dxf = String::from("0\nSECTION\n2\nENTITIES\n");
+        for ring in self.outline.iter() {
+            for k in 0..ring.len() {
+                dxf.push_str(&line("OUTLINE", ring[k], ring[(k + 1) % ring.len()]));
+            }
+        }
+        for bend in self.bends.iter() {
+            dxf.push_str(&line("BEND", bend.start, bend.end));
+        }
+        dxf.push_str("0\nENDSEC\n0\nEOF\n");
+        dxf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::LineSegment;
+
+    fn plate(width: f64, height: f64) -> SheetMetal {
+        let frame = CoordinateFrame::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        let corners = [
+            Point::new(0.0, 0.0, 0.0),
+            Point::new(width, 0.0, 0.0),
+            Point::new(width, height, 0.0),
+            Point::new(0.0, height, 0.0),
+        ];
+        let mut base = LineFace::new();
+        for k in 0..4 {
+            base.exterior
+                .add_segment(LineSegment::new(corners[k], corners[(k + 1) % 4]));
+        }
+        SheetMetal::new(frame, base, 2.0, 3.0, DEFAULT_K_FACTOR).unwrap()
+    }
+
+    #[test]
+    fn unfold_tray() {
+        let mut tray = plate(100.0, 60.0);
+        for edge in 0..4 {
+            let flange = Flange {
+                edge,
+                length: 20.0,
+                angle: PI / 2.0,
+            };
+            tray.add_flange(flange).unwrap();
+        }
+
+        let flat = tray.unfold().unwrap();
+        let allowance = PI / 2.0 * (3.0 + DEFAULT_K_FACTOR * 2.0);
+        let strip = allowance + 20.0;
+        let area = geo::Polygon::new(geo::LineString::from(flat.outline[0].clone()), vec![])
+            .unsigned_area();
+        let expected = 100.0 * 60.0 + 2.0 * strip * (100.0 + 60.0);
+        assert!((area - expected).abs() < 1e-6);
+
+        // a cross shape: the corners stay open
+        assert_eq!(flat.outline.len(), 1);
+        assert_eq!(flat.outline[0].len(), 12);
+        assert_eq!(flat.bends.len(), 4);
+        assert!((flat.bends[0].start.1 + allowance / 2.0).abs() < 1e-9);
+
+        let dxf = flat.to_dxf();
+        assert_eq!(dxf.matches("\nLINE\n").count(), 16);
+        assert_eq!(dxf.matches("\nBEND\n").count(), 4);
+        assert!(dxf.ends_with("0\nEOF\n"));
+
+        let sketch = flat.to_sketch();
+        assert_eq!(sketch.segments.len(), 16);
+
+        let mut twice = tray.clone();
+        let flange = Flange {
+            edge: 0,
+            length: 5.0,
+            angle: PI / 4.0,
+        };
+        assert!(twice.add_flange(flange).is_err());
+        assert!(tray.to_truck_solid().unwrap().boundaries().len() == 9);
+    }
+}
@@ -2,7 +2,9 @@
 #![allow(unused_variables)]
 #![allow(unused_imports)]
 
-use crate::common::{CoordinateFrame, LineFace, LineRing, LineSegment};
+use crate::common::{
+    path_polyline, thicken, CoordinateFrame, LineFace, LineRing, LineSegment, Naming, PathSegment,
+};
 use geo::polygon;
 use geo::Area;
 use geo::Contains;
@@ -184,6 +186,24 @@ impl Arc {
             return angle_1;
         }
     }
+
+    pub fn sweep_angle(&self) -> f64 {
+        // signed angle swept from start to end passing through transit,
+        // positive when the arc runs counterclockwise
+        let circle = self.to_circle();
+        let start_angle = (self.start.y - circle.center.y).atan2(self.start.x - circle.center.x);
+        let end_angle = (self.end.y - circle.center.y).atan2(self.end.x - circle.center.x);
+        let transit_angle =
+            (self.transit.y - circle.center.y).atan2(self.transit.x - circle.center.x);
+
+        let ccw_to_end = angle_difference(start_angle, end_angle);
+        let ccw_to_transit = angle_difference(start_angle, transit_angle);
+        if ccw_to_transit < ccw_to_end {
+            ccw_to_end
+        } else {
+            ccw_to_end - TAU
+        }
+    }
 }
 
 pub fn min_angle_diff(a0: f64, a1: f64) -> f64 {
@@ -361,17 +381,89 @@ impl Face {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Sketch {
     pub segments: Vec<Segment>,
+    // construction points that aren't part of any segment, like hole centers
+    #[serde(default)]
+    pub points: Vec<Point>,
 }
 
 impl Sketch {
     pub fn new() -> Sketch {
-        Sketch { segments: vec![] }
+        Sketch {
+            segments: vec![],
+            points: vec![],
+        }
+    }
+
+    pub fn scale(&mut self, factor: f64) {
+        // about the sketch origin, as when its numbers are read in another unit
+        let scale = |p: &mut Point| {
+            p.x *= factor;
+            p.y *= factor;
+        };
+        for segment in self.segments.iter_mut() {
+            match segment {
+                Segment::Line(line) => {
+                    scale(&mut line.start);
+                    scale(&mut line.end);
+                }
+                Segment::Arc(arc) => {
+                    scale(&mut arc.start);
+                    scale(&mut arc.end);
+                    scale(&mut arc.transit);
+                }
+            }
+        }
+        self.points.iter_mut().for_each(scale);
+    }
+
+    pub fn set_point_coordinate(&mut self, id: &str, axis: &str, value: f64) -> Result<(), String> {
+        // Segments hold copies of their ends, so the point is moved everywhere
+        // it appears
+        let mut found = false;
+        let mut update = |p: &mut Point| {
+            if p.id == id {
+                found = true;
+                match axis {
+                    "x" => p.x = value,
+                    _ => p.y = value,
+                }
+            }
+        };
+        if axis != "x" && axis != "y" {
+            return Err(format!("Sketch points have no {} coordinate", axis));
+        }
+        for segment in self.segments.iter_mut() {
+            match segment {
+                Segment::Line(line) => {
+                    update(&mut line.start);
+                    update(&mut line.end);
+                }
+                Segment::Arc(arc) => {
+                    update(&mut arc.start);
+                    update(&mut arc.end);
+                    update(&mut arc.transit);
+                }
+            }
+        }
+        self.points.iter_mut().for_each(&mut update);
+        if !found {
+            return Err(format!("No sketch point {}", id));
+        }
+        Ok(())
     }
 
     pub fn add_segments(&mut self, segments: Vec<Segment>) {
         self.segments.extend(segments);
     }
 
+    pub fn add_point(&mut self, point: Point) {
+        self.points.push(point);
+    }
+
+    pub fn find_point(&self, id: &str) -> Option<Point> {
+        find_point(&self.points, &self.segments, id)
+    }
+
     pub fn find_faces(&self, debug: bool) -> Vec<Face> {
         let rings = self.find_rings(debug);
 
@@ -383,7 +475,7 @@ impl Sketch {
 
         // they are already sorted from smallest to largest area
         let mut what_contains_what: Vec<(usize, usize)> = vec![];
-        for smaller_polygon_index in 0..polygons.len() - 1 {
+        for smaller_polygon_index in 0..polygons.len().saturating_sub(1) {
             let smaller_polygon = &polygons[smaller_polygon_index];
             println!("Smaller poly area: {:?}", smaller_polygon.signed_area());
 
@@ -499,6 +591,7 @@ impl Sketch {
             faces: vec![],
             segments_2d: vec![],
             faces_2d: vec![],
+            points_2d: self.points.clone(),
             coordinate_frame: frame.clone(),
         };
 
@@ -575,15 +668,136 @@ impl Sketch {
     }
 }
 
-#[derive(Debug, Clone)]
+fn find_point(points: &[Point], segments: &[Segment], id: &str) -> Option<Point> {
+    let on_segments = segments.iter().flat_map(|s| match s {
+        Segment::Line(l) => vec![&l.start, &l.end],
+        Segment::Arc(a) => vec![&a.start, &a.end, &a.transit],
+    });
+    points
+        .iter()
+        .chain(on_segments)
+        .find(|p| p.id == id)
+        .cloned()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SketchView {
     pub segments: Vec<LineSegment>,
     pub faces: Vec<LineFace>,
     pub segments_2d: Vec<Segment>,
     pub faces_2d: Vec<Face>,
+    // the points standing on their own, not at the ends of a segment
+    pub points_2d: Vec<Point>,
     pub coordinate_frame: CoordinateFrame,
 }
 
+impl SketchView {
+    pub fn find_point(&self, id: &str) -> Option<Point> {
+        find_point(&self.points_2d, &self.segments_2d, id)
+    }
+
+    pub fn thicken(
+        &self,
+        segment_indices: &[usize],
+        offsets: (f64, f64),
+    ) -> Result<LineFace, String> {
+        // A face made by widening the chained segments, which may be open or closed
+        let path = self.path(segment_indices)?;
+        thicken(&path_polyline(&path), self.coordinate_frame.normal, offsets)
+    }
+
+    pub fn segment_index(&self, face: usize, ring: usize, vertex: usize) -> Option<usize> {
+        // The sketch segment an edge of a face was drawn with. Faces are rebuilt
+        // from polygons, so the edge is matched by its ends.
+        let face = self.faces_2d.get(face)?;
+        let ring = match ring {
+            0 => &face.exterior,
+            _ => face.interiors.get(ring - 1)?,
+        };
+        let edge = ring.get(vertex)?;
+        let (a, b) = (edge.get_start(), edge.get_end());
+        self.segments_2d.iter().position(|s| {
+            let (c, d) = (s.get_start(), s.get_end());
+            (a == c && b == d) || (a == d && b == c)
+        })
+    }
+
+    pub fn naming(&self, step: &str, face: usize) -> Naming {
+        let segments = self.faces[face]
+            .rings()
+            .iter()
+            .enumerate()
+            .map(|(ring, r)| {
+                (0..r.segments.len())
+                    .map(|vertex| self.segment_index(face, ring, vertex))
+                    .collect()
+            })
+            .collect();
+        Naming::new(step, segments)
+    }
+
+    pub fn path(&self, segment_indices: &[usize]) -> Result<Vec<PathSegment>, String> {
+        // Chains the selected segments end to end, flipping any that are drawn backwards
+        let mut chain: Vec<Segment> = vec![];
+        for (i, segment_index) in segment_indices.iter().enumerate() {
+            let segment = self
+                .segments_2d
+                .get(*segment_index)
+                .ok_or(format!("No segment with index {}", segment_index))?;
+
+            match chain.last() {
+                None => {
+                    let next = segment_indices
+                        .get(1)
+                        .and_then(|j| self.segments_2d.get(*j));
+                    match next {
+                        Some(n)
+                            if segment.get_start() == n.get_start()
+                                || segment.get_start() == n.get_end() =>
+                        {
+                            chain.push(segment.reverse())
+                        }
+                        _ => chain.push(segment.clone()),
+                    }
+                }
+                Some(prior) => {
+                    if segment.continues(prior) {
+                        chain.push(segment.clone());
+                    } else if segment.reverse().continues(prior) {
+                        chain.push(segment.reverse());
+                    } else {
+                        return Err(format!(
+                            "Segment {} does not connect to the path before it",
+                            segment_indices[i]
+                        ));
+                    }
+                }
+            }
+        }
+
+        let frame = &self.coordinate_frame;
+        let axis = frame.x_axis.cross(frame.y_axis).normalize();
+        let path = chain
+            .iter()
+            .map(|segment| match segment {
+                Segment::Line(l) => PathSegment::Line {
+                    start: frame.to_3d(l.start.clone()),
+                    end: frame.to_3d(l.end.clone()),
+                },
+                Segment::Arc(a) => PathSegment::Arc {
+                    start: frame.to_3d(a.start.clone()),
+                    end: frame.to_3d(a.end.clone()),
+                    center: frame.to_3d(a.to_circle().center),
+                    axis,
+                    angle: a.sweep_angle(),
+                },
+            })
+            .collect();
+
+        Ok(path)
+    }
+}
+
 pub fn find_next_segment_index(
     segments: &Vec<Segment>,
     starting_segment: &Segment,
@@ -704,6 +918,27 @@ mod tests {
         assert_eq!(a5.angle(), 0.0);
     }
 
+    #[test]
+    fn arc_sweep_angles() {
+        let a = Point::new(1.0, 0.0, "A");
+        let b = Point::new(0.0, 1.0, "B");
+        let c = Point::new(-1.0, 0.0, "C");
+        let d = Point::new(0.0, -1.0, "D");
+
+        let quarter = Arc::new(
+            a.clone(),
+            b.clone(),
+            Point::new(0.5_f64.sqrt(), 0.5_f64.sqrt(), "T"),
+        );
+        assert!((quarter.sweep_angle() - PI / 2.0).abs() < 1e-9);
+
+        let half_ccw = Arc::new(a.clone(), c.clone(), b.clone());
+        assert!((half_ccw.sweep_angle() - PI).abs() < 1e-9);
+
+        let half_cw = Arc::new(a.clone(), c.clone(), d.clone());
+        assert!((half_cw.sweep_angle() + PI).abs() < 1e-9);
+    }
+
     #[test]
     fn simplest_triangle_rings() {
         /*
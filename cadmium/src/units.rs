@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::expression::{Expression, Operator, Value};
+
+// Lengths in the model are plain numbers in the workbench's length unit, and
+// angles are always radians. Units only come into it where a value is typed in
+// and where a solid is written out to a file.
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthUnit {
+    #[default]
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    Foot,
+}
+
+impl LengthUnit {
+    pub fn millimeters(&self) -> f64 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Centimeter => 10.0,
+            LengthUnit::Meter => 1000.0,
+            LengthUnit::Inch => 25.4,
+            LengthUnit::Foot => 304.8,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "mm",
+            LengthUnit::Centimeter => "cm",
+            LengthUnit::Meter => "m",
+            LengthUnit::Inch => "in",
+            LengthUnit::Foot => "ft",
+        }
+    }
+
+    pub fn parse(symbol: &str) -> Option<LengthUnit> {
+        match symbol {
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => {
+                Some(LengthUnit::Millimeter)
+            }
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => {
+                Some(LengthUnit::Centimeter)
+            }
+            "m" | "meter" | "meters" | "metre" | "metres" => Some(LengthUnit::Meter),
+            "in" | "inch" | "inches" => Some(LengthUnit::Inch),
+            "ft" | "foot" | "feet" => Some(LengthUnit::Foot),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AngleUnit {
+    #[default]
+    Degree,
+    Radian,
+}
+
+impl AngleUnit {
+    pub fn radians(&self) -> f64 {
+        match self {
+            AngleUnit::Degree => PI / 180.0,
+            AngleUnit::Radian => 1.0,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AngleUnit::Degree => "deg",
+            AngleUnit::Radian => "rad",
+        }
+    }
+
+    pub fn parse(symbol: &str) -> Option<AngleUnit> {
+        match symbol {
+            "deg" | "degree" | "degrees" => Some(AngleUnit::Degree),
+            "rad" | "radian" | "radians" => Some(AngleUnit::Radian),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Length(LengthUnit),
+    Angle(AngleUnit),
+}
+
+impl Unit {
+    pub fn parse(symbol: &str) -> Option<Unit> {
+        LengthUnit::parse(symbol)
+            .map(Unit::Length)
+            .or(AngleUnit::parse(symbol).map(Unit::Angle))
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Length(unit) => unit.symbol(),
+            Unit::Angle(unit) => unit.symbol(),
+        }
+    }
+}
+
+// What a step parameter measures, which decides how a bare number typed into it
+// is read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quantity {
+    Length,
+    Angle,
+    Count,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Units {
+    pub length: LengthUnit,
+    // the unit bare numbers are read in for angle parameters
+    pub angle: AngleUnit,
+}
+
+impl Units {
+    pub fn new(length: LengthUnit, angle: AngleUnit) -> Units {
+        Units { length, angle }
+    }
+
+    pub fn parse(length: &str, angle: &str) -> Result<Units, String> {
+        Ok(Units {
+            length: LengthUnit::parse(length).ok_or(format!("{} is not a length unit", length))?,
+            angle: AngleUnit::parse(angle).ok_or(format!("{} is not an angle unit", angle))?,
+        })
+    }
+
+    pub fn convert(&self, number: f64, unit: Unit) -> f64 {
+        // from the given unit to the model's: lengths in the length unit,
+        // angles in radians
+        match unit {
+            Unit::Length(from) => number * from.millimeters() / self.length.millimeters(),
+            Unit::Angle(from) => number * from.radians(),
+        }
+    }
+
+    pub fn model(&self, value: Value, quantity: Quantity) -> f64 {
+        match quantity {
+            Quantity::Angle if !value.angle => value.number * self.angle.radians(),
+            _ => value.number,
+        }
+    }
+}
+
+pub fn parse_input(text: &str) -> Result<Expression, String> {
+    // A unit at the end of a plain number, as in "1 + 1/2 in", applies to all
+    // of it. Once a formula reads a variable, a unit only covers the product it
+    // ends.
+    let trimmed = text.trim_end();
+    let (number, symbol) = trimmed.split_at(
+        trimmed
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .len(),
+    );
+    if let Some(unit) = Unit::parse(symbol) {
+        if let Ok(expression) = Expression::parse(number) {
+            if expression.variables().is_empty() && !expression.has_units() {
+                return Ok(Expression::Binary(
+                    Operator::Multiply,
+                    Box::new(expression),
+                    Box::new(Expression::Measure(1.0, unit)),
+                ));
+            }
+        }
+    }
+    Expression::parse(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn read(text: &str, units: Units, quantity: Quantity) -> f64 {
+        let value = parse_input(text)
+            .unwrap()
+            .evaluate(&HashMap::new(), &units)
+            .unwrap();
+        units.model(value, quantity)
+    }
+
+    #[test]
+    fn unit_annotated_input() {
+        let metric = Units::default();
+        let imperial = Units::parse("in", "rad").unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        assert!(close(read("1/4 in", metric, Quantity::Length), 6.35));
+        assert!(close(read("1/4in", metric, Quantity::Length), 6.35));
+        assert!(close(read("2 ft", imperial, Quantity::Length), 24.0));
+        assert!(close(read("1in + 1mm", metric, Quantity::Length), 26.4));
+        assert!(close(read("1 + 1/2 in", imperial, Quantity::Length), 1.5));
+        assert!(close(
+            read("1/4 in + 2 * 3.175mm", imperial, Quantity::Length),
+            0.5
+        ));
+        assert!(close(read("12.7", imperial, Quantity::Length), 12.7));
+
+        assert!(close(read("30 deg", metric, Quantity::Angle), PI / 6.0));
+        assert!(close(read("30", metric, Quantity::Angle), PI / 6.0));
+        assert!(close(read("0.5", imperial, Quantity::Angle), 0.5));
+        assert!(close(read("pi/2 rad", metric, Quantity::Angle), PI / 2.0));
+        assert!(close(read("asin(1)", metric, Quantity::Angle), PI / 2.0));
+        assert!(close(read("6 in", imperial, Quantity::Count), 6.0));
+
+        // a unit after a formula with variables stays on its own term
+        let mut variables = HashMap::new();
+        variables.insert("wall".to_owned(), Value::plain(2.0));
+        let value = parse_input("3 * wall + 2mm")
+            .unwrap()
+            .evaluate(&variables, &Units::parse("cm", "deg").unwrap())
+            .unwrap();
+        assert!(close(value.number, 6.2));
+
+        assert!(Units::parse("furlong", "deg").is_err());
+        assert!(parse_input("2 parsecs").is_err());
+    }
+}
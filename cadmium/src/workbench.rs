@@ -2,17 +2,119 @@
 #![allow(unused_variables)]
 #![allow(unused_imports)]
 
-use crate::common::{Plane, Point, Solid, Vector};
+use crate::common::{
+    guided_sections, loft, match_sections, path_polyline, rib, Corner, Cylinder, FaceDraft,
+    FaceName, Helix, HoleCut, HoleShape, LineFace, Naming, Plane, Point, Prism, PrismFace, Solid,
+    Thread, Transform, Vector,
+};
+use crate::error::Error;
+use crate::expression::{is_identifier, is_reserved, Expression, Value};
+use crate::hole::{find_size, ThreadStandard};
+use crate::sheet_metal::{Flange, FlatPattern, SheetMetal, DEFAULT_K_FACTOR};
 use crate::sketch::Point as Point2D;
 use crate::sketch::{Line, Segment, Sketch, SketchView};
+use crate::units::{parse_input, Quantity, Units};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Workbench {
     pub name: String,
     pub steps: Vec<Step>,
+    #[serde(default)]
+    pub units: Units,
+    // named values that step parameters can be written in terms of
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+    // steps left out of the view, along with everything that uses them
+    #[serde(default)]
+    pub suppressed: Vec<String>,
+    // How many steps are in play while the rollback bar is pulled back, None
+    // with it at the end. New steps go in at the bar.
+    #[serde(default)]
+    pub rollback: Option<usize>,
+    // shared between copies, which is safe as entries are found by content
+    #[serde(skip)]
+    cache: Arc<Mutex<ViewCache>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum ViewKey {
+    Point(String),
+    Plane(String),
+    Helix(String),
+    Sketch(String),
+    Solid(String),
+}
+
+impl ViewKey {
+    fn name(&self) -> &str {
+        match self {
+            ViewKey::Point(name)
+            | ViewKey::Plane(name)
+            | ViewKey::Helix(name)
+            | ViewKey::Sketch(name)
+            | ViewKey::Solid(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ViewEntry {
+    Point(Point),
+    Plane(Plane),
+    Helix(Helix),
+    Sketch(SketchView),
+    Solid(Box<Solid>),
+}
+
+// how many step runs are kept between views, however many views they came from
+const CACHED_RUNS: usize = 1024;
+
+#[derive(Debug, Default)]
+struct ViewCache {
+    // what each step did to the view when it was last run, by a hash of the
+    // step and of the versions of everything it read
+    steps: HashMap<u64, StepRun>,
+    // the view each run was last used in, so the stalest go first when the
+    // cache is full
+    used: HashMap<u64, u64>,
+    views: u64,
+}
+
+impl ViewCache {
+    fn trim(&mut self) {
+        // runs from the view just built are always kept
+        if self.steps.len() <= CACHED_RUNS {
+            return;
+        }
+        let mut stale: Vec<(u64, u64)> = self
+            .used
+            .iter()
+            .filter(|(_, view)| **view < self.views)
+            .map(|(hash, view)| (*view, *hash))
+            .collect();
+        stale.sort_unstable();
+        for (_, hash) in stale.into_iter().take(self.steps.len() - CACHED_RUNS) {
+            self.steps.remove(&hash);
+            self.used.remove(&hash);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StepRun {
+    changes: Vec<(ViewKey, Option<ViewEntry>)>,
+    errors: Vec<Error>,
+    // whether the step made nothing, so the steps that read from it can't run
+    failed: bool,
 }
 
 impl Workbench {
@@ -20,6 +122,12 @@ impl Workbench {
         let mut w = Workbench {
             name: name.to_owned(),
             steps: vec![],
+            units: Units::default(),
+            variables: vec![],
+            bindings: vec![],
+            suppressed: vec![],
+            rollback: None,
+            cache: Arc::new(Mutex::new(ViewCache::default())),
         };
 
         let origin = Point::new(0.0, 0.0, 0.0);
@@ -78,20 +186,74 @@ impl Workbench {
         self.add_extrusion("Extrude 1", "Sketch 1", height, vec![1, 2], Operation::New);
     }
 
+    fn push_step(&mut self, step: Step) {
+        let index = self.active_steps();
+        self.steps.insert(index, step);
+        self.step_inserted(index);
+    }
+
+    pub fn active_steps(&self) -> usize {
+        self.rollback.unwrap_or(self.steps.len())
+    }
+
+    pub fn roll_back(&mut self, position: usize) -> Result<(), Error> {
+        // the steps from position on are left out until the bar is released
+        if position > self.steps.len() {
+            return Err(Error::InvalidParameter(format!(
+                "There are only {} steps to roll back through",
+                self.steps.len()
+            )));
+        }
+        self.rollback = Some(position);
+        Ok(())
+    }
+
+    pub fn release_rollback(&mut self) {
+        self.rollback = None;
+    }
+
+    fn step_inserted(&mut self, index: usize) {
+        // a step that goes in at the bar or before it is in play
+        if let Some(position) = self.rollback {
+            if index <= position {
+                self.rollback = Some(position + 1);
+            }
+        }
+    }
+
+    fn step_removed(&mut self, index: usize) {
+        if let Some(position) = self.rollback {
+            if index < position {
+                self.rollback = Some(position - 1);
+            }
+        }
+    }
+
+    pub fn view(&self) -> WorkbenchView {
+        // the model as of the rollback bar
+        self.create_view(self.active_steps())
+    }
+
     pub fn add_point(&mut self, name: &str, p: Point) {
-        self.steps.push(Step::Point {
+        self.push_step(Step::Point {
             name: name.to_owned(),
             point: p,
         });
     }
     pub fn add_plane(&mut self, name: &str, p: Plane) {
-        self.steps.push(Step::Plane {
+        self.push_step(Step::Plane {
             name: name.to_owned(),
             plane: p,
         });
     }
+    pub fn add_helix(&mut self, name: &str, helix: Helix) {
+        self.push_step(Step::Helix {
+            name: name.to_owned(),
+            helix,
+        });
+    }
     pub fn add_sketch(&mut self, name: &str, s: Sketch, plane: &str) {
-        self.steps.push(Step::Sketch {
+        self.push_step(Step::Sketch {
             name: name.to_owned(),
             sketch: s,
             plane: plane.to_owned(),
@@ -162,359 +324,5842 @@ impl Workbench {
         let plane = self.find_sketch_plane(sketch).unwrap();
         let normal = plane.normal;
 
-        self.steps.push(Step::Extrusion {
+        self.push_step(Step::Extrusion {
             name: name.to_owned(),
             extrusion: Extrusion {
                 depth: depth,
                 direction: normal,
                 operation: operation,
+                thin: None,
             },
             sketch: sketch.to_owned(),
             faces: faces,
+            segments: vec![],
         });
     }
 
-    pub fn create_view(&self, max_steps: usize) -> WorkbenchView {
-        let mut wbv = WorkbenchView::new();
-        for step in self.steps.iter().take(max_steps) {
-            match step {
-                Step::Point { point: p, name } => {
-                    wbv.points.insert(name.to_owned(), p.clone());
-                }
-                Step::Plane { plane: p, name } => {
-                    wbv.planes.insert(name.to_owned(), p.clone());
-                }
-                Step::Sketch {
-                    sketch,
-                    name,
-                    plane,
-                } => {
-                    let actual_plane = wbv.find_plane(plane).unwrap();
-                    let transform = actual_plane.to_frame();
-                    let sketchview = sketch.create_view(&transform);
-                    wbv.sketches.insert(name.to_owned(), sketchview);
-                }
-                Step::Extrusion {
-                    name,
-                    extrusion,
-                    sketch,
-                    faces,
-                } => {
-                    let sketchview = wbv.sketches.get(sketch).unwrap();
-
-                    let mut count = 0;
-                    for face_index in faces.iter() {
-                        let face = &sketchview.faces[*face_index];
-                        let res = face.tsweep(extrusion.direction, extrusion.depth);
-                        let solid_name = format!("{}_{}", name, count);
-                        wbv.solids.insert(solid_name.to_owned(), res);
-                        count += 1;
-                    }
-                }
-            }
-        }
+    pub fn add_thin_extrusion(
+        &mut self,
+        name: &str,
+        sketch: &str,
+        depth: f64,
+        segments: Vec<usize>,
+        thin: Thin,
+        operation: Operation,
+    ) {
+        let plane = self.find_sketch_plane(sketch).unwrap();
+        let mut extrusion = Extrusion::new(depth, plane.normal, operation);
+        extrusion.thin = Some(thin);
 
-        wbv
+        self.push_step(Step::Extrusion {
+            name: name.to_owned(),
+            extrusion,
+            sketch: sketch.to_owned(),
+            faces: vec![],
+            segments,
+        });
     }
 
-    pub fn set_selected_for_operation(
+    pub fn add_revolve(&mut self, name: &str, sketch: &str, faces: Vec<usize>, revolve: Revolve) {
+        self.push_step(Step::Revolve {
+            name: name.to_owned(),
+            revolve,
+            sketch: sketch.to_owned(),
+            faces,
+            segments: vec![],
+        });
+    }
+
+    pub fn add_thin_revolve(
         &mut self,
-        step_name: &str,
-        parameter_name: &str,
-        sketch_name: &str,
-        selection: Vec<u64>,
-    ) -> Result<(), String> {
-        for step in self.steps.iter_mut() {
-            match step {
-                Step::Extrusion {
-                    name,
-                    extrusion,
-                    sketch,
-                    faces,
-                } => {
-                    if name == step_name {
-                        match parameter_name {
-                            "faces" => {
-                                faces.clear();
-                                faces.extend(selection.iter().map(|el| *el as usize));
-                                return Ok(());
-                            }
-                            _ => {
-                                return Err(format!(
-                                    "No parameter named {} for step {}",
-                                    parameter_name, step_name
-                                ));
-                            }
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
+        name: &str,
+        sketch: &str,
+        segments: Vec<usize>,
+        thin: Thin,
+        mut revolve: Revolve,
+    ) {
+        revolve.thin = Some(thin);
+        self.push_step(Step::Revolve {
+            name: name.to_owned(),
+            revolve,
+            sketch: sketch.to_owned(),
+            faces: vec![],
+            segments,
+        });
+    }
 
-        return Err(format!("No step named {}", step_name));
+    pub fn add_sweep(
+        &mut self,
+        name: &str,
+        sketch: &str,
+        faces: Vec<usize>,
+        path: &str,
+        segments: Vec<usize>,
+        sweep: Sweep,
+    ) {
+        self.push_step(Step::Sweep {
+            name: name.to_owned(),
+            sweep,
+            sketch: sketch.to_owned(),
+            faces,
+            path: path.to_owned(),
+            segments,
+        });
     }
 
-    pub fn add_segment_to_sketch(
+    pub fn add_loft(
         &mut self,
-        sketch_name: &str,
-        x1: f64,
-        y1: f64,
-        x2: f64,
-        y2: f64,
-    ) -> Result<(), String> {
-        for step in self.steps.iter_mut() {
-            match step {
-                Step::Sketch {
-                    name,
-                    sketch,
-                    plane,
-                } => {
-                    if name == sketch_name {
-                        let start = Point2D::new(x1, y1, "A");
-                        let end = Point2D::new(x2, y2, "A");
-                        let segments = Segment::link(vec![start, end], false);
+        name: &str,
+        profiles: Vec<Profile>,
+        guides: Vec<Guide>,
+        operation: Operation,
+    ) {
+        self.push_step(Step::Loft {
+            name: name.to_owned(),
+            loft: Loft {
+                operation,
+                target: None,
+            },
+            profiles,
+            guides,
+        });
+    }
 
-                        sketch.add_segments(segments);
-                        // let line = Line::new(p1, p2);
-                        // sketch.add_line(line);
-                        return Ok(());
-                    }
-                }
-                _ => {}
-            }
-        }
-        return Ok(());
+    pub fn add_fillet(&mut self, name: &str, edges: Vec<EdgeId>, radius: f64) {
+        self.push_step(Step::Fillet {
+            name: name.to_owned(),
+            fillet: Fillet { radius },
+            edges,
+        });
     }
 
-    pub fn set_step_parameters(
-        &mut self,
-        step_name: &str,
-        parameter_names: Vec<String>,
-        value: Vec<f64>,
-    ) -> Result<(), String> {
-        for step in self.steps.iter_mut() {
-            match step {
-                Step::Extrusion {
-                    name,
-                    extrusion,
-                    sketch,
-                    faces,
-                } => {
-                    println!("Found an extrusion");
-                    if name == step_name {
-                        println!("Found the right extrusion");
-                        for name in parameter_names.iter() {
-                            match name.as_str() {
-                                "depth" => {
-                                    extrusion.depth = value[0];
-                                }
-                                _ => {
-                                    return Err(format!(
-                                        "No parameter named {} for step {}",
-                                        name, step_name
-                                    ));
-                                }
-                            }
-                        }
-                        return Ok(());
-                    }
-                }
-                _ => {}
-            }
-        }
-        Err(format!("No step named {}", step_name))
+    pub fn add_chamfer(&mut self, name: &str, edges: Vec<EdgeId>, mode: ChamferMode) {
+        self.push_step(Step::Chamfer {
+            name: name.to_owned(),
+            chamfer: Chamfer { mode },
+            edges,
+        });
     }
-}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum Step {
-    Point {
-        name: String,
-        point: Point,
-    },
-    Plane {
-        name: String,
-        plane: Plane,
-    },
-    Sketch {
-        name: String,
-        sketch: Sketch,
-        plane: String,
-    },
-    Extrusion {
-        name: String,
-        extrusion: Extrusion,
-        sketch: String,
-        faces: Vec<usize>,
-    },
-}
+    pub fn add_shell(&mut self, name: &str, solid: &str, faces: Vec<PrismFace>, shell: Shell) {
+        self.push_step(Step::Shell {
+            name: name.to_owned(),
+            shell,
+            solid: solid.to_owned(),
+            faces,
+        });
+    }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub enum Operation {
-    New,
-    Add,
-    Remove,
-}
+    pub fn add_linear_pattern(
+        &mut self,
+        name: &str,
+        features: Vec<String>,
+        pattern: LinearPattern,
+    ) {
+        self.push_step(Step::LinearPattern {
+            name: name.to_owned(),
+            pattern,
+            features,
+        });
+    }
 
-impl fmt::Display for Operation {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Operation::New => write!(f, "New"),
-            Operation::Add => write!(f, "Add"),
-            Operation::Remove => write!(f, "Remove"),
-        }
+    pub fn add_circular_pattern(
+        &mut self,
+        name: &str,
+        features: Vec<String>,
+        pattern: CircularPattern,
+    ) {
+        self.push_step(Step::CircularPattern {
+            name: name.to_owned(),
+            pattern,
+            features,
+        });
     }
-}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Extrusion {
-    pub depth: f64,
-    pub direction: Vector,
-    pub operation: Operation,
-}
-impl Extrusion {
-    pub fn new(depth: f64, direction: Vector, operation: Operation) -> Extrusion {
-        Extrusion {
-            depth: depth,
-            direction: direction,
-            operation: operation,
-        }
+    pub fn add_mirror(&mut self, name: &str, features: Vec<String>, mirror: Mirror) {
+        self.push_step(Step::Mirror {
+            name: name.to_owned(),
+            mirror,
+            features,
+        });
     }
-}
 
-#[derive(Debug)]
-pub struct WorkbenchView {
-    pub points: HashMap<String, Point>,
+    pub fn add_hole(
+        &mut self,
+        name: &str,
+        solid: &str,
+        face: PrismFace,
+        sketch: &str,
+        points: Vec<String>,
+        hole: Hole,
+    ) {
+        self.push_step(Step::Hole {
+            name: name.to_owned(),
+            hole,
+            solid: solid.to_owned(),
+            face,
+            sketch: sketch.to_owned(),
+            points,
+        });
+    }
+
+    pub fn add_draft(&mut self, name: &str, solid: &str, faces: Vec<PrismFace>, draft: Draft) {
+        self.push_step(Step::Draft {
+            name: name.to_owned(),
+            draft,
+            solid: solid.to_owned(),
+            faces,
+        });
+    }
+
+    pub fn add_rib(
+        &mut self,
+        name: &str,
+        sketch: &str,
+        segments: Vec<usize>,
+        solid: &str,
+        rib: Rib,
+    ) {
+        self.push_step(Step::Rib {
+            name: name.to_owned(),
+            rib,
+            sketch: sketch.to_owned(),
+            segments,
+            solid: solid.to_owned(),
+        });
+    }
+
+    pub fn add_base_flange(&mut self, name: &str, sketch: &str, face: usize, flange: BaseFlange) {
+        self.push_step(Step::BaseFlange {
+            name: name.to_owned(),
+            flange,
+            sketch: sketch.to_owned(),
+            face,
+        });
+    }
+
+    pub fn add_edge_flange(
+        &mut self,
+        name: &str,
+        solid: &str,
+        edges: Vec<usize>,
+        flange: EdgeFlange,
+    ) {
+        self.push_step(Step::EdgeFlange {
+            name: name.to_owned(),
+            flange,
+            solid: solid.to_owned(),
+            edges,
+        });
+    }
+
+    pub fn add_cosmetic_thread(
+        &mut self,
+        name: &str,
+        solid: &str,
+        face: usize,
+        thread: CosmeticThread,
+    ) {
+        self.push_step(Step::CosmeticThread {
+            name: name.to_owned(),
+            thread,
+            solid: solid.to_owned(),
+            face,
+        });
+    }
+
+    pub fn add_thread(&mut self, name: &str, solid: &str, face: usize, thread: ModelledThread) {
+        self.push_step(Step::Thread {
+            name: name.to_owned(),
+            thread,
+            solid: solid.to_owned(),
+            face,
+        });
+    }
+
+    pub fn add_transform(&mut self, name: &str, bodies: Vec<String>, transform: BodyTransform) {
+        self.push_step(Step::Transform {
+            name: name.to_owned(),
+            transform,
+            bodies,
+        });
+    }
+
+    pub fn add_split(&mut self, name: &str, solid: &str, plane: PlaneRef) {
+        self.push_step(Step::Split {
+            name: name.to_owned(),
+            plane,
+            solid: solid.to_owned(),
+        });
+    }
+
+    pub fn add_delete_body(&mut self, name: &str, bodies: Vec<String>, keep: bool) {
+        self.push_step(Step::DeleteBody {
+            name: name.to_owned(),
+            bodies,
+            keep,
+        });
+    }
+
+    pub fn create_view(&self, max_steps: usize) -> WorkbenchView {
+        // A step is only run again when it, or something it reads, has changed
+        // since the last view. Otherwise what it did then is played back.
+        let mut wbv = WorkbenchView::new();
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.views += 1;
+        let view = cache.views;
+        let mut versions: HashMap<ViewKey, u64> = HashMap::new();
+        let mut skipped: Vec<&str> = vec![];
+        let mut failed: Vec<&str> = vec![];
+        for step in self.steps.iter().take(max_steps) {
+            let references = step.references();
+            if self.suppressed.iter().any(|n| n == step.name())
+                || references
+                    .iter()
+                    .any(|r| skipped.iter().any(|s| made_by(r, s)))
+            {
+                skipped.push(step.name());
+                continue;
+            }
+            if let Some(reference) = references
+                .iter()
+                .find(|r| failed.iter().any(|s| made_by(r, s)))
+            {
+                wbv.diagnostics.push(Diagnostic {
+                    step: step.name().to_owned(),
+                    error: Error::Dependency(format!("{} could not be built", reference)),
+                });
+                failed.push(step.name());
+                continue;
+            }
+
+            let reads = wbv.keys_read_by(step);
+            let mut hasher = DefaultHasher::new();
+            format!("{:?}", step).hash(&mut hasher);
+            for key in reads.iter() {
+                key.hash(&mut hasher);
+                versions.get(key).hash(&mut hasher);
+            }
+            let hash = hasher.finish();
+
+            let run = match cache.steps.get(&hash) {
+                Some(run) => {
+                    wbv.apply(&run.changes);
+                    run
+                }
+                None => {
+                    let before = wbv.keys();
+                    let mut errors = vec![];
+                    let result = self.run_step(step, &mut wbv, &mut errors);
+                    let failed = result.is_err();
+                    errors.extend(result.err());
+                    let run = StepRun {
+                        changes: wbv.changes_since(&before, &reads),
+                        errors,
+                        failed,
+                    };
+                    cache.steps.entry(hash).or_insert(run)
+                }
+            };
+            for (key, _) in run.changes.iter() {
+                versions.insert(key.clone(), hash);
+            }
+            for error in run.errors.iter() {
+                wbv.diagnostics.push(Diagnostic {
+                    step: step.name().to_owned(),
+                    error: error.clone(),
+                });
+            }
+            if run.failed {
+                failed.push(step.name());
+            }
+            cache.used.insert(hash, view);
+        }
+        cache.trim();
+
+        for (name, solid) in wbv.solids.iter_mut() {
+            solid.name = name.to_owned();
+            solid.unit = self.units.length;
+        }
+
+        wbv
+    }
+
+    fn run_step(
+        &self,
+        step: &Step,
+        wbv: &mut WorkbenchView,
+        errors: &mut Vec<Error>,
+    ) -> Result<(), Error> {
+        // An Err means the step made nothing to build on. Anything that only
+        // spoils part of the step, like one edge that won't fillet, goes into
+        // errors and the rest of the step carries on.
+        match step {
+            Step::Point { point: p, name } => {
+                wbv.points.insert(name.to_owned(), p.clone());
+            }
+            Step::Plane { plane: p, name } => {
+                wbv.planes.insert(name.to_owned(), p.clone());
+            }
+            Step::Helix { name, helix } => {
+                // checked again as its parameters may have been edited since
+                let helix = Helix::new(
+                    helix.origin,
+                    helix.axis,
+                    helix.reference,
+                    helix.radius,
+                    helix.pitch,
+                    helix.turns,
+                    helix.taper,
+                )
+                .map_err(Error::GeometryFailure)?;
+                wbv.helices.insert(name.to_owned(), helix);
+            }
+            Step::Sketch {
+                sketch,
+                name,
+                plane,
+            } => {
+                let actual_plane = lookup(&wbv.planes, "plane", plane)?;
+                let transform = actual_plane.to_frame();
+                let sketchview = sketch.create_view(&transform);
+                wbv.sketches.insert(name.to_owned(), sketchview);
+            }
+            Step::Extrusion {
+                name,
+                extrusion,
+                sketch,
+                faces,
+                segments,
+            } => {
+                let sketchview = lookup(&wbv.sketches, "sketch", sketch)?;
+
+                if let Some(thin) = &extrusion.thin {
+                    // a thickened profile only names its ends
+                    let face = sketchview
+                        .thicken(segments, thin.offsets())
+                        .map_err(Error::GeometryFailure)?;
+                    let mut res = face.tsweep(extrusion.direction, extrusion.depth);
+                    res.naming = Some(Naming::new(name, vec![]));
+                    wbv.solids.insert(format!("{}_0", name), res);
+                    return Ok(());
+                }
+
+                let mut count = 0;
+                for face_index in faces.iter() {
+                    let face = sketch_face(sketchview, sketch, *face_index)?;
+                    let mut res = face.tsweep(extrusion.direction, extrusion.depth);
+                    res.naming = Some(sketchview.naming(name, *face_index));
+                    let solid_name = format!("{}_{}", name, count);
+                    wbv.solids.insert(solid_name.to_owned(), res);
+                    count += 1;
+                }
+            }
+            Step::Revolve {
+                name,
+                revolve,
+                sketch,
+                faces,
+                segments,
+            } => {
+                let sketchview = lookup(&wbv.sketches, "sketch", sketch)?;
+                let profiles = match &revolve.thin {
+                    Some(thin) => vec![sketchview
+                        .thicken(segments, thin.offsets())
+                        .map_err(Error::GeometryFailure)?],
+                    None => faces
+                        .iter()
+                        .map(|face_index| sketch_face(sketchview, sketch, *face_index).cloned())
+                        .collect::<Result<_, _>>()?,
+                };
+
+                let mut count = 0;
+                for face in profiles.iter() {
+                    match revolve.sweep(face, sketchview.coordinate_frame.normal) {
+                        Ok(res) => {
+                            wbv.solids.insert(format!("{}_{}", name, count), res);
+                            count += 1;
+                        }
+                        Err(e) => errors.push(Error::GeometryFailure(e)),
+                    }
+                }
+            }
+            Step::Sweep {
+                name,
+                sweep,
+                sketch,
+                faces,
+                path,
+                segments,
+            } => {
+                let sketchview = lookup(&wbv.sketches, "sketch", sketch)?;
+                let follow_path = match sweep.orientation {
+                    SweepOrientation::FollowPath => true,
+                    SweepOrientation::KeepNormal => false,
+                };
+
+                // each face is swept into one solid, along a helix or a sketched path
+                let helix = wbv.helices.get(path);
+                let path_segments = match helix {
+                    Some(_) => vec![],
+                    None => lookup(&wbv.sketches, "sketch or helix", path)?
+                        .path(segments)
+                        .map_err(Error::InvalidParameter)?,
+                };
+                let mut swept = vec![];
+                for face_index in faces.iter() {
+                    let face = sketch_face(sketchview, sketch, *face_index)?;
+                    let solid = match helix {
+                        Some(helix) => helix.sweep(face, follow_path),
+                        None => face.sweep(&path_segments, follow_path),
+                    };
+                    match solid {
+                        Ok(solid) => swept.push(solid),
+                        Err(e) => errors.push(Error::GeometryFailure(e)),
+                    }
+                }
+                wbv.combine_into(
+                    name,
+                    swept,
+                    &sweep.operation,
+                    sweep.target.as_deref(),
+                    errors,
+                )?;
+            }
+            Step::Loft {
+                name,
+                loft: Loft { operation, target },
+                profiles,
+                guides,
+            } => {
+                if profiles.len() < 2 {
+                    return Err(Error::InvalidParameter(format!(
+                        "A loft runs between at least two profiles, and {} has {}",
+                        name,
+                        profiles.len()
+                    )));
+                }
+                // only the outlines are skinned, so a profile with a hole in it
+                // would come out solid
+                let rings = profiles
+                    .iter()
+                    .map(|p| {
+                        let sketchview = lookup(&wbv.sketches, "sketch", &p.sketch)?;
+                        let face = sketch_face(sketchview, &p.sketch, p.face)?;
+                        if !face.interiors.is_empty() {
+                            return Err(Error::InvalidParameter(format!(
+                                "Face {} of {} has a hole in it, and only profiles without holes can be lofted",
+                                p.face, p.sketch
+                            )));
+                        }
+                        Ok(face.exterior.clone())
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let guide_paths = guides
+                    .iter()
+                    .map(|g| {
+                        lookup(&wbv.sketches, "sketch", &g.sketch)?
+                            .path(&g.segments)
+                            .map_err(Error::InvalidParameter)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                let mut sections = match_sections(&rings).map_err(Error::InvalidParameter)?;
+                if !guide_paths.is_empty() {
+                    sections = guided_sections(&sections, &guide_paths, LOFT_GUIDE_STEPS);
+                }
+
+                let res = loft(&sections).map_err(Error::GeometryFailure)?;
+                wbv.combine_into(name, vec![res], operation, target.as_deref(), errors)?;
+            }
+            Step::Fillet { fillet, edges, .. } => {
+                for edge in edges.iter() {
+                    let solid = lookup_mut(&mut wbv.solids, "solid", &edge.solid)?;
+                    if let Err(e) = side_edge(solid, edge, "filleted") {
+                        errors.push(e);
+                        continue;
+                    }
+                    let corner = Corner::Fillet(fillet.radius);
+                    if let Err(e) = solid.set_corner(edge.ring, edge.vertex, corner) {
+                        errors.push(Error::GeometryFailure(e));
+                    }
+                }
+            }
+            Step::Chamfer { chamfer, edges, .. } => {
+                for edge in edges.iter() {
+                    let solid = lookup_mut(&mut wbv.solids, "solid", &edge.solid)?;
+                    if let Err(e) = side_edge(solid, edge, "chamfered") {
+                        errors.push(e);
+                        continue;
+                    }
+                    let corner = match chamfer.mode {
+                        ChamferMode::DistanceDistance(d1, d2) => Corner::Chamfer(d1, d2),
+                        ChamferMode::DistanceAngle(d, angle) => Corner::ChamferAngle(d, angle),
+                    };
+                    if let Err(e) = solid.set_corner(edge.ring, edge.vertex, corner) {
+                        errors.push(Error::GeometryFailure(e));
+                    }
+                }
+            }
+            Step::LinearPattern {
+                name,
+                pattern,
+                features,
+            } => wbv.pattern(
+                name,
+                features,
+                pattern.transforms(),
+                &pattern.operation,
+                pattern.target.as_deref(),
+                errors,
+            )?,
+            Step::CircularPattern {
+                name,
+                pattern,
+                features,
+            } => wbv.pattern(
+                name,
+                features,
+                pattern.transforms(),
+                &pattern.operation,
+                pattern.target.as_deref(),
+                errors,
+            )?,
+            Step::Mirror {
+                name,
+                mirror,
+                features,
+            } => {
+                let (origin, normal) = wbv
+                    .resolve_plane(&mirror.plane)
+                    .ok_or(Error::not_found("plane", &mirror.plane.to_string()))?;
+
+                let mut count = 0;
+                for solid_name in wbv.feature_solids(features) {
+                    let solid = lookup_mut(&mut wbv.solids, "solid", &solid_name)?;
+                    if mirror.merge {
+                        if let Err(e) = solid.merge_mirror(origin, normal) {
+                            errors.push(Error::GeometryFailure(e));
+                        }
+                    } else {
+                        let mirrored = solid.transformed(&Transform::Mirror { origin, normal });
+                        wbv.solids.insert(format!("{}_{}", name, count), mirrored);
+                        count += 1;
+                    }
+                }
+            }
+            Step::Hole {
+                hole,
+                solid,
+                face,
+                sketch,
+                points,
+                ..
+            } => {
+                if let PrismFace::Side(..) = face {
+                    return Err(Error::InvalidParameter(format!(
+                        "Holes can only be drilled into the top or bottom of {}, not its sides",
+                        solid
+                    )));
+                }
+                let centers = lookup(&wbv.sketches, "sketch", sketch)?;
+                let frame = &centers.coordinate_frame;
+                let target = lookup_mut(&mut wbv.solids, "solid", solid)?;
+                let prism = prism_of(target, solid, "drilled")?;
+                let (origin, normal) =
+                    prism
+                        .face_plane(*face)
+                        .ok_or(Error::InvalidParameter(format!(
+                            "{} has no face {:?}",
+                            solid, face
+                        )))?;
+                let thickness = prism.direction.scale(prism.depth).length();
+
+                for id in points.iter() {
+                    let Some(center) = centers.find_point(id) else {
+                        errors.push(Error::not_found("point", id));
+                        continue;
+                    };
+                    // drop the sketch point straight onto the face
+                    let center = frame.to_3d(center);
+                    let gap = origin.to_vector().subtract(center.to_vector()).dot(normal);
+                    let entry = center.add_vec(normal.scale(gap));
+
+                    let drilled = hole
+                        .cut(entry, normal.negate(), thickness)
+                        .and_then(|(cut, thread)| target.add_hole(cut).map(|_| thread));
+                    match drilled {
+                        Ok(Some(thread)) => target.threads.push(thread),
+                        Ok(None) => {}
+                        Err(e) => errors.push(Error::GeometryFailure(e)),
+                    }
+                }
+            }
+            Step::Draft {
+                draft,
+                solid,
+                faces,
+                ..
+            } => {
+                let (origin, normal) = wbv
+                    .resolve_plane(&draft.neutral)
+                    .ok_or(Error::not_found("plane", &draft.neutral.to_string()))?;
+                let target = lookup_mut(&mut wbv.solids, "solid", solid)?;
+                let prism = prism_of(target, solid, "drafted")?;
+                let up = prism.direction.normalize();
+                if normal.normalize().dot(up).abs() < 1.0 - 1e-9 {
+                    return Err(Error::InvalidParameter(
+                        "The neutral plane must be square to the extrusion".to_owned(),
+                    ));
+                }
+                let start = prism.face.exterior.segments[0].start;
+                let neutral = origin.to_vector().subtract(start.to_vector()).dot(up);
+                let face_draft = FaceDraft {
+                    angle: draft.angle,
+                    neutral,
+                };
+
+                for face in faces.iter() {
+                    let drafted = match face {
+                        PrismFace::Side(ring, vertex)
+                            if target
+                                .prism
+                                .as_ref()
+                                .and_then(|p| p.face_plane(*face))
+                                .is_none() =>
+                        {
+                            Err(Error::InvalidParameter(format!(
+                                "{} has no side face {}:{}",
+                                solid, ring, vertex
+                            )))
+                        }
+                        PrismFace::Side(ring, vertex) => target
+                            .set_draft(*ring, *vertex, face_draft)
+                            .map_err(Error::GeometryFailure),
+                        _ => Err(Error::InvalidParameter(
+                            "Only the side faces of an extrusion can be drafted, not its end caps"
+                                .to_owned(),
+                        )),
+                    };
+                    if let Err(e) = drafted {
+                        errors.push(e);
+                    }
+                }
+            }
+            Step::Shell {
+                shell,
+                solid,
+                faces,
+                ..
+            } => {
+                let target = lookup_mut(&mut wbv.solids, "solid", solid)?;
+                prism_of(target, solid, "shelled")?;
+                if faces.iter().any(|f| matches!(f, PrismFace::Side(..))) {
+                    return Err(Error::InvalidParameter(
+                        "Only the end caps of an extruded solid can be opened, not its sides"
+                            .to_owned(),
+                    ));
+                }
+                let outward = matches!(shell.direction, ShellDirection::Outward);
+                target
+                    .shell(shell.thickness, outward, faces)
+                    .map_err(Error::GeometryFailure)?;
+            }
+            Step::BaseFlange {
+                name,
+                flange,
+                sketch,
+                face,
+            } => {
+                let sketchview = lookup(&wbv.sketches, "sketch", sketch)?;
+                let profile = sketch_face(sketchview, sketch, *face)?;
+                let (truck_solid, sheet) = SheetMetal::new(
+                    sketchview.coordinate_frame,
+                    profile.clone(),
+                    flange.thickness,
+                    flange.bend_radius,
+                    flange.k_factor,
+                )
+                .and_then(|sheet| Ok((sheet.to_truck_solid()?, sheet)))
+                .map_err(Error::GeometryFailure)?;
+                let mut res = Solid::new(truck_solid);
+                res.sheet = Some(sheet);
+                wbv.solids.insert(format!("{}_0", name), res);
+            }
+            Step::EdgeFlange {
+                flange,
+                solid,
+                edges,
+                ..
+            } => {
+                let target = lookup_mut(&mut wbv.solids, "solid", solid)?;
+                for edge in edges.iter() {
+                    let bent = target.add_flange(Flange {
+                        edge: *edge,
+                        length: flange.length,
+                        angle: flange.angle,
+                    });
+                    if let Err(e) = bent {
+                        errors.push(Error::GeometryFailure(e));
+                    }
+                }
+            }
+            Step::CosmeticThread {
+                thread,
+                solid,
+                face,
+                ..
+            } => {
+                let target = lookup_mut(&mut wbv.solids, "solid", solid)?;
+                let cylinder = cylinder_face(target, solid, *face)?;
+                let fitted = thread.fit(&cylinder).map_err(Error::GeometryFailure)?;
+                target.threads.push(fitted);
+            }
+            Step::Thread {
+                name,
+                thread,
+                solid,
+                face,
+            } => {
+                let target = lookup(&wbv.solids, "solid", solid)?;
+                let cylinder = cylinder_face(target, solid, *face)?;
+                let modelled = thread.model(&cylinder).map_err(Error::GeometryFailure)?;
+                wbv.solids.insert(format!("{}_0", name), modelled);
+            }
+            Step::Transform {
+                name,
+                transform,
+                bodies,
+            } => {
+                let motion = transform
+                    .motion
+                    .to_transform(&wbv.points)
+                    .map_err(Error::InvalidParameter)?;
+                for (count, body) in wbv.feature_solids(bodies).iter().enumerate() {
+                    let moved = lookup(&wbv.solids, "solid", body)?.transformed(&motion);
+                    if transform.copy {
+                        wbv.solids.insert(format!("{}_{}", name, count), moved);
+                    } else {
+                        wbv.solids.insert(body.to_owned(), moved);
+                    }
+                }
+            }
+            Step::Split { name, plane, solid } => {
+                let (origin, normal) = wbv
+                    .resolve_plane(plane)
+                    .ok_or(Error::not_found("plane", &plane.to_string()))?;
+                let pieces = lookup(&wbv.solids, "solid", solid)?
+                    .split(origin, normal)
+                    .map_err(Error::GeometryFailure)?;
+                wbv.solids.remove(solid);
+                for (count, piece) in pieces.into_iter().enumerate() {
+                    wbv.solids.insert(format!("{}_{}", name, count), piece);
+                }
+            }
+            Step::DeleteBody { bodies, keep, .. } => {
+                let selected = wbv.feature_solids(bodies);
+                wbv.solids
+                    .retain(|solid_name, _| selected.contains(solid_name) == *keep);
+            }
+            Step::Rib {
+                name,
+                rib: r,
+                sketch,
+                segments,
+                solid,
+            } => {
+                let sketchview = lookup(&wbv.sketches, "sketch", sketch)?;
+                let frame = sketchview.coordinate_frame;
+                let chain =
+                    path_polyline(&sketchview.path(segments).map_err(Error::InvalidParameter)?);
+                let prism = prism_of(lookup(&wbv.solids, "solid", solid)?, solid, "ribbed")?;
+                let res = prism
+                    .cross_section(frame.origin, frame.normal)
+                    .and_then(|section| rib(&chain, &section, &frame, r.thickness))
+                    .map_err(Error::GeometryFailure)?;
+                wbv.solids.insert(format!("{}_0", name), res);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_selected_for_operation(
+        &mut self,
+        step_name: &str,
+        parameter_name: &str,
+        sketch_name: &str,
+        selection: Vec<u64>,
+    ) -> Result<(), Error> {
+        for step in self.steps.iter_mut() {
+            match step {
+                Step::Extrusion {
+                    name,
+                    extrusion,
+                    sketch,
+                    faces,
+                    segments,
+                } => {
+                    if name == step_name {
+                        match parameter_name {
+                            "faces" => {
+                                faces.clear();
+                                faces.extend(selection.iter().map(|el| *el as usize));
+                                return Ok(());
+                            }
+                            "segments" => {
+                                segments.clear();
+                                segments.extend(selection.iter().map(|el| *el as usize));
+                                return Ok(());
+                            }
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    parameter_name, step_name
+                                )));
+                            }
+                        }
+                    }
+                }
+                Step::Sweep {
+                    name,
+                    faces,
+                    segments,
+                    ..
+                } if name == step_name => match parameter_name {
+                    "faces" => {
+                        faces.clear();
+                        faces.extend(selection.iter().map(|el| *el as usize));
+                        return Ok(());
+                    }
+                    "path" => {
+                        segments.clear();
+                        segments.extend(selection.iter().map(|el| *el as usize));
+                        return Ok(());
+                    }
+                    _ => {
+                        return Err(Error::InvalidParameter(format!(
+                            "No parameter named {} for step {}",
+                            parameter_name, step_name
+                        )));
+                    }
+                },
+                Step::Revolve {
+                    name,
+                    faces,
+                    segments,
+                    ..
+                } if name == step_name => match parameter_name {
+                    "faces" => {
+                        faces.clear();
+                        faces.extend(selection.iter().map(|el| *el as usize));
+                        return Ok(());
+                    }
+                    "segments" => {
+                        segments.clear();
+                        segments.extend(selection.iter().map(|el| *el as usize));
+                        return Ok(());
+                    }
+                    _ => {
+                        return Err(Error::InvalidParameter(format!(
+                            "No parameter named {} for step {}",
+                            parameter_name, step_name
+                        )));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        return Err(Error::not_found("step", step_name));
+    }
+
+    pub fn add_segment_to_sketch(
+        &mut self,
+        sketch_name: &str,
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    ) -> Result<(), Error> {
+        for step in self.steps.iter_mut() {
+            match step {
+                Step::Sketch {
+                    name,
+                    sketch,
+                    plane,
+                } => {
+                    if name == sketch_name {
+                        let start = Point2D::new(x1, y1, "A");
+                        let end = Point2D::new(x2, y2, "A");
+                        let segments = Segment::link(vec![start, end], false);
+
+                        sketch.add_segments(segments);
+                        // let line = Line::new(p1, p2);
+                        // sketch.add_line(line);
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(Error::not_found("sketch", sketch_name))
+    }
+
+    pub fn step_index(&self, name: &str) -> Option<usize> {
+        self.steps.iter().position(|s| s.name() == name)
+    }
+
+    fn maker(&self, reference: &str) -> Option<usize> {
+        // the step a reference is to, either by name or through a solid it made
+        self.step_index(reference)
+            .or(self.steps.iter().position(|s| made_by(reference, s.name())))
+    }
+
+    pub fn step_dependents(&self, name: &str) -> Vec<String> {
+        // the steps that read from the named one, directly or through others
+        let mut names = vec![name.to_owned()];
+        for step in self.steps.iter() {
+            if !names.iter().any(|n| n == step.name())
+                && step
+                    .references()
+                    .iter()
+                    .any(|r| names.iter().any(|n| made_by(r, n)))
+            {
+                names.push(step.name().to_owned());
+            }
+        }
+        names.remove(0);
+        names
+    }
+
+    fn check_order(&self) -> Result<(), Error> {
+        // every step has to come after the steps it reads from
+        for (i, step) in self.steps.iter().enumerate() {
+            for reference in step.references() {
+                if let Some(j) = self.maker(reference) {
+                    if j >= i {
+                        return Err(Error::Dependency(format!(
+                            "{} uses {}, which would come after it",
+                            step.name(),
+                            reference
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_crossing(&self, from: usize, index: usize) -> Result<(), Error> {
+        // A step that changes a solid in place can't trade places with another
+        // step working on the same solid, as each would see it differently
+        let moved = &self.steps[from];
+        let crossed = match from < index {
+            true => from + 1..index + 1,
+            false => index..from,
+        };
+        for other in self.steps[crossed].iter() {
+            if let Some(solid) = moved.shares_writes(other) {
+                return Err(Error::Dependency(format!(
+                    "{} can't be moved past {}, as both work on {}",
+                    moved.name(),
+                    other.name(),
+                    solid
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_step(&mut self, step: Step) -> Result<(), Error> {
+        // like the add_ builders, but checked, for steps made elsewhere
+        self.insert_step(self.active_steps(), step)
+    }
+
+    pub fn insert_step(&mut self, index: usize, step: Step) -> Result<(), Error> {
+        if index > self.steps.len() {
+            return Err(Error::InvalidParameter(format!(
+                "There is no position {} to insert at",
+                index
+            )));
+        }
+        if self.maker(step.name()).is_some() {
+            return Err(Error::already_exists("step", step.name()));
+        }
+        self.steps.insert(index, step);
+        if let Err(e) = self.check_order() {
+            self.steps.remove(index);
+            return Err(e);
+        }
+        self.step_inserted(index);
+        Ok(())
+    }
+
+    pub fn move_step(&mut self, name: &str, index: usize) -> Result<(), Error> {
+        let from = self
+            .step_index(name)
+            .ok_or(Error::not_found("step", name))?;
+        if index >= self.steps.len() {
+            return Err(Error::InvalidParameter(format!(
+                "There is no position {} to move to",
+                index
+            )));
+        }
+        self.check_crossing(from, index)?;
+        let step = self.steps.remove(from);
+        self.steps.insert(index, step);
+        if let Err(e) = self.check_order() {
+            let step = self.steps.remove(index);
+            self.steps.insert(from, step);
+            return Err(e);
+        }
+        self.step_removed(from);
+        self.step_inserted(index);
+        Ok(())
+    }
+
+    pub fn delete_step(&mut self, name: &str) -> Result<(), Error> {
+        let index = self
+            .step_index(name)
+            .ok_or(Error::not_found("step", name))?;
+        let dependents = self.step_dependents(name);
+        if !dependents.is_empty() {
+            return Err(Error::Dependency(format!(
+                "{} is used by {}",
+                name,
+                dependents.join(", ")
+            )));
+        }
+        self.steps.remove(index);
+        self.step_removed(index);
+        self.bindings.retain(|b| b.step != name);
+        self.suppressed.retain(|n| n != name);
+        Ok(())
+    }
+
+    pub fn restore_step(&mut self, step: Step, bindings: Vec<Binding>) -> Result<(), Error> {
+        // puts back the step of the same name, along with the formulas its
+        // parameters had, as it was before an edit
+        let index = self
+            .step_index(step.name())
+            .ok_or(Error::not_found("step", step.name()))?;
+        self.bindings.retain(|b| b.step != step.name());
+        self.bindings.extend(bindings);
+        self.steps[index] = step;
+        Ok(())
+    }
+
+    pub fn set_step_suppressed(&mut self, name: &str, suppressed: bool) -> Result<(), Error> {
+        self.step_index(name)
+            .ok_or(Error::not_found("step", name))?;
+        self.suppressed.retain(|n| n != name);
+        if suppressed {
+            self.suppressed.push(name.to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn rename_step(&mut self, name: &str, new_name: &str) -> Result<(), Error> {
+        // Everything that refers to the step by name follows it, including the
+        // names of the solids it made
+        let index = self
+            .step_index(name)
+            .ok_or(Error::not_found("step", name))?;
+        if new_name.trim().is_empty() {
+            return Err(Error::InvalidParameter(format!(
+                "'{}' can't be used as a step name",
+                new_name
+            )));
+        }
+        if new_name == name {
+            return Ok(());
+        }
+        if self.maker(new_name).is_some() {
+            return Err(Error::already_exists("step", new_name));
+        }
+        let renamed = |reference: &mut String| {
+            if made_by(reference, name) {
+                *reference = format!("{}{}", new_name, &reference[name.len()..]);
+            }
+        };
+        for step in self.steps.iter_mut() {
+            step.references_mut().into_iter().for_each(renamed);
+        }
+        *self.steps[index].name_mut() = new_name.to_owned();
+        for binding in self.bindings.iter_mut() {
+            if binding.step == name {
+                binding.step = new_name.to_owned();
+            }
+        }
+        self.suppressed.iter_mut().for_each(renamed);
+        Ok(())
+    }
+
+    pub fn set_variable(&mut self, name: &str, expression: &str) -> Result<(), Error> {
+        // Adds the variable or rewrites its formula, then brings every step
+        // parameter written in terms of it up to date
+        if !is_identifier(name) || is_reserved(name) {
+            return Err(Error::InvalidParameter(format!(
+                "{} can't be used as a variable name",
+                name
+            )));
+        }
+        parse_input(expression).map_err(Error::InvalidExpression)?;
+        let prior = self.variables.clone();
+        match self.variables.iter_mut().find(|v| v.name == name) {
+            Some(variable) => variable.expression = expression.to_owned(),
+            None => self.variables.push(Variable {
+                name: name.to_owned(),
+                expression: expression.to_owned(),
+            }),
+        }
+        if let Err(e) = self.apply_bindings() {
+            self.variables = prior;
+            self.apply_bindings()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn remove_variable(&mut self, name: &str) -> Result<(), Error> {
+        let dependents = self.dependents(name);
+        if !dependents.is_empty() {
+            return Err(Error::Dependency(format!(
+                "{} is used by {}",
+                name,
+                dependents.join(", ")
+            )));
+        }
+        self.variables.retain(|v| v.name != name);
+        Ok(())
+    }
+
+    pub fn evaluate_variables(&self) -> Result<HashMap<String, f64>, Error> {
+        Ok(self
+            .variable_values()?
+            .into_iter()
+            .map(|(name, value)| (name, value.number))
+            .collect())
+    }
+
+    fn variable_values(&self) -> Result<HashMap<String, Value>, Error> {
+        let mut formulas: HashMap<String, Expression> = HashMap::new();
+        for variable in self.variables.iter() {
+            let expression = parse_input(&variable.expression)
+                .map_err(|e| Error::InvalidExpression(format!("In {}: {}", variable.name, e)))?;
+            formulas.insert(variable.name.to_owned(), expression);
+        }
+        let mut values = HashMap::new();
+        for variable in self.variables.iter() {
+            resolve(
+                &variable.name,
+                &formulas,
+                &self.units,
+                &mut values,
+                &mut vec![],
+            )?;
+        }
+        Ok(values)
+    }
+
+    pub fn evaluate(&self, expression: &str) -> Result<f64, Error> {
+        let values = self.variable_values()?;
+        Ok(parse_input(expression)
+            .and_then(|e| e.evaluate(&values, &self.units))
+            .map_err(Error::InvalidExpression)?
+            .number)
+    }
+
+    fn evaluate_parameter(
+        &self,
+        parameter_name: &str,
+        expression: &str,
+        values: &HashMap<String, Value>,
+    ) -> Result<f64, Error> {
+        let value = parse_input(expression)
+            .and_then(|e| e.evaluate(values, &self.units))
+            .map_err(Error::InvalidExpression)?;
+        Ok(self.units.model(value, parameter_quantity(parameter_name)))
+    }
+
+    pub fn set_units(&mut self, units: Units) -> Result<(), Error> {
+        // The model keeps its size. Lengths held as plain numbers are
+        // converted to the new unit, and bound parameters are worked out
+        // again, so "1/4 in" stays a quarter inch. A formula's bare numbers
+        // are read in the new unit, as they would be if typed in afresh.
+        let prior = (self.units, self.steps.clone());
+        let factor = self.units.length.millimeters() / units.length.millimeters();
+        if factor != 1.0 {
+            for step in self.steps.iter_mut() {
+                step.scale_lengths(factor);
+            }
+        }
+        self.units = units;
+        if let Err(e) = self.apply_bindings() {
+            (self.units, self.steps) = prior;
+            self.apply_bindings()?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn dependents(&self, name: &str) -> Vec<String> {
+        // the variables and bound step parameters that use name, directly or
+        // through other variables, the latter as step.parameter
+        let uses = |expression: &str, names: &[String]| {
+            parse_input(expression)
+                .map(|e| e.variables().iter().any(|v| names.contains(v)))
+                .unwrap_or(false)
+        };
+        let mut names = vec![name.to_owned()];
+        loop {
+            let more: Vec<String> = self
+                .variables
+                .iter()
+                .filter(|v| !names.contains(&v.name) && uses(&v.expression, &names))
+                .map(|v| v.name.to_owned())
+                .collect();
+            if more.is_empty() {
+                break;
+            }
+            names.extend(more);
+        }
+        let bound: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|b| uses(&b.expression, &names))
+            .map(|b| format!("{}.{}", b.step, b.parameter))
+            .collect();
+        names.remove(0);
+        names.extend(bound);
+        names
+    }
+
+    pub fn bind_step_parameter(
+        &mut self,
+        step_name: &str,
+        parameter_name: &str,
+        expression: &str,
+    ) -> Result<(), Error> {
+        // the parameter follows the formula from now on, until it is given a
+        // plain value or unbound
+        let value =
+            self.evaluate_parameter(parameter_name, expression, &self.variable_values()?)?;
+        self.apply_step_parameters(step_name, vec![parameter_name.to_owned()], vec![value])?;
+        let binding = Binding {
+            step: step_name.to_owned(),
+            parameter: parameter_name.to_owned(),
+            expression: expression.to_owned(),
+        };
+        match self
+            .bindings
+            .iter_mut()
+            .find(|b| b.step == step_name && b.parameter == parameter_name)
+        {
+            Some(existing) => *existing = binding,
+            None => self.bindings.push(binding),
+        }
+        Ok(())
+    }
+
+    pub fn unbind_step_parameter(&mut self, step_name: &str, parameter_name: &str) {
+        // the parameter keeps the value it last had
+        self.bindings
+            .retain(|b| b.step != step_name || b.parameter != parameter_name);
+    }
+
+    fn apply_bindings(&mut self) -> Result<(), Error> {
+        let values = self.variable_values()?;
+        for binding in self.bindings.clone() {
+            let value = self
+                .evaluate_parameter(&binding.parameter, &binding.expression, &values)
+                .map_err(|e| {
+                    Error::InvalidExpression(format!(
+                        "In {}.{}: {}",
+                        binding.step, binding.parameter, e
+                    ))
+                })?;
+            self.apply_step_parameters(&binding.step, vec![binding.parameter], vec![value])?;
+        }
+        Ok(())
+    }
+
+    pub fn set_step_parameters(
+        &mut self,
+        step_name: &str,
+        parameter_names: Vec<String>,
+        value: Vec<f64>,
+    ) -> Result<(), Error> {
+        // a plain value replaces any formula the parameter had
+        self.apply_step_parameters(step_name, parameter_names.clone(), value)?;
+        self.bindings
+            .retain(|b| b.step != step_name || !parameter_names.contains(&b.parameter));
+        Ok(())
+    }
+
+    fn apply_step_parameters(
+        &mut self,
+        step_name: &str,
+        parameter_names: Vec<String>,
+        values: Vec<f64>,
+    ) -> Result<(), Error> {
+        check_parameter_values(&parameter_names, &values)?;
+        let parameters = parameter_names.iter().zip(values);
+        for step in self.steps.iter_mut() {
+            match step {
+                Step::Extrusion {
+                    name, extrusion, ..
+                } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "depth" => {
+                                extrusion.depth = value;
+                            }
+                            "thickness" if extrusion.thin.is_some() => {
+                                if let Some(thin) = extrusion.thin.as_mut() {
+                                    thin.thickness = value;
+                                }
+                            }
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Fillet { name, fillet, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "radius" => {
+                                fillet.radius = value;
+                            }
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::LinearPattern { name, pattern, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        let direction = match name.as_str() {
+                            "count" | "spacing" => pattern.directions.get_mut(0),
+                            "count2" | "spacing2" => pattern.directions.get_mut(1),
+                            _ => None,
+                        };
+                        match (name.as_str(), direction) {
+                            ("count", Some(d)) | ("count2", Some(d)) => {
+                                d.count = value.round().max(1.0) as usize;
+                            }
+                            ("spacing", Some(d)) | ("spacing2", Some(d)) => d.spacing = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::CircularPattern { name, pattern, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "count" => pattern.count = value.round().max(1.0) as usize,
+                            "spacing" => pattern.spacing = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Hole { name, hole, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match (name.as_str(), &mut hole.depth) {
+                            ("depth", HoleDepth::Blind(depth)) => *depth = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Draft { name, draft, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "angle" => draft.angle = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Revolve { name, revolve, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "angle" => revolve.angle = value,
+                            "thickness" if revolve.thin.is_some() => {
+                                if let Some(thin) = revolve.thin.as_mut() {
+                                    thin.thickness = value;
+                                }
+                            }
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Helix { name, helix } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "radius" => helix.radius = value,
+                            "pitch" => helix.pitch = value,
+                            "turns" => helix.turns = value,
+                            "taper" => helix.taper = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Thread { name, thread, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "length" => thread.length = Some(value),
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::CosmeticThread { name, thread, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "length" => thread.length = Some(value),
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Transform {
+                    name, transform, ..
+                } if name == step_name => {
+                    for (name, value) in parameters {
+                        match (name.as_str(), &mut transform.motion) {
+                            ("distance", Motion::Translate { distance, .. }) => *distance = value,
+                            ("angle", Motion::Rotate { angle, .. }) => *angle = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::BaseFlange { name, flange, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "thickness" => flange.thickness = value,
+                            "bend_radius" => flange.bend_radius = value,
+                            "k_factor" => flange.k_factor = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::EdgeFlange { name, flange, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "length" => flange.length = value,
+                            "angle" => flange.angle = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Rib { name, rib, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "thickness" => rib.thickness = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Shell { name, shell, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match name.as_str() {
+                            "thickness" => shell.thickness = value,
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Chamfer { name, chamfer, .. } if name == step_name => {
+                    for (name, value) in parameters {
+                        match (name.as_str(), &mut chamfer.mode) {
+                            ("distance", ChamferMode::DistanceDistance(d, _))
+                            | ("distance", ChamferMode::DistanceAngle(d, _)) => {
+                                *d = value;
+                            }
+                            ("distance2", ChamferMode::DistanceDistance(_, d)) => {
+                                *d = value;
+                            }
+                            ("angle", ChamferMode::DistanceAngle(_, angle)) => {
+                                *angle = value;
+                            }
+                            _ => {
+                                return Err(Error::InvalidParameter(format!(
+                                    "No parameter named {} for step {}",
+                                    name, step_name
+                                )));
+                            }
+                        }
+                    }
+                    return Ok(());
+                }
+                Step::Sketch { name, sketch, .. } if name == step_name => {
+                    // sketch dimensions are the coordinates of its points, as "A.x"
+                    for (name, value) in parameters {
+                        let Some((id, axis)) = name.rsplit_once('.') else {
+                            return Err(Error::InvalidParameter(format!(
+                                "No parameter named {} for step {}",
+                                name, step_name
+                            )));
+                        };
+                        sketch
+                            .set_point_coordinate(id, axis, value)
+                            .map_err(Error::InvalidParameter)?;
+                    }
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err(Error::not_found("step", step_name))
+    }
+}
+
+pub(crate) fn check_parameter_values(names: &[String], values: &[f64]) -> Result<(), Error> {
+    if names.len() != values.len() {
+        return Err(Error::InvalidParameter(format!(
+            "{} parameters were given {} values",
+            names.len(),
+            values.len()
+        )));
+    }
+    Ok(())
+}
+
+fn lookup<'a, T>(
+    map: &'a HashMap<String, T>,
+    kind: &'static str,
+    name: &str,
+) -> Result<&'a T, Error> {
+    map.get(name).ok_or(Error::not_found(kind, name))
+}
+
+fn lookup_mut<'a, T>(
+    map: &'a mut HashMap<String, T>,
+    kind: &'static str,
+    name: &str,
+) -> Result<&'a mut T, Error> {
+    map.get_mut(name).ok_or(Error::not_found(kind, name))
+}
+
+fn sketch_face<'a>(
+    view: &'a SketchView,
+    sketch: &str,
+    index: usize,
+) -> Result<&'a LineFace, Error> {
+    view.faces.get(index).ok_or(Error::InvalidParameter(format!(
+        "{} has no face {}",
+        sketch, index
+    )))
+}
+
+fn cylinder_face(solid: &Solid, name: &str, index: usize) -> Result<Cylinder, Error> {
+    solid
+        .cylinders()
+        .get(index)
+        .copied()
+        .ok_or(Error::InvalidParameter(format!(
+            "{} has no cylindrical face {}",
+            name, index
+        )))
+}
+
+fn prism_of<'a>(solid: &'a Solid, name: &str, treatment: &str) -> Result<&'a Prism, Error> {
+    // Holes, drafts, shells and ribs are all rebuilt from the extruded profile
+    solid.prism.as_ref().ok_or(Error::InvalidParameter(format!(
+        "{} can't be {}: only plain extrusions can be, not revolves, sweeps, lofts or \
+         bodies that have been joined, cut or reshaped",
+        name, treatment
+    )))
+}
+
+fn side_edge(solid: &Solid, edge: &EdgeId, treatment: &str) -> Result<(), Error> {
+    // Corners are only rebuilt into the profile of a plain extrusion, so edges of
+    // revolves, sweeps, lofts and of anything joined, cut or reshaped since are
+    // out of reach, as are the edges around either cap
+    let prism = solid.prism.as_ref().ok_or(Error::InvalidParameter(format!(
+        "{} can't be {}: only the side edges of a plain extrusion can be, not the edges \
+         of a revolve, sweep or loft or of a body that has been joined, cut or reshaped",
+        edge.solid, treatment
+    )))?;
+    prism
+        .lateral_edge(edge.ring, edge.vertex)
+        .map(|_| ())
+        .ok_or(Error::InvalidParameter(format!(
+            "{} has no side edge {}:{}, and only the side edges of an extrusion can be {}, \
+             not those around its ends",
+            edge.solid, edge.ring, edge.vertex, treatment
+        )))
+}
+
+fn parameter_quantity(parameter_name: &str) -> Quantity {
+    match parameter_name {
+        "angle" | "taper" => Quantity::Angle,
+        "count" | "turns" | "k_factor" => Quantity::Count,
+        _ => Quantity::Length,
+    }
+}
+
+fn resolve(
+    name: &str,
+    formulas: &HashMap<String, Expression>,
+    units: &Units,
+    values: &mut HashMap<String, Value>,
+    chain: &mut Vec<String>,
+) -> Result<Value, Error> {
+    // evaluates a variable after everything it depends on, following the chain
+    // of dependencies to catch any that loop back on themselves
+    if let Some(value) = values.get(name) {
+        return Ok(*value);
+    }
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_owned());
+        return Err(Error::Dependency(format!(
+            "Variables depend on each other: {}",
+            chain.join(" -> ")
+        )));
+    }
+    let formula = formulas
+        .get(name)
+        .ok_or(Error::not_found("variable", name))?;
+    chain.push(name.to_owned());
+    for dependency in formula.variables() {
+        resolve(&dependency, formulas, units, values, chain)?;
+    }
+    chain.pop();
+    let value = formula
+        .evaluate(values, units)
+        .map_err(|e| Error::InvalidExpression(format!("In {}: {}", name, e)))?;
+    values.insert(name.to_owned(), value);
+    Ok(value)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub expression: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Binding {
+    // a step parameter, as passed to set_step_parameters, and its formula
+    pub step: String,
+    pub parameter: String,
+    pub expression: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Step {
+    Point {
+        name: String,
+        point: Point,
+    },
+    Plane {
+        name: String,
+        plane: Plane,
+    },
+    Helix {
+        name: String,
+        helix: Helix,
+    },
+    Sketch {
+        name: String,
+        sketch: Sketch,
+        plane: String,
+    },
+    Extrusion {
+        name: String,
+        extrusion: Extrusion,
+        sketch: String,
+        faces: Vec<usize>,
+        // the chain to thicken when the extrusion is thin, in place of faces
+        #[serde(default)]
+        segments: Vec<usize>,
+    },
+    Revolve {
+        name: String,
+        revolve: Revolve,
+        sketch: String,
+        faces: Vec<usize>,
+        segments: Vec<usize>,
+    },
+    Sweep {
+        name: String,
+        sweep: Sweep,
+        sketch: String,
+        faces: Vec<usize>,
+        path: String,
+        segments: Vec<usize>,
+    },
+    Loft {
+        name: String,
+        loft: Loft,
+        profiles: Vec<Profile>,
+        guides: Vec<Guide>,
+    },
+    Fillet {
+        name: String,
+        fillet: Fillet,
+        edges: Vec<EdgeId>,
+    },
+    Chamfer {
+        name: String,
+        chamfer: Chamfer,
+        edges: Vec<EdgeId>,
+    },
+    Shell {
+        name: String,
+        shell: Shell,
+        solid: String,
+        faces: Vec<PrismFace>,
+    },
+    LinearPattern {
+        name: String,
+        pattern: LinearPattern,
+        // step names (all of their solids) or individual solid names
+        features: Vec<String>,
+    },
+    CircularPattern {
+        name: String,
+        pattern: CircularPattern,
+        features: Vec<String>,
+    },
+    Mirror {
+        name: String,
+        mirror: Mirror,
+        features: Vec<String>,
+    },
+    Hole {
+        name: String,
+        hole: Hole,
+        solid: String,
+        face: PrismFace,
+        // the sketch holding the hole centers, and the ids of those points
+        sketch: String,
+        points: Vec<String>,
+    },
+    Draft {
+        name: String,
+        draft: Draft,
+        solid: String,
+        faces: Vec<PrismFace>,
+    },
+    Rib {
+        name: String,
+        rib: Rib,
+        // an open chain of segments in the sketch, closed off against the
+        // solid's section in the sketch plane
+        sketch: String,
+        segments: Vec<usize>,
+        solid: String,
+    },
+    CosmeticThread {
+        name: String,
+        thread: CosmeticThread,
+        solid: String,
+        // an index into the solid's cylinders()
+        face: usize,
+    },
+    Thread {
+        name: String,
+        thread: ModelledThread,
+        solid: String,
+        // an index into the solid's cylinders()
+        face: usize,
+    },
+    Transform {
+        name: String,
+        transform: BodyTransform,
+        // step names (all of their solids) or individual solid names
+        bodies: Vec<String>,
+    },
+    Split {
+        name: String,
+        plane: PlaneRef,
+        solid: String,
+    },
+    DeleteBody {
+        name: String,
+        // step names (all of their solids) or individual solid names
+        bodies: Vec<String>,
+        // keep the selected bodies and delete every other one instead
+        keep: bool,
+    },
+    BaseFlange {
+        name: String,
+        flange: BaseFlange,
+        sketch: String,
+        face: usize,
+    },
+    EdgeFlange {
+        name: String,
+        flange: EdgeFlange,
+        solid: String,
+        // edges of the base flange's outline
+        edges: Vec<usize>,
+    },
+}
+
+impl Step {
+    pub fn name(&self) -> &str {
+        match self {
+            Step::Point { name, .. }
+            | Step::Plane { name, .. }
+            | Step::Helix { name, .. }
+            | Step::Sketch { name, .. }
+            | Step::Extrusion { name, .. }
+            | Step::Revolve { name, .. }
+            | Step::Sweep { name, .. }
+            | Step::Loft { name, .. }
+            | Step::Fillet { name, .. }
+            | Step::Chamfer { name, .. }
+            | Step::Shell { name, .. }
+            | Step::LinearPattern { name, .. }
+            | Step::CircularPattern { name, .. }
+            | Step::Mirror { name, .. }
+            | Step::Hole { name, .. }
+            | Step::Draft { name, .. }
+            | Step::Rib { name, .. }
+            | Step::CosmeticThread { name, .. }
+            | Step::Thread { name, .. }
+            | Step::Transform { name, .. }
+            | Step::Split { name, .. }
+            | Step::DeleteBody { name, .. }
+            | Step::BaseFlange { name, .. }
+            | Step::EdgeFlange { name, .. } => name,
+        }
+    }
+
+    fn name_mut(&mut self) -> &mut String {
+        match self {
+            Step::Point { name, .. }
+            | Step::Plane { name, .. }
+            | Step::Helix { name, .. }
+            | Step::Sketch { name, .. }
+            | Step::Extrusion { name, .. }
+            | Step::Revolve { name, .. }
+            | Step::Sweep { name, .. }
+            | Step::Loft { name, .. }
+            | Step::Fillet { name, .. }
+            | Step::Chamfer { name, .. }
+            | Step::Shell { name, .. }
+            | Step::LinearPattern { name, .. }
+            | Step::CircularPattern { name, .. }
+            | Step::Mirror { name, .. }
+            | Step::Hole { name, .. }
+            | Step::Draft { name, .. }
+            | Step::Rib { name, .. }
+            | Step::CosmeticThread { name, .. }
+            | Step::Thread { name, .. }
+            | Step::Transform { name, .. }
+            | Step::Split { name, .. }
+            | Step::DeleteBody { name, .. }
+            | Step::BaseFlange { name, .. }
+            | Step::EdgeFlange { name, .. } => name,
+        }
+    }
+
+    fn scale_lengths(&mut self, factor: f64) {
+        // every length the step holds as a plain number, so the model keeps
+        // its size when those numbers are read in another unit
+        let scale = |length: &mut f64| *length *= factor;
+        match self {
+            Step::Point { point, .. } => *point = point.scale(factor),
+            Step::Plane { plane, .. } => {
+                plane.origin = plane.origin.scale(factor);
+                plane.frame.origin = plane.frame.origin.scale(factor);
+                scale(&mut plane.width);
+                scale(&mut plane.height);
+            }
+            Step::Helix { helix, .. } => {
+                helix.origin = helix.origin.scale(factor);
+                scale(&mut helix.radius);
+                scale(&mut helix.pitch);
+            }
+            Step::Sketch { sketch, .. } => sketch.scale(factor),
+            Step::Extrusion { extrusion, .. } => {
+                scale(&mut extrusion.depth);
+                extrusion
+                    .thin
+                    .iter_mut()
+                    .for_each(|t| scale(&mut t.thickness));
+            }
+            Step::Revolve { revolve, .. } => {
+                revolve.origin = revolve.origin.scale(factor);
+                revolve
+                    .thin
+                    .iter_mut()
+                    .for_each(|t| scale(&mut t.thickness));
+            }
+            Step::Fillet { fillet, .. } => scale(&mut fillet.radius),
+            Step::Chamfer { chamfer, .. } => match &mut chamfer.mode {
+                ChamferMode::DistanceDistance(d1, d2) => {
+                    scale(d1);
+                    scale(d2);
+                }
+                ChamferMode::DistanceAngle(d, _) => scale(d),
+            },
+            Step::Shell { shell, .. } => scale(&mut shell.thickness),
+            Step::LinearPattern { pattern, .. } => pattern
+                .directions
+                .iter_mut()
+                .for_each(|d| scale(&mut d.spacing)),
+            Step::CircularPattern { pattern, .. } => pattern.origin = pattern.origin.scale(factor),
+            Step::Hole { hole, .. } => {
+                if let HoleDepth::Blind(depth) = &mut hole.depth {
+                    scale(depth);
+                }
+            }
+            Step::Rib { rib, .. } => scale(&mut rib.thickness),
+            Step::CosmeticThread { thread, .. } => thread.length.iter_mut().for_each(scale),
+            Step::Thread { thread, .. } => thread.length.iter_mut().for_each(scale),
+            Step::Transform { transform, .. } => match &mut transform.motion {
+                Motion::Translate { distance, .. } => scale(distance),
+                Motion::Rotate { origin, .. } => *origin = origin.scale(factor),
+                Motion::PointToPoint { .. } => {}
+            },
+            Step::BaseFlange { flange, .. } => {
+                scale(&mut flange.thickness);
+                scale(&mut flange.bend_radius);
+            }
+            Step::EdgeFlange { flange, .. } => scale(&mut flange.length),
+            Step::Sweep { .. }
+            | Step::Loft { .. }
+            | Step::Mirror { .. }
+            | Step::Draft { .. }
+            | Step::Split { .. }
+            | Step::DeleteBody { .. } => {}
+        }
+    }
+
+    pub fn references(&self) -> Vec<&str> {
+        // the names of the steps, or of the solids they made, that this step
+        // reads from
+        let mut references: Vec<&str> = vec![];
+        match self {
+            Step::Point { .. } | Step::Plane { .. } | Step::Helix { .. } => {}
+            Step::Sketch { plane, .. } => references.push(plane),
+            Step::Extrusion { sketch, .. }
+            | Step::Revolve { sketch, .. }
+            | Step::BaseFlange { sketch, .. } => references.push(sketch),
+            Step::Sweep {
+                sketch,
+                path,
+                sweep: Sweep { target, .. },
+                ..
+            } => {
+                references.extend([sketch.as_str(), path]);
+                references.extend(target.as_deref());
+            }
+            Step::Loft {
+                loft: Loft { target, .. },
+                profiles,
+                guides,
+                ..
+            } => {
+                references.extend(profiles.iter().map(|p| p.sketch.as_str()));
+                references.extend(guides.iter().map(|g| g.sketch.as_str()));
+                references.extend(target.as_deref());
+            }
+            Step::Fillet { edges, .. } | Step::Chamfer { edges, .. } => {
+                references.extend(edges.iter().map(|e| e.solid.as_str()))
+            }
+            Step::Shell { solid, .. }
+            | Step::CosmeticThread { solid, .. }
+            | Step::Thread { solid, .. }
+            | Step::EdgeFlange { solid, .. } => references.push(solid),
+            Step::Draft { draft, solid, .. } => {
+                references.extend([solid.as_str(), draft.neutral.reference()])
+            }
+            Step::LinearPattern {
+                pattern: LinearPattern { target, .. },
+                features,
+                ..
+            }
+            | Step::CircularPattern {
+                pattern: CircularPattern { target, .. },
+                features,
+                ..
+            } => {
+                references.extend(features.iter().map(|f| f.as_str()));
+                references.extend(target.as_deref());
+            }
+            Step::DeleteBody {
+                bodies: features, ..
+            } => references.extend(features.iter().map(|f| f.as_str())),
+            Step::Mirror {
+                mirror, features, ..
+            } => {
+                references.extend(features.iter().map(|f| f.as_str()));
+                references.push(mirror.plane.reference());
+            }
+            Step::Hole { solid, sketch, .. } | Step::Rib { solid, sketch, .. } => {
+                references.extend([solid.as_str(), sketch])
+            }
+            Step::Transform {
+                transform, bodies, ..
+            } => {
+                references.extend(bodies.iter().map(|b| b.as_str()));
+                if let Motion::PointToPoint { from, to } = &transform.motion {
+                    references.extend([from.as_str(), to]);
+                }
+            }
+            Step::Split { plane, solid, .. } => references.extend([plane.reference(), solid]),
+        }
+        references
+    }
+
+    pub fn writes(&self) -> Vec<&str> {
+        // the solids among its references that this step changes in place or
+        // takes away, rather than only reading
+        let mut writes: Vec<&str> = vec![];
+        match self {
+            Step::Fillet { edges, .. } | Step::Chamfer { edges, .. } => {
+                writes.extend(edges.iter().map(|e| e.solid.as_str()))
+            }
+            Step::Shell { solid, .. }
+            | Step::CosmeticThread { solid, .. }
+            | Step::EdgeFlange { solid, .. }
+            | Step::Hole { solid, .. }
+            | Step::Draft { solid, .. }
+            | Step::Split { solid, .. } => writes.push(solid),
+            Step::Mirror {
+                mirror, features, ..
+            } if mirror.merge => writes.extend(features.iter().map(|f| f.as_str())),
+            Step::Transform {
+                transform, bodies, ..
+            } if !transform.copy => writes.extend(bodies.iter().map(|b| b.as_str())),
+            Step::DeleteBody { bodies, .. } => writes.extend(bodies.iter().map(|b| b.as_str())),
+            Step::LinearPattern {
+                pattern: LinearPattern { target, .. },
+                ..
+            }
+            | Step::CircularPattern {
+                pattern: CircularPattern { target, .. },
+                ..
+            }
+            | Step::Sweep {
+                sweep: Sweep { target, .. },
+                ..
+            }
+            | Step::Loft {
+                loft: Loft { target, .. },
+                ..
+            } => writes.extend(target.as_deref()),
+            _ => {}
+        }
+        writes
+    }
+
+    fn shares_writes<'a>(&'a self, other: &'a Step) -> Option<&'a str> {
+        // a solid one step changes in place and the other works on at all
+        let same = |a: &str, b: &str| made_by(a, b) || made_by(b, a);
+        let everything = |step: &Step| matches!(step, Step::DeleteBody { keep: true, .. });
+        let crossing = |writer: &'a Step, reader: &'a Step| {
+            let reads = reader.references();
+            writer.writes().into_iter().find(|w| {
+                (everything(writer) && !reads.is_empty()) || reads.iter().any(|r| same(w, r))
+            })
+        };
+        crossing(self, other).or(crossing(other, self))
+    }
+
+    fn references_mut(&mut self) -> Vec<&mut String> {
+        let mut references: Vec<&mut String> = vec![];
+        match self {
+            Step::Point { .. } | Step::Plane { .. } | Step::Helix { .. } => {}
+            Step::Sketch { plane, .. } => references.push(plane),
+            Step::Extrusion { sketch, .. }
+            | Step::Revolve { sketch, .. }
+            | Step::BaseFlange { sketch, .. } => references.push(sketch),
+            Step::Sweep {
+                sketch,
+                path,
+                sweep: Sweep { target, .. },
+                ..
+            } => {
+                references.extend([sketch, path]);
+                references.extend(target.as_mut());
+            }
+            Step::Loft {
+                loft: Loft { target, .. },
+                profiles,
+                guides,
+                ..
+            } => {
+                references.extend(profiles.iter_mut().map(|p| &mut p.sketch));
+                references.extend(guides.iter_mut().map(|g| &mut g.sketch));
+                references.extend(target.as_mut());
+            }
+            Step::Fillet { edges, .. } | Step::Chamfer { edges, .. } => {
+                references.extend(edges.iter_mut().map(|e| &mut e.solid))
+            }
+            Step::Shell { solid, .. }
+            | Step::CosmeticThread { solid, .. }
+            | Step::Thread { solid, .. }
+            | Step::EdgeFlange { solid, .. } => references.push(solid),
+            Step::Draft { draft, solid, .. } => {
+                references.extend([solid, draft.neutral.reference_mut()])
+            }
+            Step::LinearPattern {
+                pattern: LinearPattern { target, .. },
+                features,
+                ..
+            }
+            | Step::CircularPattern {
+                pattern: CircularPattern { target, .. },
+                features,
+                ..
+            } => {
+                references.extend(features.iter_mut());
+                references.extend(target.as_mut());
+            }
+            Step::DeleteBody {
+                bodies: features, ..
+            } => references.extend(features.iter_mut()),
+            Step::Mirror {
+                mirror, features, ..
+            } => {
+                references.extend(features.iter_mut());
+                references.push(mirror.plane.reference_mut());
+            }
+            Step::Hole { solid, sketch, .. } | Step::Rib { solid, sketch, .. } => {
+                references.extend([solid, sketch])
+            }
+            Step::Transform {
+                transform, bodies, ..
+            } => {
+                references.extend(bodies.iter_mut());
+                if let Motion::PointToPoint { from, to } = &mut transform.motion {
+                    references.extend([from, to]);
+                }
+            }
+            Step::Split { plane, solid, .. } => references.extend([plane.reference_mut(), solid]),
+        }
+        references
+    }
+}
+
+fn made_by(reference: &str, step_name: &str) -> bool {
+    // a step's own name, or one of the solids it made, named step_0, step_1..
+    reference == step_name
+        || reference
+            .strip_prefix(step_name)
+            .and_then(|rest| rest.strip_prefix('_'))
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Operation {
+    New,
+    Add,
+    Remove,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::New => write!(f, "New"),
+            Operation::Add => write!(f, "Add"),
+            Operation::Remove => write!(f, "Remove"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Extrusion {
+    pub depth: f64,
+    pub direction: Vector,
+    pub operation: Operation,
+    #[serde(default)]
+    pub thin: Option<Thin>,
+}
+impl Extrusion {
+    pub fn new(depth: f64, direction: Vector, operation: Operation) -> Extrusion {
+        Extrusion {
+            depth: depth,
+            direction: direction,
+            operation: operation,
+            thin: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Revolve {
+    // the axis should lie in the sketch plane, clear of the profile
+    pub origin: Point,
+    pub axis: Vector,
+    // radians, counterclockwise looking back down the axis
+    pub angle: f64,
+    pub operation: Operation,
+    pub thin: Option<Thin>,
+}
+
+impl Revolve {
+    pub fn new(origin: Point, axis: Vector, angle: f64, operation: Operation) -> Revolve {
+        Revolve {
+            origin,
+            axis,
+            angle,
+            operation,
+            thin: None,
+        }
+    }
+
+    pub fn sweep(&self, face: &LineFace, normal: Vector) -> Result<Solid, String> {
+        let axis = self.axis.normalize();
+        if axis.dot(normal).abs() > 1e-9 {
+            return Err("The axis must lie in the sketch plane".to_owned());
+        }
+        if self.angle.abs() < 1e-12 {
+            return Err("The angle of revolution must not be zero".to_owned());
+        }
+        let across = normal.cross(axis);
+        let sides: Vec<f64> = face
+            .exterior
+            .segments
+            .iter()
+            .map(|s| {
+                s.start
+                    .to_vector()
+                    .subtract(self.origin.to_vector())
+                    .dot(across)
+            })
+            .collect();
+        let lowest = sides.iter().cloned().fold(f64::MAX, f64::min);
+        let highest = sides.iter().cloned().fold(f64::MIN, f64::max);
+        if lowest < -1e-9 && highest > 1e-9 {
+            return Err("The profile crosses the axis of revolution".to_owned());
+        }
+        Ok(face.rsweep(self.origin, axis, self.angle))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ThinSide {
+    // to the left of the chain, looking down the sketch normal
+    OneSide,
+    OtherSide,
+    Mid,
+}
+
+impl fmt::Display for ThinSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThinSide::OneSide => write!(f, "One Side"),
+            ThinSide::OtherSide => write!(f, "Other Side"),
+            ThinSide::Mid => write!(f, "Mid"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Thin {
+    pub thickness: f64,
+    pub side: ThinSide,
+}
+
+impl Thin {
+    pub fn new(thickness: f64, side: ThinSide) -> Thin {
+        Thin { thickness, side }
+    }
+
+    pub fn offsets(&self) -> (f64, f64) {
+        match self.side {
+            ThinSide::OneSide => (0.0, self.thickness),
+            ThinSide::OtherSide => (-self.thickness, 0.0),
+            ThinSide::Mid => (-self.thickness / 2.0, self.thickness / 2.0),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum SweepOrientation {
+    FollowPath,
+    KeepNormal,
+}
+
+impl fmt::Display for SweepOrientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepOrientation::FollowPath => write!(f, "FollowPath"),
+            SweepOrientation::KeepNormal => write!(f, "KeepNormal"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sweep {
+    pub orientation: SweepOrientation,
+    pub operation: Operation,
+    // the body Add and Remove sweeps join to or cut from
+    #[serde(default)]
+    pub target: Option<String>,
+}
+impl Sweep {
+    pub fn new(orientation: SweepOrientation, operation: Operation) -> Sweep {
+        Sweep {
+            orientation,
+            operation,
+            target: None,
+        }
+    }
+}
+
+const LOFT_GUIDE_STEPS: usize = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Loft {
+    pub operation: Operation,
+    // the body Add and Remove lofts join to or cut from
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub sketch: String,
+    pub face: usize,
+}
+impl Profile {
+    pub fn new(sketch: &str, face: usize) -> Profile {
+        Profile {
+            sketch: sketch.to_owned(),
+            face,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Guide {
+    pub sketch: String,
+    pub segments: Vec<usize>,
+}
+impl Guide {
+    pub fn new(sketch: &str, segments: Vec<usize>) -> Guide {
+        Guide {
+            sketch: sketch.to_owned(),
+            segments,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EdgeId {
+    // Names a lateral edge of an extruded solid by the profile corner it was swept
+    // from, so the reference holds when the extrusion depth changes. The corner is
+    // positional, so an edit that renumbers the profile's corners moves it too
+    pub solid: String,
+    pub ring: usize,
+    pub vertex: usize,
+}
+impl EdgeId {
+    pub fn new(solid: &str, ring: usize, vertex: usize) -> EdgeId {
+        EdgeId {
+            solid: solid.to_owned(),
+            ring,
+            vertex,
+        }
+    }
+}
+
+impl fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.solid, self.ring, self.vertex)
+    }
+}
+
+// Rounds the side edges of a plain extrusion. Edges of other solids, of bodies
+// that have been joined or cut, and around the caps are rejected
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fillet {
+    pub radius: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ChamferMode {
+    DistanceDistance(f64, f64),
+    // distance along the first edge, then the angle in radians the cut makes with it
+    DistanceAngle(f64, f64),
+}
+
+impl fmt::Display for ChamferMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChamferMode::DistanceDistance(_, _) => write!(f, "DistanceDistance"),
+            ChamferMode::DistanceAngle(_, _) => write!(f, "DistanceAngle"),
+        }
+    }
+}
+
+// Bevels the side edges of a plain extrusion, with the same reach as a fillet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Chamfer {
+    pub mode: ChamferMode,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum ShellDirection {
+    // the walls are carved out of the solid, keeping its outside
+    Inward,
+    // the walls are grown around the solid, keeping its inside as the cavity
+    Outward,
+}
+
+impl fmt::Display for ShellDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellDirection::Inward => write!(f, "Inward"),
+            ShellDirection::Outward => write!(f, "Outward"),
+        }
+    }
+}
+
+// Hollows a plain extrusion, opening only its end caps
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Shell {
+    pub thickness: f64,
+    pub direction: ShellDirection,
+}
+
+impl Shell {
+    pub fn new(thickness: f64, direction: ShellDirection) -> Self {
+        Shell {
+            thickness,
+            direction,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PatternDirection {
+    pub direction: Vector,
+    // the number of instances along this direction, counting the original
+    pub count: usize,
+    pub spacing: f64,
+}
+impl PatternDirection {
+    pub fn new(direction: Vector, count: usize, spacing: f64) -> PatternDirection {
+        PatternDirection {
+            direction,
+            count,
+            spacing,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinearPattern {
+    // one direction for a row, two for a grid
+    pub directions: Vec<PatternDirection>,
+    pub operation: Operation,
+    // the body Add and Remove patterns join their instances to or cut them from
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+impl LinearPattern {
+    pub fn new(directions: Vec<PatternDirection>, operation: Operation) -> LinearPattern {
+        LinearPattern {
+            directions,
+            operation,
+            target: None,
+        }
+    }
+
+    pub fn transforms(&self) -> Vec<Transform> {
+        // every cell of the grid except the one the original already occupies
+        let mut offsets = vec![Vector::new(0.0, 0.0, 0.0)];
+        for d in self.directions.iter() {
+            let step = d.direction.normalize().scale(d.spacing);
+            offsets = offsets
+                .iter()
+                .flat_map(|o| (0..d.count.max(1)).map(move |k| o.add(step.scale(k as f64))))
+                .collect();
+        }
+        offsets
+            .into_iter()
+            .skip(1)
+            .map(Transform::Translate)
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CircularPattern {
+    pub origin: Point,
+    pub axis: Vector,
+    // the number of instances around the axis, counting the original
+    pub count: usize,
+    // the angle between neighbouring instances, in radians
+    pub spacing: f64,
+    pub operation: Operation,
+    // the body Add and Remove patterns join their instances to or cut them from
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+impl CircularPattern {
+    pub fn new(
+        origin: Point,
+        axis: Vector,
+        count: usize,
+        spacing: f64,
+        operation: Operation,
+    ) -> CircularPattern {
+        CircularPattern {
+            origin,
+            axis,
+            count,
+            spacing,
+            operation,
+            target: None,
+        }
+    }
+
+    pub fn full_circle(origin: Point, axis: Vector, count: usize, operation: Operation) -> Self {
+        let spacing = 2.0 * PI / count.max(1) as f64;
+        CircularPattern::new(origin, axis, count, spacing, operation)
+    }
+
+    pub fn transforms(&self) -> Vec<Transform> {
+        (1..self.count)
+            .map(|k| Transform::Rotate {
+                origin: self.origin,
+                axis: self.axis,
+                angle: self.spacing * k as f64,
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum PlaneRef {
+    // a datum plane added with add_plane
+    Plane(String),
+    // a planar face of an extruded solid
+    Face { solid: String, face: PrismFace },
+}
+
+impl PlaneRef {
+    fn reference(&self) -> &str {
+        match self {
+            PlaneRef::Plane(plane) => plane,
+            PlaneRef::Face { solid, .. } => solid,
+        }
+    }
+
+    fn reference_mut(&mut self) -> &mut String {
+        match self {
+            PlaneRef::Plane(plane) => plane,
+            PlaneRef::Face { solid, .. } => solid,
+        }
+    }
+}
+
+impl fmt::Display for PlaneRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaneRef::Plane(plane) => write!(f, "{}", plane),
+            PlaneRef::Face { solid, face } => write!(f, "{}:{:?}", solid, face),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mirror {
+    pub plane: PlaneRef,
+    // join each mirror image to its original instead of adding a new solid
+    pub merge: bool,
+}
+
+impl Mirror {
+    pub fn new(plane: PlaneRef, merge: bool) -> Mirror {
+        Mirror { plane, merge }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Motion {
+    Translate {
+        direction: Vector,
+        distance: f64,
+    },
+    // radians, counterclockwise looking back down the axis
+    Rotate {
+        origin: Point,
+        axis: Vector,
+        angle: f64,
+    },
+    // by the offset between two named points, so moving either one moves the bodies
+    PointToPoint {
+        from: String,
+        to: String,
+    },
+}
+
+impl Motion {
+    pub fn to_transform(&self, points: &HashMap<String, Point>) -> Result<Transform, String> {
+        match self {
+            Motion::Translate {
+                direction,
+                distance,
+            } => Ok(Transform::Translate(direction.normalize().scale(*distance))),
+            Motion::Rotate {
+                origin,
+                axis,
+                angle,
+            } => Ok(Transform::Rotate {
+                origin: *origin,
+                axis: *axis,
+                angle: *angle,
+            }),
+            Motion::PointToPoint { from, to } => {
+                let find = |name: &String| points.get(name).ok_or(format!("No point {}", name));
+                let (from, to) = (find(from)?, find(to)?);
+                Ok(Transform::Translate(
+                    to.to_vector().subtract(from.to_vector()),
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Motion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Motion::Translate { .. } => write!(f, "Translate"),
+            Motion::Rotate { .. } => write!(f, "Rotate"),
+            Motion::PointToPoint { .. } => write!(f, "Point to Point"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BodyTransform {
+    pub motion: Motion,
+    // leave the originals where they are and add moved copies
+    pub copy: bool,
+}
+
+impl BodyTransform {
+    pub fn new(motion: Motion, copy: bool) -> BodyTransform {
+        BodyTransform { motion, copy }
+    }
+}
+
+// Tapers the side faces of a plain extrusion about a neutral plane square to it.
+// End caps, other solids and bodies that have been joined or cut are rejected
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Draft {
+    // radians; positive angles take material away moving up the extrusion from
+    // the neutral plane, which must lie square to it
+    pub angle: f64,
+    pub neutral: PlaneRef,
+}
+
+impl Draft {
+    pub fn new(angle: f64, neutral: PlaneRef) -> Draft {
+        Draft { angle, neutral }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BaseFlange {
+    pub thickness: f64,
+    // inside radius used by every bend in the part
+    pub bend_radius: f64,
+    pub k_factor: f64,
+}
+
+impl BaseFlange {
+    pub fn new(thickness: f64, bend_radius: f64) -> BaseFlange {
+        BaseFlange {
+            thickness,
+            bend_radius,
+            k_factor: DEFAULT_K_FACTOR,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EdgeFlange {
+    // the straight run past the bend
+    pub length: f64,
+    // radians up from the base, toward the side the sheet was thickened
+    pub angle: f64,
+}
+
+impl EdgeFlange {
+    pub fn new(length: f64, angle: f64) -> EdgeFlange {
+        EdgeFlange { length, angle }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Rib {
+    // split evenly either side of the sketch plane
+    pub thickness: f64,
+    pub operation: Operation,
+}
+
+impl Rib {
+    pub fn new(thickness: f64, operation: Operation) -> Rib {
+        Rib {
+            thickness,
+            operation,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum HoleKind {
+    Simple,
+    Counterbore,
+    Countersink,
+    Tapped,
+}
+
+impl fmt::Display for HoleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoleKind::Simple => write!(f, "Simple"),
+            HoleKind::Counterbore => write!(f, "Counterbore"),
+            HoleKind::Countersink => write!(f, "Countersink"),
+            HoleKind::Tapped => write!(f, "Tapped"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum HoleDepth {
+    Blind(f64),
+    ThroughAll,
+}
+
+impl fmt::Display for HoleDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoleDepth::Blind(_) => write!(f, "Blind"),
+            HoleDepth::ThroughAll => write!(f, "ThroughAll"),
+        }
+    }
+}
+
+// Drilled straight into an end cap of a plain extrusion
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hole {
+    pub kind: HoleKind,
+    pub standard: ThreadStandard,
+    // a size from the hole table, like M6 or 1/4
+    pub size: String,
+    pub depth: HoleDepth,
+}
+
+impl Hole {
+    pub fn new(kind: HoleKind, standard: ThreadStandard, size: &str, depth: HoleDepth) -> Hole {
+        Hole {
+            kind,
+            standard,
+            size: size.to_owned(),
+            depth,
+        }
+    }
+
+    pub fn cut(
+        &self,
+        entry: Point,
+        axis: Vector,
+        thickness: f64,
+    ) -> Result<(HoleCut, Option<Thread>), String> {
+        let size = find_size(self.standard, &self.size)
+            .ok_or(format!("No {} hole size {}", self.standard, self.size))?;
+        let shape = match self.kind {
+            HoleKind::Simple | HoleKind::Tapped => HoleShape::Simple,
+            HoleKind::Counterbore => HoleShape::Counterbore {
+                diameter: size.counterbore_diameter,
+                depth: size.counterbore_depth,
+            },
+            HoleKind::Countersink => HoleShape::Countersink {
+                diameter: size.countersink_diameter,
+                angle: size.countersink_angle,
+            },
+        };
+        let (diameter, depth) = match (self.kind, self.depth) {
+            (HoleKind::Tapped, HoleDepth::Blind(depth)) => (size.tap_drill, Some(depth)),
+            (HoleKind::Tapped, HoleDepth::ThroughAll) => (size.tap_drill, None),
+            (_, HoleDepth::Blind(depth)) => (size.clearance, Some(depth)),
+            (_, HoleDepth::ThroughAll) => (size.clearance, None),
+        };
+        let cut = HoleCut {
+            entry,
+            axis,
+            diameter,
+            depth,
+            shape,
+        };
+
+        let thread = match self.kind {
+            HoleKind::Tapped => Some(Thread {
+                designation: size.designation(),
+                major_diameter: size.major_diameter,
+                pitch: size.pitch,
+                length: depth.unwrap_or(thickness),
+                internal: true,
+                origin: entry,
+                axis,
+            }),
+            _ => None,
+        };
+        Ok((cut, thread))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CosmeticThread {
+    pub standard: ThreadStandard,
+    pub size: String,
+    // the whole face when not given
+    pub length: Option<f64>,
+}
+
+impl CosmeticThread {
+    pub fn new(standard: ThreadStandard, size: &str, length: Option<f64>) -> CosmeticThread {
+        CosmeticThread {
+            standard,
+            size: size.to_owned(),
+            length,
+        }
+    }
+
+    pub fn fit(&self, cylinder: &Cylinder) -> Result<Thread, String> {
+        // A bore should be about the tap drill and a shaft about the major
+        // diameter, so either must land between the two
+        let size = find_size(self.standard, &self.size)
+            .ok_or(format!("No {} thread size {}", self.standard, self.size))?;
+        let diameter = cylinder.radius * 2.0;
+        if diameter < size.tap_drill - 1e-6 || diameter > size.major_diameter + 1e-6 {
+            return Err(format!(
+                "A {} thread does not fit a face {} across",
+                size.designation(),
+                diameter
+            ));
+        }
+        let length = self.length.unwrap_or(cylinder.length);
+        if length <= 0.0 || length > cylinder.length + 1e-9 {
+            return Err(format!(
+                "Thread length {} does not fit a face {} long",
+                length, cylinder.length
+            ));
+        }
+        Ok(Thread {
+            designation: size.designation(),
+            major_diameter: size.major_diameter,
+            pitch: size.pitch,
+            length,
+            internal: cylinder.internal,
+            origin: cylinder.origin,
+            axis: cylinder.axis,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModelledThread {
+    pub standard: ThreadStandard,
+    pub size: String,
+    // the whole face when not given
+    pub length: Option<f64>,
+    // Add builds the ridges up from a face at the minor diameter, Remove cuts
+    // the grooves into one at the major diameter. A bore is the other way round.
+    pub operation: Operation,
+}
+
+impl ModelledThread {
+    pub fn new(
+        standard: ThreadStandard,
+        size: &str,
+        length: Option<f64>,
+        operation: Operation,
+    ) -> ModelledThread {
+        ModelledThread {
+            standard,
+            size: size.to_owned(),
+            length,
+            operation,
+        }
+    }
+
+    pub fn model(&self, cylinder: &Cylinder) -> Result<Solid, String> {
+        let thread = CosmeticThread::new(self.standard, &self.size, self.length).fit(cylinder)?;
+        thread.model(!matches!(self.operation, Operation::Remove))
+    }
+}
+
+// A step that could not be built, or only partly. The view carries on past it
+// with whatever it did make.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub step: String,
+    pub error: Error,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.step, self.error)
+    }
+}
+
+#[derive(Debug)]
+pub struct WorkbenchView {
+    pub points: HashMap<String, Point>,
     pub planes: HashMap<String, Plane>,
+    pub helices: HashMap<String, Helix>,
     pub sketches: HashMap<String, SketchView>,
     pub solids: HashMap<String, Solid>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl WorkbenchView {
+    pub fn new() -> WorkbenchView {
+        WorkbenchView {
+            points: HashMap::new(),
+            planes: HashMap::new(),
+            helices: HashMap::new(),
+            sketches: HashMap::new(),
+            solids: HashMap::new(),
+            diagnostics: vec![],
+        }
+    }
+
+    pub fn find_plane(&self, name: &str) -> Option<&Plane> {
+        self.planes.get(name)
+    }
+
+    pub fn flat_pattern(&self, solid: &str) -> Result<FlatPattern, Error> {
+        lookup(&self.solids, "solid", solid)?
+            .sheet
+            .as_ref()
+            .ok_or(Error::InvalidParameter(format!(
+                "{} is not a sheet metal part",
+                solid
+            )))?
+            .unfold()
+            .map_err(Error::GeometryFailure)
+    }
+
+    fn pattern(
+        &mut self,
+        name: &str,
+        features: &[String],
+        transforms: Vec<Transform>,
+        operation: &Operation,
+        target: Option<&str>,
+        errors: &mut Vec<Error>,
+    ) -> Result<(), Error> {
+        // Each instance is made the same way: as a body of its own, or joined
+        // to or cut from the target
+        let seeds = self
+            .feature_solids(features)
+            .iter()
+            .map(|n| lookup(&self.solids, "solid", n).cloned())
+            .collect::<Result<Vec<Solid>, Error>>()?;
+        let instances = transforms
+            .iter()
+            .flat_map(|t| seeds.iter().map(move |seed| seed.transformed(t)))
+            .collect();
+        self.combine_into(name, instances, operation, target, errors)
+    }
+
+    fn combine_into(
+        &mut self,
+        name: &str,
+        solids: Vec<Solid>,
+        operation: &Operation,
+        target: Option<&str>,
+        errors: &mut Vec<Error>,
+    ) -> Result<(), Error> {
+        // New solids are bodies of their own, named for the step. Add and
+        // Remove join each one to the target or cut it away.
+        let add = match operation {
+            Operation::New => {
+                for (count, solid) in solids.into_iter().enumerate() {
+                    self.solids.insert(format!("{}_{}", name, count), solid);
+                }
+                return Ok(());
+            }
+            Operation::Add => true,
+            Operation::Remove => false,
+        };
+        let target = target.ok_or(Error::InvalidParameter(format!(
+            "{} needs a target body to {}",
+            name,
+            operation.to_string().to_lowercase()
+        )))?;
+        let body = lookup_mut(&mut self.solids, "solid", target)?;
+        for solid in solids.iter() {
+            if let Err(e) = body.combine(solid, add) {
+                errors.push(Error::GeometryFailure(e));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn errors(&self, step: &str) -> Vec<&Error> {
+        // what went wrong with the named step, if anything
+        self.diagnostics
+            .iter()
+            .filter(|d| d.step == step)
+            .map(|d| &d.error)
+            .collect()
+    }
+
+    pub fn resolve_plane(&self, plane: &PlaneRef) -> Option<(Point, Vector)> {
+        match plane {
+            PlaneRef::Plane(plane) => self.planes.get(plane).map(|p| (p.origin, p.normal)),
+            PlaneRef::Face { solid, face } => self
+                .solids
+                .get(solid)
+                .and_then(|s| s.prism.as_ref())
+                .and_then(|p| p.face_plane(*face)),
+        }
+    }
+
+    fn keys(&self) -> Vec<ViewKey> {
+        let points = self.points.keys().map(|n| ViewKey::Point(n.to_owned()));
+        let planes = self.planes.keys().map(|n| ViewKey::Plane(n.to_owned()));
+        let helices = self.helices.keys().map(|n| ViewKey::Helix(n.to_owned()));
+        let sketches = self.sketches.keys().map(|n| ViewKey::Sketch(n.to_owned()));
+        let solids = self.solids.keys().map(|n| ViewKey::Solid(n.to_owned()));
+        points
+            .chain(planes)
+            .chain(helices)
+            .chain(sketches)
+            .chain(solids)
+            .collect()
+    }
+
+    fn keys_read_by(&self, step: &Step) -> Vec<ViewKey> {
+        // what the step's references name, or the whole view for a step that
+        // keeps only the bodies it names
+        let everything = matches!(step, Step::DeleteBody { keep: true, .. });
+        let references = step.references();
+        let mut keys: Vec<ViewKey> = self
+            .keys()
+            .into_iter()
+            .filter(|k| everything || references.iter().any(|r| made_by(k.name(), r)))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn entry(&self, key: &ViewKey) -> Option<ViewEntry> {
+        match key {
+            ViewKey::Point(name) => self.points.get(name).cloned().map(ViewEntry::Point),
+            ViewKey::Plane(name) => self.planes.get(name).cloned().map(ViewEntry::Plane),
+            ViewKey::Helix(name) => self.helices.get(name).cloned().map(ViewEntry::Helix),
+            ViewKey::Sketch(name) => self.sketches.get(name).cloned().map(ViewEntry::Sketch),
+            ViewKey::Solid(name) => self
+                .solids
+                .get(name)
+                .map(|solid| ViewEntry::Solid(Box::new(solid.clone()))),
+        }
+    }
+
+    fn changes_since(
+        &self,
+        before: &[ViewKey],
+        reads: &[ViewKey],
+    ) -> Vec<(ViewKey, Option<ViewEntry>)> {
+        // the entries a step added or removed, and those it read, which it may
+        // have changed in place
+        let before: HashSet<&ViewKey> = before.iter().collect();
+        let after = self.keys();
+        let mut touched: Vec<ViewKey> = after
+            .iter()
+            .filter(|k| !before.contains(k))
+            .cloned()
+            .collect();
+        let after: HashSet<&ViewKey> = after.iter().collect();
+        touched.extend(
+            before
+                .iter()
+                .filter(|k| !after.contains(*k))
+                .cloned()
+                .cloned(),
+        );
+        touched.extend(reads.iter().cloned());
+        touched.sort();
+        touched.dedup();
+        touched
+            .into_iter()
+            .map(|key| {
+                let entry = self.entry(&key);
+                (key, entry)
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, changes: &[(ViewKey, Option<ViewEntry>)]) {
+        for (key, entry) in changes.iter() {
+            let name = key.name().to_owned();
+            match (key, entry.clone()) {
+                (ViewKey::Point(_), Some(ViewEntry::Point(point))) => {
+                    self.points.insert(name, point);
+                }
+                (ViewKey::Plane(_), Some(ViewEntry::Plane(plane))) => {
+                    self.planes.insert(name, plane);
+                }
+                (ViewKey::Helix(_), Some(ViewEntry::Helix(helix))) => {
+                    self.helices.insert(name, helix);
+                }
+                (ViewKey::Sketch(_), Some(ViewEntry::Sketch(sketch))) => {
+                    self.sketches.insert(name, sketch);
+                }
+                (ViewKey::Solid(_), Some(ViewEntry::Solid(solid))) => {
+                    self.solids.insert(name, *solid);
+                }
+                (ViewKey::Point(_), _) => {
+                    self.points.remove(&name);
+                }
+                (ViewKey::Plane(_), _) => {
+                    self.planes.remove(&name);
+                }
+                (ViewKey::Helix(_), _) => {
+                    self.helices.remove(&name);
+                }
+                (ViewKey::Sketch(_), _) => {
+                    self.sketches.remove(&name);
+                }
+                (ViewKey::Solid(_), _) => {
+                    self.solids.remove(&name);
+                }
+            }
+        }
+    }
+
+    pub fn feature_solids(&self, features: &[String]) -> Vec<String> {
+        // a feature is either a solid name or a step name standing for all of
+        // that step's solids, which are named step_0, step_1, ...
+        let mut names: Vec<String> = self
+            .solids
+            .keys()
+            .filter(|solid_name| {
+                features.iter().any(|f| {
+                    *solid_name == f
+                        || solid_name
+                            .strip_prefix(f.as_str())
+                            .and_then(|rest| rest.strip_prefix('_'))
+                            .is_some_and(|index| index.parse::<usize>().is_ok())
+                })
+            })
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
 }
 
-impl WorkbenchView {
-    pub fn new() -> WorkbenchView {
-        WorkbenchView {
-            points: HashMap::new(),
-            planes: HashMap::new(),
-            sketches: HashMap::new(),
-            solids: HashMap::new(),
+#[cfg(test)]
+mod tests {
+    use crate::sketch;
+
+    use super::*;
+    use truck_modeling::ShellCondition;
+
+    #[test]
+    fn test_add_sketch_and_extrusion() {
+        let mut wb = Workbench::new("wb");
+        wb.add_sketch_and_extrusion();
+        let wbv = wb.create_view(100);
+        let solid0 = wbv.solids.get("Extrude 1_0").unwrap();
+        let as_mesh = solid0.get_mesh();
+
+        let solid1 = wbv.solids.get("Extrude 1_1").unwrap();
+        let as_mesh = solid1.get_mesh();
+    }
+
+    #[test]
+    fn test_actual_dummy_steps() {
+        // let mut wb = Workbench::new("wb");
+        let width = 100.0;
+        let depth = 75.0;
+        let height = 20.0;
+        let sep = 20.0;
+        let a = Point2D::new(-width / 2.0, -depth / 2.0, "A");
+        let b = Point2D::new(-width / 2.0, depth / 2.0, "B");
+        let c = Point2D::new(width / 2.0, depth / 2.0, "C");
+        let d = Point2D::new(width / 2.0, -depth / 2.0, "D");
+
+        let e = Point2D::new(-width / 2.0 + width + sep, -depth / 2.0, "E");
+        let f = Point2D::new(-width / 2.0 + width + sep, depth / 2.0, "F");
+        let g = Point2D::new(width / 2.0 + width + sep, depth / 2.0, "G");
+        let h = Point2D::new(width / 2.0 + width + sep, -depth / 2.0, "H");
+
+        let i = Point2D::new(-width / 4.0, -depth / 4.0, "I");
+        let j = Point2D::new(-width / 4.0, depth / 4.0, "J");
+        let k = Point2D::new(width / 4.0, depth / 4.0, "K");
+        let l = Point2D::new(width / 4.0, -depth / 4.0, "L");
+
+        let segments = Segment::link(vec![a, b, c, d], true);
+        let mut sketch1 = Sketch::new();
+        sketch1.add_segments(segments);
+
+        let segments2 = Segment::link(vec![e, f, g, h], true);
+        sketch1.add_segments(segments2);
+
+        let segments_hole = Segment::link(vec![i, j, k, l], true);
+        sketch1.add_segments(segments_hole);
+        // wb.add_sketch("Sketch 1", sketch1, "Top");
+
+        let faces = sketch1.find_faces(false);
+        assert_eq!(faces.len(), 3);
+    }
+
+    #[test]
+    fn test_trangular_prism() {
+        let mut wb = Workbench::new("wb");
+
+        let a = Point2D::new(-1.0, 0.0, "A");
+        let b = Point2D::new(1.0, 0.0, "B");
+        let c = Point2D::new(0.0, 1.0, "C");
+        let segments = Segment::link(vec![a, b, c], true);
+        let mut sketch1 = Sketch::new();
+        sketch1.add_segments(segments);
+        wb.add_sketch("sketch1", sketch1, "Front");
+
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let as_mesh = solid.get_mesh();
+
+        solid.save_as_obj("test0.obj");
+    }
+
+    #[test]
+    fn test_square_with_hole() {
+        let mut wb = Workbench::new("wb");
+
+        let a = Point2D::new(-100.0, -100.0, "A");
+        let b = Point2D::new(100.0, -100.0, "B");
+        let c = Point2D::new(100.0, 100.0, "C");
+        let d = Point2D::new(-100.0, 100.0, "D");
+        let mut segments_0 = Segment::link(vec![a, b, c, d], true);
+
+        let e = Point2D::new(-200.0, -200.0, "E");
+        let f = Point2D::new(200.0, -200.0, "F");
+        let g = Point2D::new(200.0, 200.0, "G");
+        let h = Point2D::new(-200.0, 200.0, "H");
+        let segments_1 = Segment::link(vec![e, f, g, h], true);
+
+        segments_0.extend(segments_1);
+
+        let mut sketch1 = Sketch::new();
+        sketch1.add_segments(segments_0);
+
+        wb.add_sketch("sketch1", sketch1, "Front");
+        wb.add_extrusion("ext1", "sketch1", 200.0, vec![1], Operation::New);
+
+        let wbv = wb.create_view(100);
+
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let as_mesh = solid.get_mesh();
+
+        solid.save_as_obj("test1.obj");
+        solid.save_as_step("test1.step");
+    }
+
+    fn add_tube_sketches(wb: &mut Workbench) {
+        // a small square profile on the Right plane, centered on the origin
+        let a = Point2D::new(-1.0, -1.0, "A");
+        let b = Point2D::new(1.0, -1.0, "B");
+        let c = Point2D::new(1.0, 1.0, "C");
+        let d = Point2D::new(-1.0, 1.0, "D");
+        let mut profile = Sketch::new();
+        profile.add_segments(Segment::link(vec![a, b, c, d], true));
+        wb.add_sketch("profile", profile, "Right");
+
+        // a path on the Top plane: out along X, around a bend, then up along Y
+        let p0 = Point2D::new(0.0, 0.0, "P0");
+        let p1 = Point2D::new(10.0, 0.0, "P1");
+        let p2 = Point2D::new(20.0, 10.0, "P2");
+        let p3 = Point2D::new(20.0, 30.0, "P3");
+        let transit = Point2D::new(
+            10.0 + 10.0 * (PI / 4.0).sin(),
+            10.0 - 10.0 * (PI / 4.0).cos(),
+            "T",
+        );
+        let mut path = Sketch::new();
+        path.add_segments(vec![
+            Segment::Line(Line::new(p0, p1.clone())),
+            Segment::Arc(crate::sketch::Arc::new(p1, p2.clone(), transit)),
+            Segment::Line(Line::new(p3, p2)),
+        ]);
+        wb.add_sketch("path", path, "Top");
+    }
+
+    #[test]
+    fn test_sweep_follow_path() {
+        let mut wb = Workbench::new("wb");
+        add_tube_sketches(&mut wb);
+        wb.add_sweep(
+            "sweep1",
+            "profile",
+            vec![0],
+            "path",
+            vec![0, 1, 2],
+            Sweep::new(SweepOrientation::FollowPath, Operation::New),
+        );
+
+        let wbv = wb.create_view(100);
+        assert!(wbv.diagnostics.is_empty());
+        assert_eq!(wbv.solids.len(), 1);
+        let tube = wbv.solids.get("sweep1_0").unwrap();
+        assert!(!tube.get_step_text().is_empty());
+
+        // the 2x2 square is carried round the bend, so the tube holds its
+        // area along the whole length of the path, less a little where the
+        // bend is taken in short steps
+        let length = 10.0 + 5.0 * PI + 20.0;
+        assert!((signed_volume(tube) - 4.0 * length).abs() / (4.0 * length) < 1e-3);
+
+        // and it ends at the top of the path
+        let max_y = tube
+            .get_mesh()
+            .vertices
+            .iter()
+            .map(|v| v.y)
+            .fold(f64::MIN, f64::max);
+        assert!((max_y - 30.0).abs() < 1e-6);
+
+        // Add and Remove sweeps join to or cut from a target, here a block
+        // the first 3 of the tube's length runs through the top half of
+        add_square(&mut wb, "block", "Top", 3.0);
+        wb.add_extrusion("block", "block", 2.0, vec![0], Operation::New);
+        let mut sweeps = vec![];
+        for (name, operation, target) in [
+            ("join", Operation::Add, Some("block_0")),
+            ("cut", Operation::Remove, Some("block_0")),
+            ("loose", Operation::Add, None),
+        ] {
+            let mut sweep = Sweep::new(SweepOrientation::FollowPath, operation);
+            sweep.target = target.map(str::to_owned);
+            let mut wb = wb.clone();
+            wb.add_sweep(name, "profile", vec![0], "path", vec![0, 1, 2], sweep);
+            sweeps.push(wb.create_view(100));
+        }
+        let [join, cut, loose] = &sweeps[..] else {
+            unreachable!()
+        };
+        assert!(join.diagnostics.is_empty() && cut.diagnostics.is_empty());
+        assert!(!join.solids.contains_key("join_0"));
+        let joined = signed_volume(&join.solids["block_0"]);
+        assert!((joined - (72.0 + signed_volume(tube) - 6.0)).abs() < 1e-6);
+        assert!((signed_volume(&cut.solids["block_0"]) - 66.0).abs() < 1e-6);
+        assert!(matches!(
+            loose.errors("loose")[..],
+            [Error::InvalidParameter(_)]
+        ));
+        wb.add_sweep(
+            "join",
+            "profile",
+            vec![0],
+            "path",
+            vec![0, 1, 2],
+            Sweep {
+                target: Some("block_0".to_owned()),
+                ..Sweep::new(SweepOrientation::FollowPath, Operation::Add)
+            },
+        );
+        assert_eq!(wb.step_dependents("block"), vec!["join"]);
+    }
+
+    #[test]
+    fn test_sweep_mitres_corners() {
+        // a square ring swept round a sharp corner makes one hollow elbow
+        let mut wb = Workbench::new("wb");
+        let mut profile = Sketch::new();
+        for (half, tag) in [(2.0, "O"), (1.0, "I")] {
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .iter()
+                .enumerate()
+                .map(|(i, (x, y))| Point2D::new(x * half, y * half, &format!("{}{}", tag, i)))
+                .collect();
+            profile.add_segments(Segment::link(corners, true));
+        }
+        wb.add_sketch("profile", profile, "Right");
+        let p0 = Point2D::new(0.0, 0.0, "P0");
+        let p1 = Point2D::new(10.0, 0.0, "P1");
+        let p2 = Point2D::new(10.0, 10.0, "P2");
+        let mut path = Sketch::new();
+        path.add_segments(Segment::link(vec![p0, p1, p2], false));
+        wb.add_sketch("path", path, "Top");
+        // face 0 is the square in the middle, face 1 the ring around it
+        wb.add_sweep(
+            "elbow",
+            "profile",
+            vec![1],
+            "path",
+            vec![0, 1],
+            Sweep::new(SweepOrientation::FollowPath, Operation::New),
+        );
+
+        let wbv = wb.create_view(100);
+        assert!(wbv.diagnostics.is_empty());
+        assert_eq!(wbv.solids.len(), 1);
+        // each leg, measured along its middle, holds the ring's area
+        let elbow = wbv.solids.get("elbow_0").unwrap();
+        assert!((signed_volume(elbow) - 20.0 * 12.0).abs() < 1e-6);
+    }
+
+    fn add_square(wb: &mut Workbench, name: &str, plane: &str, half: f64) {
+        let a = Point2D::new(-half, -half, "A");
+        let b = Point2D::new(half, -half, "B");
+        let c = Point2D::new(half, half, "C");
+        let d = Point2D::new(-half, half, "D");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(vec![a, b, c, d], true));
+        wb.add_sketch(name, sketch, plane);
+    }
+
+    #[test]
+    fn test_loft_square_to_triangle() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "bottom", "Top", 10.0);
+
+        let raised = Plane::new(
+            Point::new(0.0, 0.0, 30.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Raised", raised);
+        let a = Point2D::new(-5.0, -5.0, "A");
+        let b = Point2D::new(5.0, -5.0, "B");
+        let c = Point2D::new(0.0, 5.0, "C");
+        let mut top = Sketch::new();
+        top.add_segments(Segment::link(vec![a, b, c], true));
+        wb.add_sketch("top", top, "Raised");
+
+        wb.add_loft(
+            "loft1",
+            vec![Profile::new("bottom", 0), Profile::new("top", 0)],
+            vec![],
+            Operation::New,
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("loft1_0").unwrap();
+        let mesh = solid.get_mesh();
+        let max_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MIN, f64::max);
+        let min_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MAX, f64::min);
+        assert!((max_z - 30.0).abs() < 1e-6);
+        assert!(min_z.abs() < 1e-6);
+        assert!(!solid.get_step_text().is_empty());
+
+        // the caps face outward
+        for (v, n) in mesh.vertices.iter().zip(mesh.normals.iter()) {
+            if n.z.abs() > 0.999 {
+                assert_eq!(v.z > 15.0, n.z > 0.0);
+            }
+        }
+
+        // an Add or Remove loft needs a body to join onto or cut from
+        wb.add_loft(
+            "loft2",
+            vec![Profile::new("bottom", 0), Profile::new("top", 0)],
+            vec![],
+            Operation::Remove,
+        );
+        // and a frame would be lofted into a plug, so it is turned away
+        let mut frame = Sketch::new();
+        for (half, tag) in [(10.0, "O"), (5.0, "I")] {
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .iter()
+                .enumerate()
+                .map(|(i, (x, y))| Point2D::new(x * half, y * half, &format!("{}{}", tag, i)))
+                .collect();
+            frame.add_segments(Segment::link(corners, true));
+        }
+        wb.add_sketch("frame", frame, "Top");
+        wb.add_loft(
+            "loft3",
+            vec![Profile::new("frame", 1), Profile::new("top", 0)],
+            vec![],
+            Operation::New,
+        );
+
+        // as are lofts with nothing to run between
+        wb.add_loft("loft4", vec![], vec![], Operation::New);
+        wb.add_loft(
+            "loft5",
+            vec![Profile::new("bottom", 0)],
+            vec![],
+            Operation::New,
+        );
+
+        let wbv = wb.create_view(100);
+        for step in ["loft2", "loft3", "loft4", "loft5"] {
+            assert!(matches!(wbv.errors(step)[..], [Error::InvalidParameter(_)]));
+            assert!(!wbv.solids.contains_key(&format!("{}_0", step)));
+        }
+        assert!(match_sections(&[]).is_err());
+
+        // given one, it joins to or cuts from a slab the loft passes through
+        let mid = Plane::new(
+            Point::new(0.0, 0.0, 10.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Mid", mid);
+        add_square(&mut wb, "slab", "Mid", 20.0);
+        wb.add_extrusion("slab", "slab", 10.0, vec![0], Operation::New);
+        let slab = 40.0 * 40.0 * 10.0;
+        for (name, operation) in [("join", Operation::Add), ("cut", Operation::Remove)] {
+            let mut wb = wb.clone();
+            wb.add_loft(
+                name,
+                vec![Profile::new("bottom", 0), Profile::new("top", 0)],
+                vec![],
+                operation,
+            );
+            if let Some(Step::Loft { loft, .. }) = wb.steps.last_mut() {
+                loft.target = Some("slab_0".to_owned());
+            }
+            assert_eq!(wb.step_dependents("slab"), vec![name]);
+            let wbv = wb.create_view(100);
+            assert!(wbv.errors(name).is_empty());
+            assert!(!wbv.solids.contains_key(&format!("{}_0", name)));
+            let volume = signed_volume(&wbv.solids["slab_0"]);
+            match name {
+                "join" => assert!(volume > slab + 1.0),
+                _ => assert!(volume < slab - 1.0 && volume > 0.0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_loft_with_guide() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "bottom", "Top", 10.0);
+        let raised = Plane::new(
+            Point::new(0.0, 0.0, 30.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Raised", raised);
+        add_square(&mut wb, "top", "Raised", 10.0);
+
+        // a guide on the Front plane that bows out to x = 20 halfway up
+        let g0 = Point2D::new(0.0, 0.0, "G0");
+        let g1 = Point2D::new(0.0, 30.0, "G1");
+        let transit = Point2D::new(20.0, 15.0, "T");
+        let mut guide = Sketch::new();
+        guide.add_segments(vec![Segment::Arc(crate::sketch::Arc::new(g0, g1, transit))]);
+        wb.add_sketch("guide", guide, "Front");
+
+        wb.add_loft(
+            "loft1",
+            vec![Profile::new("bottom", 0), Profile::new("top", 0)],
+            vec![Guide::new("guide", vec![0])],
+            Operation::New,
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("loft1_0").unwrap();
+        let max_x = solid
+            .get_mesh()
+            .vertices
+            .iter()
+            .map(|v| v.x)
+            .fold(f64::MIN, f64::max);
+        assert!(max_x > 25.0);
+    }
+
+    #[test]
+    fn test_fillet_box_edges() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb.add_fillet(
+            "fillet1",
+            vec![EdgeId::new("ext1_0", 0, 0), EdgeId::new("ext1_0", 0, 2)],
+            3.0,
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        // 4 sides + 2 caps + 2 rounded edges
+        let faces: usize = solid
+            .truck_solid
+            .boundaries()
+            .iter()
+            .map(|shell| shell.len())
+            .sum();
+        assert_eq!(faces, 8);
+
+        // every mesh vertex stays inside the original box, and only two of the
+        // four sharp corners are left
+        let mesh = solid.get_mesh();
+        let mut sharp_corners: Vec<(i64, i64)> = vec![];
+        for v in mesh.vertices.iter() {
+            assert!(v.x.abs() <= 10.0 + 1e-6 && v.y.abs() <= 10.0 + 1e-6);
+            if (v.x.abs() - 10.0).abs() < 1e-6 && (v.y.abs() - 10.0).abs() < 1e-6 {
+                let corner = (v.x.signum() as i64, v.y.signum() as i64);
+                if !sharp_corners.contains(&corner) {
+                    sharp_corners.push(corner);
+                }
+            }
+        }
+        assert_eq!(sharp_corners.len(), 2);
+        assert!(solid.get_step_text().contains("B_SPLINE"));
+    }
+
+    #[test]
+    fn test_fillet_survives_depth_change() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 2.0);
+        wb.set_step_parameters("ext1", vec!["depth".to_owned()], vec![12.0])
+            .unwrap();
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let max_z = solid
+            .get_mesh()
+            .vertices
+            .iter()
+            .map(|v| v.z)
+            .fold(f64::MIN, f64::max);
+        assert!((max_z - 12.0).abs() < 1e-6);
+        let faces: usize = solid
+            .truck_solid
+            .boundaries()
+            .iter()
+            .map(|shell| shell.len())
+            .sum();
+        assert_eq!(faces, 7);
+    }
+
+    #[test]
+    fn test_fillet_too_large() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb.add_fillet(
+            "fillet1",
+            vec![EdgeId::new("ext1_0", 0, 0), EdgeId::new("ext1_0", 0, 1)],
+            15.0,
+        );
+
+        // the second fillet cannot fit, so only the first one is applied
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let prism = solid.prism.as_ref().unwrap();
+        assert_eq!(prism.corners[0][0], Corner::Fillet(15.0));
+        assert_eq!(prism.corners[0][1], Corner::Sharp);
+    }
+
+    fn add_revolved_block(wb: &mut Workbench) {
+        // a quarter turn of sketch1's square, which has no prism to treat
+        wb.add_revolve(
+            "rev1",
+            "sketch1",
+            vec![0],
+            Revolve::new(
+                Point::new(20.0, 0.0, 0.0),
+                Vector::new(0.0, 1.0, 0.0),
+                PI / 2.0,
+                Operation::New,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_fillet_needs_a_side_edge() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        add_revolved_block(&mut wb);
+        // a square has four side edges, and the revolved block none
+        wb.add_fillet(
+            "fillet1",
+            vec![
+                EdgeId::new("ext1_0", 0, 4),
+                EdgeId::new("ext1_0", 1, 0),
+                EdgeId::new("rev1_0", 0, 0),
+                EdgeId::new("ext1_0", 0, 3),
+            ],
+            2.0,
+        );
+
+        let wbv = wb.create_view(100);
+        assert!(matches!(
+            wbv.errors("fillet1")[..],
+            [
+                Error::InvalidParameter(_),
+                Error::InvalidParameter(_),
+                Error::InvalidParameter(_)
+            ]
+        ));
+        match &wbv.errors("fillet1")[..] {
+            [Error::InvalidParameter(cap), _, Error::InvalidParameter(revolve)] => {
+                assert!(cap.contains("not those around its ends"));
+                assert!(revolve.contains("only the side edges of a plain extrusion"));
+            }
+            other => panic!("unexpected errors {:?}", other),
+        }
+        let prism = wbv.solids.get("ext1_0").unwrap().prism.as_ref().unwrap();
+        assert_eq!(prism.corners[0][3], Corner::Fillet(2.0));
+        assert!(wbv.solids.get("rev1_0").unwrap().prism.is_none());
+    }
+
+    #[test]
+    fn test_fillet_rejects_a_joined_body() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        let mut pattern = LinearPattern::new(
+            vec![PatternDirection::new(Vector::new(1.0, 1.0, 1.0), 2, 3.0)],
+            Operation::Add,
+        );
+        pattern.target = Some("ext1_0".to_owned());
+        wb.add_linear_pattern("pattern1", vec!["ext1".to_owned()], pattern);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 0)], 2.0);
+
+        let wbv = wb.create_view(100);
+        assert!(wbv.solids.get("ext1_0").unwrap().prism.is_none());
+        match &wbv.errors("fillet1")[..] {
+            [Error::InvalidParameter(message)] => {
+                assert!(message.contains("joined, cut or reshaped"))
+            }
+            other => panic!("unexpected errors {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_chamfer_distance_distance() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb.add_chamfer(
+            "chamfer1",
+            vec![EdgeId::new("ext1_0", 0, 0)],
+            ChamferMode::DistanceDistance(2.0, 4.0),
+        );
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 2)], 1.0);
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let shells = solid.truck_solid.boundaries();
+        assert_eq!(shells[0].len(), 8);
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+
+        let prism = solid.prism.as_ref().unwrap();
+        assert_eq!(prism.corners[0][0], Corner::Chamfer(2.0, 4.0));
+        assert_eq!(prism.corners[0][2], Corner::Fillet(1.0));
+        assert!(solid.get_step_text().contains("CLOSED_SHELL"));
+
+        // only the side edges of an extrusion can be chamfered
+        add_revolved_block(&mut wb);
+        wb.add_chamfer(
+            "chamfer2",
+            vec![EdgeId::new("ext1_0", 0, 4), EdgeId::new("rev1_0", 0, 0)],
+            ChamferMode::DistanceDistance(1.0, 1.0),
+        );
+        let wbv = wb.create_view(100);
+        match &wbv.errors("chamfer2")[..] {
+            [Error::InvalidParameter(cap), Error::InvalidParameter(revolve)] => {
+                assert!(cap.contains("can be chamfered, not those around its ends"));
+                assert!(revolve.contains("can't be chamfered"));
+            }
+            other => panic!("unexpected errors {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_chamfer_distance_angle() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb.add_chamfer(
+            "chamfer1",
+            vec![EdgeId::new("ext1_0", 0, 1)],
+            ChamferMode::DistanceAngle(3.0, PI / 4.0),
+        );
+
+        // a 45 degree chamfer on a square corner is the same as an equal chamfer
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let chamfered = solid.get_mesh();
+
+        let mut wb2 = Workbench::new("wb");
+        add_square(&mut wb2, "sketch1", "Top", 10.0);
+        wb2.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        wb2.add_chamfer(
+            "chamfer1",
+            vec![EdgeId::new("ext1_0", 0, 1)],
+            ChamferMode::DistanceDistance(3.0, 3.0),
+        );
+        let wbv2 = wb2.create_view(100);
+        let expected = wbv2.solids.get("ext1_0").unwrap().get_mesh();
+
+        assert_eq!(chamfered.vertices.len(), expected.vertices.len());
+        for (a, b) in chamfered.vertices.iter().zip(expected.vertices.iter()) {
+            assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_sweep_keep_normal() {
+        let mut wb = Workbench::new("wb");
+        add_tube_sketches(&mut wb);
+        wb.add_sweep(
+            "sweep1",
+            "profile",
+            vec![0],
+            "path",
+            vec![0, 1, 2],
+            Sweep::new(SweepOrientation::KeepNormal, Operation::New),
+        );
+
+        let wbv = wb.create_view(100);
+        assert!(wbv.diagnostics.is_empty());
+        assert_eq!(wbv.solids.len(), 1);
+        // the profile isn't turned, so it ends on the top of the path still
+        // facing along X
+        let tube = wbv.solids.get("sweep1_0").unwrap();
+        assert!(signed_volume(tube) > 0.0);
+        let top: Vec<f64> = tube
+            .get_mesh()
+            .vertices
+            .iter()
+            .filter(|v| (v.y - 31.0).abs() < 1e-6)
+            .map(|v| v.x)
+            .collect();
+        assert!(!top.is_empty());
+        assert!(top.iter().all(|x| (x - 20.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_shell_open_top() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_shell(
+            "shell1",
+            "ext1_0",
+            vec![PrismFace::Top],
+            Shell::new(1.0, ShellDirection::Inward),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let shells = solid.truck_solid.boundaries();
+        assert_eq!(shells.len(), 1);
+        // six outer faces, the top one with a hole in it, plus five cavity faces
+        assert_eq!(shells[0].len(), 11);
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+        assert!(solid.prism.is_none());
+
+        // the outside is untouched and the cavity floor sits one wall above the bottom
+        let mesh = solid.get_mesh();
+        let max_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MIN, f64::max);
+        assert!((max_x - 10.0).abs() < 1e-6);
+        assert!(mesh.vertices.iter().any(|v| (v.z - 1.0).abs() < 1e-6));
+        assert!(solid.get_step_text().contains("CLOSED_SHELL"));
+    }
+
+    #[test]
+    fn test_shell_closed_outward() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_shell(
+            "shell1",
+            "ext1_0",
+            vec![],
+            Shell::new(2.0, ShellDirection::Outward),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let shells = solid.truck_solid.boundaries();
+        assert_eq!(shells.len(), 2);
+        assert!(shells.iter().all(|s| s.len() == 6));
+
+        let mesh = solid.get_mesh();
+        let max_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MIN, f64::max);
+        let min_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MAX, f64::min);
+        assert!((max_x - 12.0).abs() < 1e-6);
+        assert!((min_z + 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_shell_too_thick() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 30.0, vec![0], Operation::New);
+        wb.add_shell(
+            "shell1",
+            "ext1_0",
+            vec![PrismFace::Top],
+            Shell::new(12.0, ShellDirection::Inward),
+        );
+
+        // the walls would cross in the middle, so the solid is left as it was
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        assert!(solid.prism.is_some());
+        let prism = solid.prism.as_ref().unwrap();
+        let err = prism.shell(12.0, false, &[PrismFace::Top]).unwrap_err();
+        assert!(err.contains("too large"));
+        let err = prism
+            .shell(1.0, false, &[PrismFace::Side(0, 0)])
+            .unwrap_err();
+        assert!(err.contains("end caps"));
+
+        // a side can't be opened, and a body that isn't an extrusion can't be
+        // shelled at all
+        wb.add_shell(
+            "shell2",
+            "ext1_0",
+            vec![PrismFace::Side(0, 0)],
+            Shell::new(1.0, ShellDirection::Inward),
+        );
+        add_revolved_block(&mut wb);
+        wb.add_shell(
+            "shell3",
+            "rev1_0",
+            vec![],
+            Shell::new(1.0, ShellDirection::Inward),
+        );
+        let wbv = wb.create_view(100);
+        for step in ["shell2", "shell3"] {
+            assert!(matches!(wbv.errors(step)[..], [Error::InvalidParameter(_)]));
+        }
+        assert!(wbv.solids.get("ext1_0").unwrap().prism.is_some());
+    }
+
+    #[test]
+    fn test_linear_pattern_grid() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 2.0);
+        wb.add_extrusion("ext1", "sketch1", 3.0, vec![0], Operation::New);
+        wb.add_linear_pattern(
+            "pattern1",
+            vec!["ext1".to_owned()],
+            LinearPattern::new(
+                vec![
+                    PatternDirection::new(Vector::new(1.0, 0.0, 0.0), 3, 10.0),
+                    PatternDirection::new(Vector::new(0.0, 2.0, 0.0), 2, 6.0),
+                ],
+                Operation::New,
+            ),
+        );
+
+        let wbv = wb.create_view(100);
+        assert_eq!(wbv.solids.len(), 6);
+        let last = wbv.solids.get("pattern1_4").unwrap().get_mesh();
+        let min_x = last.vertices.iter().map(|v| v.x).fold(f64::MAX, f64::min);
+        let min_y = last.vertices.iter().map(|v| v.y).fold(f64::MAX, f64::min);
+        assert!((min_x - 18.0).abs() < 1e-6);
+        assert!((min_y - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_circular_pattern_bolt_circle() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "plate", "Top", 30.0);
+        wb.add_extrusion("plate", "plate", 3.0, vec![0], Operation::New);
+        // the bolt runs right through the plate, so no faces line up
+        let below = Plane::new(
+            Point::new(0.0, 0.0, -1.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Below", below);
+        let a = Point2D::new(19.0, -1.0, "A");
+        let b = Point2D::new(21.0, -1.0, "B");
+        let c = Point2D::new(21.0, 1.0, "C");
+        let d = Point2D::new(19.0, 1.0, "D");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(vec![a, b, c, d], true));
+        wb.add_sketch("sketch1", sketch, "Below");
+        wb.add_extrusion("ext1", "sketch1", 5.0, vec![0], Operation::New);
+        let mut pattern = CircularPattern::full_circle(
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            6,
+            Operation::Remove,
+        );
+        pattern.target = Some("plate_0".to_owned());
+        wb.add_circular_pattern("bolts", vec!["ext1_0".to_owned()], pattern.clone());
+        wb.set_step_parameters("bolts", vec!["count".to_owned()], vec![4.0])
+            .unwrap();
+
+        // the spacing stays at 60 degrees, so the three copies land at 60,
+        // 120 and 180, each cut from the plate rather than added beside it
+        let wbv = wb.create_view(100);
+        assert!(wbv.diagnostics.is_empty());
+        assert_eq!(wbv.solids.len(), 2);
+        let plate = wbv.solids.get("plate_0").unwrap();
+        let faces: usize = plate
+            .truck_solid
+            .boundaries()
+            .iter()
+            .map(|shell| shell.len())
+            .sum();
+        assert_eq!(faces, 6 + 3 * 4);
+        assert!((signed_volume(plate) - (60.0 * 60.0 * 3.0 - 3.0 * 12.0)).abs() < 1e-3);
+        let mesh = plate.get_mesh();
+        let corner = |x: f64, y: f64| {
+            mesh.vertices
+                .iter()
+                .any(|v| (v.x - x).abs() < 1e-6 && (v.y - y).abs() < 1e-6)
+        };
+        assert!(corner(-19.0, 1.0) && corner(-21.0, -1.0));
+        assert!(!corner(19.0, 1.0));
+
+        // a cut has to know what it's cutting
+        pattern.target = None;
+        wb.add_circular_pattern("loose", vec!["ext1_0".to_owned()], pattern);
+        let wbv = wb.create_view(100);
+        assert!(matches!(
+            wbv.errors("loose")[..],
+            [Error::InvalidParameter(_)]
+        ));
+
+        // new instances remember their profile, so their edges can still be
+        // treated
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 1.0);
+        wb.add_extrusion("ext1", "sketch1", 3.0, vec![0], Operation::New);
+        wb.add_circular_pattern(
+            "copies",
+            vec!["ext1_0".to_owned()],
+            CircularPattern::full_circle(
+                Point::new(20.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+                2,
+                Operation::New,
+            ),
+        );
+        let wbv = wb.create_view(100);
+        let prism = wbv.solids.get("copies_0").unwrap().prism.as_ref().unwrap();
+        assert!((prism.face.exterior.segments[0].start.x - 41.0).abs() < 1e-6);
+    }
+
+    fn signed_volume(solid: &Solid) -> f64 {
+        let mesh = solid.get_mesh();
+        mesh.vertices
+            .chunks(3)
+            .map(|t| {
+                t[0].to_vector()
+                    .dot(t[1].to_vector().cross(t[2].to_vector()))
+                    / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_mirror_across_plane() {
+        let mut wb = Workbench::new("wb");
+        let a = Point2D::new(5.0, -2.0, "A");
+        let b = Point2D::new(9.0, -2.0, "B");
+        let c = Point2D::new(9.0, 2.0, "C");
+        let d = Point2D::new(5.0, 2.0, "D");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(vec![a, b, c, d], true));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 3.0, vec![0], Operation::New);
+        wb.add_mirror(
+            "mirror1",
+            vec!["ext1".to_owned()],
+            Mirror::new(PlaneRef::Plane("Right".to_owned()), false),
+        );
+        wb.add_fillet("fillet1", vec![EdgeId::new("mirror1_0", 0, 0)], 1.0);
+
+        let wbv = wb.create_view(100);
+        let original = wbv.solids.get("ext1_0").unwrap();
+        let mirrored = wbv.solids.get("mirror1_0").unwrap();
+        let mesh = mirrored.get_mesh();
+        let max_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MIN, f64::max);
+        let min_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MAX, f64::min);
+        assert!((max_x + 5.0).abs() < 1e-6);
+        assert!((min_x + 9.0).abs() < 1e-6);
+
+        // still a closed solid facing outward, filleted after mirroring
+        let shells = mirrored.truck_solid.boundaries();
+        assert_eq!(shells[0].len(), 7);
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+        assert!(signed_volume(mirrored) > 0.0);
+        assert!((signed_volume(original) - 48.0).abs() < 1e-6);
+        assert!(mirrored.get_step_text().contains("CLOSED_SHELL"));
+    }
+
+    #[test]
+    fn test_mirror_merge_across_face() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 2.0);
+        wb.add_extrusion("ext1", "sketch1", 3.0, vec![0], Operation::New);
+        wb.add_mirror(
+            "mirror1",
+            vec!["ext1_0".to_owned()],
+            Mirror::new(
+                PlaneRef::Face {
+                    solid: "ext1_0".to_owned(),
+                    face: PrismFace::Side(0, 1),
+                },
+                true,
+            ),
+        );
+
+        let wbv = wb.create_view(100);
+        assert_eq!(wbv.solids.len(), 1);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let prism = solid.prism.as_ref().unwrap();
+        assert_eq!(prism.face.exterior.segments.len(), 4);
+        assert!((signed_volume(solid) - 96.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mirror_merge_across_cap() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 2.0);
+        wb.add_extrusion("ext1", "sketch1", 3.0, vec![0], Operation::New);
+        wb.add_mirror(
+            "mirror1",
+            vec!["ext1".to_owned()],
+            Mirror::new(
+                PlaneRef::Face {
+                    solid: "ext1_0".to_owned(),
+                    face: PrismFace::Bottom,
+                },
+                true,
+            ),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let mesh = solid.get_mesh();
+        let min_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MAX, f64::min);
+        assert!((min_z + 3.0).abs() < 1e-6);
+        assert!((signed_volume(solid) - 96.0).abs() < 1e-6);
+    }
+
+    fn add_hole_block(wb: &mut Workbench) {
+        let a = Point2D::new(-10.0, -10.0, "A");
+        let b = Point2D::new(10.0, -10.0, "B");
+        let c = Point2D::new(10.0, 10.0, "C");
+        let d = Point2D::new(-10.0, 10.0, "D");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(vec![a, b, c, d], true));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+
+        let mut centers = Sketch::new();
+        centers.add_point(Point2D::new(-5.0, 0.0, "left"));
+        centers.add_point(Point2D::new(5.0, 0.0, "right"));
+        centers.add_point(Point2D::new(9.0, 9.0, "corner"));
+        wb.add_sketch("centers", centers, "Top");
+    }
+
+    #[test]
+    fn test_hole_through_all() {
+        let mut wb = Workbench::new("wb");
+        add_hole_block(&mut wb);
+        wb.add_hole(
+            "hole1",
+            "ext1_0",
+            PrismFace::Top,
+            "centers",
+            vec!["left".to_owned(), "right".to_owned()],
+            Hole::new(
+                HoleKind::Simple,
+                ThreadStandard::IsoMetric,
+                "M6",
+                HoleDepth::ThroughAll,
+            ),
+        );
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 0)], 2.0);
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let shells = solid.truck_solid.boundaries();
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+        assert_eq!(solid.prism.as_ref().unwrap().holes.len(), 2);
+        assert!(solid.threads.is_empty());
+        assert!(solid.get_step_text().contains("CLOSED_SHELL"));
+
+        // both 6.6mm clearance holes are cut, and the fillet kept them
+        let fillet = 2.0 * 2.0 * (1.0 - PI / 4.0) * 10.0;
+        let bores = 2.0 * PI * 3.3 * 3.3 * 10.0;
+        let expected = 4000.0 - fillet - bores;
+        assert!((signed_volume(solid) - expected).abs() / expected < 1e-3);
+
+        // holes go in through the caps only
+        wb.add_hole(
+            "hole2",
+            "ext1_0",
+            PrismFace::Side(0, 0),
+            "centers",
+            vec!["corner".to_owned()],
+            Hole::new(
+                HoleKind::Simple,
+                ThreadStandard::IsoMetric,
+                "M3",
+                HoleDepth::ThroughAll,
+            ),
+        );
+        let wbv = wb.create_view(100);
+        assert!(matches!(
+            wbv.errors("hole2")[..],
+            [Error::InvalidParameter(_)]
+        ));
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        assert_eq!(solid.prism.as_ref().unwrap().holes.len(), 2);
+    }
+
+    #[test]
+    fn test_hole_counterbore_and_countersink() {
+        let mut wb = Workbench::new("wb");
+        add_hole_block(&mut wb);
+        wb.add_hole(
+            "cbore",
+            "ext1_0",
+            PrismFace::Top,
+            "centers",
+            vec!["left".to_owned()],
+            Hole::new(
+                HoleKind::Counterbore,
+                ThreadStandard::IsoMetric,
+                "M4",
+                HoleDepth::ThroughAll,
+            ),
+        );
+        wb.add_hole(
+            "csink",
+            "ext1_0",
+            PrismFace::Bottom,
+            "centers",
+            vec!["right".to_owned()],
+            Hole::new(
+                HoleKind::Countersink,
+                ThreadStandard::IsoMetric,
+                "M4",
+                HoleDepth::Blind(6.0),
+            ),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        assert_eq!(solid.prism.as_ref().unwrap().holes.len(), 2);
+        assert_eq!(
+            solid.truck_solid.boundaries()[0].shell_condition(),
+            ShellCondition::Closed
+        );
+
+        // counterbore: 8mm across and 4mm deep over a 4.5mm through hole
+        let cbore = PI * 4.0 * 4.0 * 4.0 + PI * 2.25 * 2.25 * 6.0;
+        // countersink: a 90 degree cone from 8.96mm down to 4.5mm, then the
+        // rest of the bore and a 118 degree drill point
+        let (r, big) = (2.25, 4.48);
+        let sink = big - r;
+        let cone = PI * sink / 3.0 * (big * big + big * r + r * r);
+        let tip = r / (59.0_f64.to_radians()).tan();
+        let csink = cone + PI * r * r * (6.0 - sink) + PI * r * r * tip / 3.0;
+        let expected = 4000.0 - cbore - csink;
+        assert!((signed_volume(solid) - expected).abs() / expected < 1e-3);
+
+        // the countersink opens on the bottom face
+        let mesh = solid.get_mesh();
+        assert!(mesh
+            .vertices
+            .iter()
+            .any(|v| v.z.abs() < 1e-9 && ((v.x - 5.0).hypot(v.y) - big).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_hole_tapped_records_thread() {
+        let mut wb = Workbench::new("wb");
+        add_hole_block(&mut wb);
+        wb.add_hole(
+            "tap",
+            "ext1_0",
+            PrismFace::Top,
+            "centers",
+            vec!["left".to_owned(), "corner".to_owned()],
+            Hole::new(
+                HoleKind::Tapped,
+                ThreadStandard::Unc,
+                "1/4",
+                HoleDepth::Blind(6.0),
+            ),
+        );
+
+        // the hole at the corner would break out of the side, so only one is drilled
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        assert_eq!(solid.prism.as_ref().unwrap().holes.len(), 1);
+        assert_eq!(solid.threads.len(), 1);
+        let thread = &solid.threads[0];
+        assert_eq!(thread.designation, "1/4-20 UNC");
+        assert!(thread.internal);
+        assert!((thread.length - 6.0).abs() < 1e-9);
+        assert!((thread.origin.z - 10.0).abs() < 1e-9);
+        assert!((thread.axis.z + 1.0).abs() < 1e-9);
+
+        wb.set_step_parameters("tap", vec!["depth".to_owned()], vec![12.0])
+            .unwrap();
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        assert!(solid.prism.as_ref().unwrap().holes.is_empty());
+    }
+
+    #[test]
+    fn test_draft_side_faces() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 2)], 1.0);
+        wb.add_draft(
+            "draft1",
+            "ext1_0",
+            (0..4).map(|k| PrismFace::Side(0, k)).collect(),
+            Draft::new(
+                5.0_f64.to_radians(),
+                PlaneRef::Face {
+                    solid: "ext1_0".to_owned(),
+                    face: PrismFace::Bottom,
+                },
+            ),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let shells = solid.truck_solid.boundaries();
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+        assert!(solid.get_step_text().contains("CLOSED_SHELL"));
+
+        // every side leans in by the same amount, so the top shrinks evenly
+        let inset = 10.0 * 5.0_f64.to_radians().tan();
+        let mesh = solid.get_mesh();
+        for v in mesh.vertices.iter().filter(|v| (v.z - 10.0).abs() < 1e-9) {
+            assert!(v.x.abs() <= 10.0 - inset + 1e-6);
+            assert!(v.y.abs() <= 10.0 - inset + 1e-6);
         }
+        let (a1, a2) = (400.0, (20.0 - 2.0 * inset).powi(2));
+        let frustum = 10.0 / 3.0 * (a1 + a2 + (a1 * a2).sqrt());
+        let fillet = 1.0 - PI / 4.0;
+        assert!((signed_volume(solid) - (frustum - 10.0 * fillet)).abs() / frustum < 1e-3);
     }
 
-    pub fn find_plane(&self, name: &str) -> Option<&Plane> {
-        self.planes.get(name)
+    #[test]
+    fn test_draft_neutral_plane_and_limits() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        let middle = Plane::new(
+            Point::new(0.0, 0.0, 5.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Middle", middle);
+        wb.add_draft(
+            "draft1",
+            "ext1_0",
+            vec![PrismFace::Side(0, 1), PrismFace::Top],
+            Draft::new(10.0_f64.to_radians(), PlaneRef::Plane("Middle".to_owned())),
+        );
+
+        // the face pivots about z = 5, sticking out below and tucked in above
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("ext1_0").unwrap();
+        let prism = solid.prism.as_ref().unwrap();
+        assert!(prism.drafts[0][1].is_some());
+        let lean = 5.0 * 10.0_f64.to_radians().tan();
+        let mesh = solid.get_mesh();
+        let max_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MIN, f64::max);
+        assert!((max_x - (10.0 + lean)).abs() < 1e-6);
+        assert!((signed_volume(solid) - 4000.0).abs() < 1e-3);
+
+        // a steep draft from the bottom would cross the opposite wall
+        wb.add_draft(
+            "draft2",
+            "ext1_0",
+            vec![PrismFace::Side(0, 0), PrismFace::Side(0, 2)],
+            Draft::new(
+                60.0_f64.to_radians(),
+                PlaneRef::Face {
+                    solid: "ext1_0".to_owned(),
+                    face: PrismFace::Bottom,
+                },
+            ),
+        );
+        let wbv = wb.create_view(100);
+        let prism = wbv.solids.get("ext1_0").unwrap().prism.as_ref().unwrap();
+        assert!(prism.drafts[0][0].is_some());
+        assert!(prism.drafts[0][2].is_none());
+
+        // the top isn't a side face, a square has no fifth side, and a body
+        // that isn't an extrusion has no sides to draft
+        match &wbv.errors("draft1")[..] {
+            [Error::InvalidParameter(message)] => assert!(message.contains("not its end caps")),
+            other => panic!("unexpected errors {:?}", other),
+        }
+        let bottom = PlaneRef::Face {
+            solid: "ext1_0".to_owned(),
+            face: PrismFace::Bottom,
+        };
+        wb.add_draft(
+            "draft3",
+            "ext1_0",
+            vec![PrismFace::Side(0, 4)],
+            Draft::new(5.0_f64.to_radians(), bottom.clone()),
+        );
+        add_revolved_block(&mut wb);
+        wb.add_draft(
+            "draft4",
+            "rev1_0",
+            vec![PrismFace::Side(0, 0)],
+            Draft::new(5.0_f64.to_radians(), bottom),
+        );
+        wb.add_shell(
+            "shell1",
+            "rev1_0",
+            vec![PrismFace::Top],
+            Shell::new(1.0, ShellDirection::Inward),
+        );
+        let wbv = wb.create_view(100);
+        for step in ["draft3", "draft4"] {
+            assert!(matches!(wbv.errors(step)[..], [Error::InvalidParameter(_)]));
+        }
+        // the error for a body that isn't a plain extrusion says what can be treated
+        for (step, treatment) in [("draft4", "drafted"), ("shell1", "shelled")] {
+            match &wbv.errors(step)[..] {
+                [Error::InvalidParameter(message)] => {
+                    assert!(message.contains(&format!("can't be {}", treatment)));
+                    assert!(message.contains("only plain extrusions can be"));
+                }
+                other => panic!("unexpected errors {:?}", other),
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::sketch;
+    fn add_l_bracket(wb: &mut Workbench) {
+        // an L-shaped profile 4 thick with 20 long legs, extruded 10 up
+        let points = vec![
+            Point2D::new(0.0, 0.0, "A"),
+            Point2D::new(20.0, 0.0, "B"),
+            Point2D::new(20.0, 4.0, "C"),
+            Point2D::new(4.0, 4.0, "D"),
+            Point2D::new(4.0, 20.0, "E"),
+            Point2D::new(0.0, 20.0, "F"),
+        ];
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(points, true));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+    }
 
-    use super::*;
+    #[test]
+    fn test_rib_gusset_in_profile_plane() {
+        let mut wb = Workbench::new("wb");
+        add_l_bracket(&mut wb);
+        let middle = Plane::new(
+            Point::new(0.0, 0.0, 5.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Middle", middle);
+        let mut chain = Sketch::new();
+        chain.add_segments(Segment::link(
+            vec![Point2D::new(14.0, 6.0, "G"), Point2D::new(6.0, 14.0, "H")],
+            false,
+        ));
+        wb.add_sketch("chain", chain, "Middle");
+        wb.add_rib(
+            "rib1",
+            "chain",
+            vec![0],
+            "ext1_0",
+            Rib::new(2.0, Operation::Add),
+        );
+
+        let wbv = wb.create_view(100);
+        let rib = wbv.solids.get("rib1_0").unwrap();
+        let shells = rib.truck_solid.boundaries();
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+
+        // the line runs on to both legs, closing off the triangle in the corner
+        assert!((signed_volume(rib) - 72.0 * 2.0).abs() < 1e-6);
+        let mesh = rib.get_mesh();
+        let min_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MAX, f64::min);
+        let max_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MIN, f64::max);
+        assert!((min_z - 4.0).abs() < 1e-9 && (max_z - 6.0).abs() < 1e-9);
+    }
 
     #[test]
-    fn test_add_sketch_and_extrusion() {
+    fn test_rib_across_extrusion_and_failures() {
         let mut wb = Workbench::new("wb");
-        wb.add_sketch_and_extrusion();
+        add_l_bracket(&mut wb);
+        let across = Plane::new(
+            Point::new(2.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(1.0, 0.0, 0.0),
+        );
+        wb.add_plane("Across", across);
+        let mut chain = Sketch::new();
+        chain.add_segments(Segment::link(
+            vec![
+                Point2D::new(6.0, 14.0, "G"),
+                Point2D::new(10.0, 18.0, "H"),
+                Point2D::new(14.0, 14.0, "I"),
+            ],
+            false,
+        ));
+        wb.add_sketch("chain", chain, "Across");
+        wb.add_rib(
+            "rib1",
+            "chain",
+            vec![0, 1],
+            "ext1_0",
+            Rib::new(2.0, Operation::Add),
+        );
+        // the second line alone heads away from the body at one end
+        wb.add_rib(
+            "rib2",
+            "chain",
+            vec![0],
+            "ext1_0",
+            Rib::new(2.0, Operation::Add),
+        );
+
+        let tilted = Plane::new(
+            Point::new(0.0, 0.0, 5.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 0.6, 0.8),
+            Vector::new(0.0, -0.8, 0.6),
+        );
+        wb.add_plane("Tilted", tilted);
+        let mut skew = Sketch::new();
+        skew.add_segments(Segment::link(
+            vec![Point2D::new(14.0, 6.0, "G"), Point2D::new(6.0, 14.0, "H")],
+            false,
+        ));
+        wb.add_sketch("skew", skew, "Tilted");
+        wb.add_rib(
+            "rib3",
+            "skew",
+            vec![0],
+            "ext1_0",
+            Rib::new(2.0, Operation::Add),
+        );
+
         let wbv = wb.create_view(100);
-        let solid0 = wbv.solids.get("Extrude 1_0").unwrap();
-        let as_mesh = solid0.get_mesh();
+        let rib = wbv.solids.get("rib1_0").unwrap();
+        assert_eq!(
+            rib.truck_solid.boundaries()[0].shell_condition(),
+            ShellCondition::Closed
+        );
+        // a fin on top of the upright leg, peaked over its middle
+        assert!((signed_volume(rib) - 64.0 * 2.0).abs() < 1e-6);
+        let mesh = rib.get_mesh();
+        let max_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MIN, f64::max);
+        assert!((max_z - 18.0).abs() < 1e-9);
 
-        let solid1 = wbv.solids.get("Extrude 1_1").unwrap();
-        let as_mesh = solid1.get_mesh();
+        assert!(!wbv.solids.contains_key("rib2_0"));
+        assert!(!wbv.solids.contains_key("rib3_0"));
     }
 
     #[test]
-    fn test_actual_dummy_steps() {
-        // let mut wb = Workbench::new("wb");
-        let width = 100.0;
-        let depth = 75.0;
-        let height = 20.0;
-        let sep = 20.0;
-        let a = Point2D::new(-width / 2.0, -depth / 2.0, "A");
-        let b = Point2D::new(-width / 2.0, depth / 2.0, "B");
-        let c = Point2D::new(width / 2.0, depth / 2.0, "C");
-        let d = Point2D::new(width / 2.0, -depth / 2.0, "D");
+    fn test_thin_extrusion_sides_and_tube() {
+        let mut wb = Workbench::new("wb");
+        let mut bracket = Sketch::new();
+        bracket.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(20.0, 0.0, "B"),
+                Point2D::new(20.0, 10.0, "C"),
+            ],
+            false,
+        ));
+        wb.add_sketch("bracket", bracket, "Top");
+        add_square(&mut wb, "square", "Top", 10.0);
+        for (name, side) in [
+            ("one", ThinSide::OneSide),
+            ("other", ThinSide::OtherSide),
+            ("mid", ThinSide::Mid),
+        ] {
+            let thin = Thin::new(2.0, side);
+            wb.add_thin_extrusion(name, "bracket", 5.0, vec![0, 1], thin, Operation::New);
+        }
+        let thin = Thin::new(2.0, ThinSide::Mid);
+        wb.add_thin_extrusion(
+            "tube",
+            "square",
+            10.0,
+            vec![0, 1, 2, 3],
+            thin,
+            Operation::New,
+        );
+        let thin = Thin::new(30.0, ThinSide::OneSide);
+        wb.add_thin_extrusion(
+            "thick",
+            "square",
+            10.0,
+            vec![0, 1, 2, 3],
+            thin,
+            Operation::New,
+        );
 
-        let e = Point2D::new(-width / 2.0 + width + sep, -depth / 2.0, "E");
-        let f = Point2D::new(-width / 2.0 + width + sep, depth / 2.0, "F");
-        let g = Point2D::new(width / 2.0 + width + sep, depth / 2.0, "G");
-        let h = Point2D::new(width / 2.0 + width + sep, -depth / 2.0, "H");
+        let wbv = wb.create_view(100);
+        // the open chain's band is mitered at the bend
+        for (name, area) in [("one", 56.0), ("other", 64.0), ("mid", 60.0)] {
+            let solid = wbv.solids.get(&format!("{}_0", name)).unwrap();
+            assert!((signed_volume(solid) - 5.0 * area).abs() < 1e-6);
+        }
 
-        let i = Point2D::new(-width / 4.0, -depth / 4.0, "I");
-        let j = Point2D::new(-width / 4.0, depth / 4.0, "J");
-        let k = Point2D::new(width / 4.0, depth / 4.0, "K");
-        let l = Point2D::new(width / 4.0, -depth / 4.0, "L");
+        // a closed chain becomes a square tube that can still be filleted
+        let tube = wbv.solids.get("tube_0").unwrap();
+        assert_eq!(
+            tube.truck_solid.boundaries()[0].shell_condition(),
+            ShellCondition::Closed
+        );
+        assert!((signed_volume(tube) - 10.0 * (484.0 - 324.0)).abs() < 1e-6);
+        assert_eq!(tube.prism.as_ref().unwrap().face.interiors.len(), 1);
 
-        let segments = Segment::link(vec![a, b, c, d], true);
-        let mut sketch1 = Sketch::new();
-        sketch1.add_segments(segments);
+        assert!(!wbv.solids.contains_key("thick_0"));
+    }
 
-        let segments2 = Segment::link(vec![e, f, g, h], true);
-        sketch1.add_segments(segments2);
+    #[test]
+    fn test_revolve_solid_and_thin() {
+        let mut wb = Workbench::new("wb");
+        let mut ring = Sketch::new();
+        ring.add_segments(Segment::link(
+            vec![
+                Point2D::new(5.0, 0.0, "A"),
+                Point2D::new(10.0, 0.0, "B"),
+                Point2D::new(10.0, 4.0, "C"),
+                Point2D::new(5.0, 4.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("ring", ring, "Front");
+        let mut wall = Sketch::new();
+        wall.add_segments(Segment::link(
+            vec![Point2D::new(5.0, 0.0, "A"), Point2D::new(5.0, 10.0, "B")],
+            false,
+        ));
+        wb.add_sketch("wall", wall, "Front");
+        let mut straddle = Sketch::new();
+        straddle.add_segments(Segment::link(
+            vec![
+                Point2D::new(-2.0, 0.0, "A"),
+                Point2D::new(3.0, 0.0, "B"),
+                Point2D::new(3.0, 4.0, "C"),
+                Point2D::new(-2.0, 4.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("straddle", straddle, "Front");
 
-        let segments_hole = Segment::link(vec![i, j, k, l], true);
-        sketch1.add_segments(segments_hole);
-        // wb.add_sketch("Sketch 1", sketch1, "Top");
+        let origin = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 0.0, 1.0);
+        wb.add_revolve(
+            "washer",
+            "ring",
+            vec![0],
+            Revolve::new(origin, up, 2.0 * PI, Operation::New),
+        );
+        wb.add_thin_revolve(
+            "cup",
+            "wall",
+            vec![0],
+            Thin::new(1.0, ThinSide::OneSide),
+            Revolve::new(origin, up, PI, Operation::New),
+        );
+        wb.add_revolve(
+            "bad",
+            "straddle",
+            vec![0],
+            Revolve::new(origin, up, 2.0 * PI, Operation::New),
+        );
 
-        let faces = sketch1.find_faces(false);
-        println!("Found {} faces", faces.len());
-        for f in faces.iter() {
-            println!("\nFace: {:?}", f);
+        let wbv = wb.create_view(100);
+        let washer = wbv.solids.get("washer_0").unwrap();
+        assert_eq!(
+            washer.truck_solid.boundaries()[0].shell_condition(),
+            ShellCondition::Closed
+        );
+        let expected = PI * (100.0 - 25.0) * 4.0;
+        assert!((signed_volume(washer) - expected).abs() / expected < 1e-2);
+
+        // the wall thickens toward the axis, then sweeps half way round
+        let cup = wbv.solids.get("cup_0").unwrap();
+        let expected = PI / 2.0 * (25.0 - 16.0) * 10.0;
+        assert!((signed_volume(cup) - expected).abs() / expected < 1e-2);
+
+        assert!(!wbv.solids.contains_key("bad_0"));
+    }
+
+    #[test]
+    fn test_sheet_metal_bracket() {
+        let mut wb = Workbench::new("wb");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(100.0, 0.0, "B"),
+                Point2D::new(100.0, 60.0, "C"),
+                Point2D::new(0.0, 60.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_base_flange("base", "sketch1", 0, BaseFlange::new(2.0, 3.0));
+        wb.add_edge_flange(
+            "flange1",
+            "base_0",
+            vec![0, 2],
+            EdgeFlange::new(20.0, PI / 2.0),
+        );
+
+        let wbv = wb.create_view(100);
+        let part = wbv.solids.get("base_0").unwrap();
+        for shell in part.truck_solid.boundaries() {
+            assert_eq!(shell.shell_condition(), ShellCondition::Closed);
         }
+        let bend = PI / 4.0 * (25.0 - 9.0) * 100.0;
+        let expected = 100.0 * 60.0 * 2.0 + 2.0 * (bend + 20.0 * 2.0 * 100.0);
+        assert!((signed_volume(part) - expected).abs() / expected < 1e-2);
+
+        // the flanges stand up on the long edges, just outside the base
+        let mesh = part.get_mesh();
+        let max_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MIN, f64::max);
+        let min_y = mesh.vertices.iter().map(|v| v.y).fold(f64::MAX, f64::min);
+        assert!((max_z - 25.0).abs() < 1e-6);
+        assert!((min_y + 5.0).abs() < 1e-6);
+
+        let flat = wbv.flat_pattern("base_0").unwrap();
+        let allowance = PI / 2.0 * (3.0 + DEFAULT_K_FACTOR * 2.0);
+        let (ys, xs): (Vec<f64>, Vec<f64>) = flat.outline[0].iter().map(|p| (p.1, p.0)).unzip();
+        let height = ys.iter().cloned().fold(f64::MIN, f64::max)
+            - ys.iter().cloned().fold(f64::MAX, f64::min);
+        assert!((height - (60.0 + 2.0 * (allowance + 20.0))).abs() < 1e-9);
+        assert!(xs.iter().all(|x| (-1e-9..=100.0 + 1e-9).contains(x)));
+        assert_eq!(flat.bends.len(), 2);
+
+        assert!(wbv.flat_pattern("sketch1").is_err());
     }
 
     #[test]
-    fn test_trangular_prism() {
+    fn test_split_and_delete_body() {
         let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 2)], 1.0);
+        let middle = Plane::new(
+            Point::new(0.0, 0.0, 4.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        );
+        wb.add_plane("Middle", middle);
+        wb.add_split("split1", "ext1_0", PlaneRef::Plane("Middle".to_owned()));
+        // then cut the top piece in half down the Right plane
+        wb.add_split("split2", "split1_1", PlaneRef::Plane("Right".to_owned()));
 
-        let a = Point2D::new(-1.0, 0.0, "A");
-        let b = Point2D::new(1.0, 0.0, "B");
-        let c = Point2D::new(0.0, 1.0, "C");
-        let segments = Segment::link(vec![a, b, c], true);
-        let mut sketch1 = Sketch::new();
-        sketch1.add_segments(segments);
-        wb.add_sketch("sketch1", sketch1, "Front");
+        let wbv = wb.create_view(100);
+        assert!(!wbv.solids.contains_key("ext1_0"));
+        // the filleted corner survives the cut across the extrusion
+        let fillet = 1.0 - PI / 4.0;
+        let bottom = wbv.solids.get("split1_0").unwrap();
+        assert!((signed_volume(bottom) - 4.0 * (400.0 - fillet)).abs() < 1e-2);
+        assert!(!wbv.solids.contains_key("split2_0"));
 
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
         wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_plane("Middle", middle);
+        wb.add_split("split1", "ext1_0", PlaneRef::Plane("Middle".to_owned()));
+        wb.add_split("split2", "split1_1", PlaneRef::Plane("Right".to_owned()));
+        let wbv = wb.create_view(100);
+        let behind = wbv.solids.get("split2_0").unwrap();
+        let ahead = wbv.solids.get("split2_1").unwrap();
+        assert!((signed_volume(behind) - 6.0 * 200.0).abs() < 1e-6);
+        assert!((signed_volume(ahead) - 6.0 * 200.0).abs() < 1e-6);
+        let mesh = behind.get_mesh();
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|v| v.x <= 1e-9 && v.z >= 4.0 - 1e-9));
 
+        wb.add_delete_body("delete1", vec!["split2".to_owned()], false);
         let wbv = wb.create_view(100);
-        let solid = wbv.solids.get("ext1_0").unwrap();
-        let as_mesh = solid.get_mesh();
+        assert_eq!(wbv.solids.keys().collect::<Vec<_>>(), vec!["split1_0"]);
 
-        solid.save_as_obj("test0.obj");
+        wb.steps.pop();
+        wb.add_delete_body("keep1", vec!["split2_1".to_owned()], true);
+        let wbv = wb.create_view(100);
+        assert_eq!(wbv.solids.keys().collect::<Vec<_>>(), vec!["split2_1"]);
     }
 
     #[test]
-    fn test_square_with_hole() {
+    fn test_transform_move_and_copy() {
         let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 10.0, vec![0], Operation::New);
+        wb.add_transform(
+            "copy1",
+            vec!["ext1".to_owned()],
+            BodyTransform::new(
+                Motion::Translate {
+                    direction: Vector::new(1.0, 0.0, 0.0),
+                    distance: 30.0,
+                },
+                true,
+            ),
+        );
+        wb.add_transform(
+            "turn1",
+            vec!["copy1_0".to_owned()],
+            BodyTransform::new(
+                Motion::Rotate {
+                    origin: Point::new(0.0, 0.0, 0.0),
+                    axis: Vector::new(0.0, 0.0, 1.0),
+                    angle: PI / 2.0,
+                },
+                false,
+            ),
+        );
+        wb.add_point("from", Point::new(0.0, 0.0, 0.0));
+        wb.add_point("to", Point::new(0.0, 0.0, 5.0));
+        wb.add_transform(
+            "lift1",
+            vec!["ext1_0".to_owned()],
+            BodyTransform::new(
+                Motion::PointToPoint {
+                    from: "from".to_owned(),
+                    to: "to".to_owned(),
+                },
+                false,
+            ),
+        );
 
-        let a = Point2D::new(-100.0, -100.0, "A");
-        let b = Point2D::new(100.0, -100.0, "B");
-        let c = Point2D::new(100.0, 100.0, "C");
-        let d = Point2D::new(-100.0, 100.0, "D");
-        let mut segments_0 = Segment::link(vec![a, b, c, d], true);
+        let bounds = |solid: &Solid| {
+            let mesh = solid.get_mesh();
+            let fold = |f: fn(&Point) -> f64, g: fn(f64, f64) -> f64, start: f64| {
+                mesh.vertices.iter().map(f).fold(start, g)
+            };
+            (
+                fold(|v| v.x, f64::min, f64::MAX),
+                fold(|v| v.y, f64::min, f64::MAX),
+                fold(|v| v.z, f64::min, f64::MAX),
+            )
+        };
+        let wbv = wb.create_view(100);
+        assert_eq!(wbv.solids.len(), 2);
+        // the copy slid 30 along x, then swung a quarter turn onto the y axis
+        let (x, y, z) = bounds(wbv.solids.get("copy1_0").unwrap());
+        assert!((x + 10.0).abs() < 1e-6 && (y - 20.0).abs() < 1e-6 && z.abs() < 1e-6);
+        let (x, y, z) = bounds(wbv.solids.get("ext1_0").unwrap());
+        assert!((x + 10.0).abs() < 1e-6 && (y + 10.0).abs() < 1e-6 && (z - 5.0).abs() < 1e-6);
 
-        let e = Point2D::new(-200.0, -200.0, "E");
-        let f = Point2D::new(200.0, -200.0, "F");
-        let g = Point2D::new(200.0, 200.0, "G");
-        let h = Point2D::new(-200.0, 200.0, "H");
-        let segments_1 = Segment::link(vec![e, f, g, h], true);
+        // edits upstream carry through
+        wb.set_step_parameters("copy1", vec!["distance".to_owned()], vec![50.0])
+            .unwrap();
+        for step in wb.steps.iter_mut() {
+            if let Step::Point { name, point } = step {
+                if name == "to" {
+                    *point = Point::new(0.0, 0.0, 8.0);
+                }
+            }
+        }
+        let wbv = wb.create_view(100);
+        let (_, y, _) = bounds(wbv.solids.get("copy1_0").unwrap());
+        assert!((y - 40.0).abs() < 1e-6);
+        let (_, _, z) = bounds(wbv.solids.get("ext1_0").unwrap());
+        assert!((z - 8.0).abs() < 1e-6);
+        assert!(wb
+            .set_step_parameters("lift1", vec!["angle".to_owned()], vec![1.0])
+            .is_err());
+    }
 
-        segments_0.extend(segments_1);
+    #[test]
+    fn test_cosmetic_threads_on_tube() {
+        let mut wb = Workbench::new("wb");
+        let mut tube = Sketch::new();
+        tube.add_segments(Segment::link(
+            vec![
+                Point2D::new(2.0, 0.0, "A"),
+                Point2D::new(3.0, 0.0, "B"),
+                Point2D::new(3.0, 20.0, "C"),
+                Point2D::new(2.0, 20.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("tube", tube, "Front");
+        let up = Vector::new(0.0, 0.0, 1.0);
+        wb.add_revolve(
+            "rev1",
+            "tube",
+            vec![0],
+            Revolve::new(Point::new(0.0, 0.0, 0.0), up, 2.0 * PI, Operation::New),
+        );
 
-        let mut sketch1 = Sketch::new();
-        sketch1.add_segments(segments_0);
+        let wbv = wb.create_view(100);
+        let cylinders = wbv.solids.get("rev1_0").unwrap().cylinders();
+        let outside = cylinders.iter().position(|c| !c.internal).unwrap();
+        let inside = cylinders.iter().position(|c| c.internal).unwrap();
+        assert!((cylinders[outside].radius - 3.0).abs() < 1e-9);
+        assert!((cylinders[inside].radius - 2.0).abs() < 1e-9);
+        assert!((cylinders[inside].length - 20.0).abs() < 1e-9);
 
-        wb.add_sketch("sketch1", sketch1, "Front");
-        wb.add_extrusion("ext1", "sketch1", 200.0, vec![1], Operation::New);
+        let metric = ThreadStandard::IsoMetric;
+        wb.add_cosmetic_thread(
+            "thread1",
+            "rev1_0",
+            outside,
+            CosmeticThread::new(metric, "M6", Some(12.0)),
+        );
+        wb.add_cosmetic_thread(
+            "thread2",
+            "rev1_0",
+            inside,
+            CosmeticThread::new(metric, "M4", None),
+        );
+        // too big for the shaft
+        wb.add_cosmetic_thread(
+            "thread3",
+            "rev1_0",
+            outside,
+            CosmeticThread::new(metric, "M8", None),
+        );
+
+        let wbv = wb.create_view(100);
+        let solid = wbv.solids.get("rev1_0").unwrap();
+        assert_eq!(solid.threads.len(), 2);
+        assert!(!solid.threads[0].internal && solid.threads[1].internal);
+        assert_eq!(solid.threads[0].callout(), "M6x1 x 12");
+        assert!((solid.threads[1].length - 20.0).abs() < 1e-9);
+
+        let step = solid.get_step_text();
+        assert!(step.contains("DESCRIPTIVE_REPRESENTATION_ITEM('M6x1 x 12', 'external thread"));
+        assert!(step.contains("DESCRIPTIVE_REPRESENTATION_ITEM('M4x0.7 - 20 DEEP'"));
+        assert!(step.trim_end().ends_with("END-ISO-10303-21;"));
+    }
+
+    #[test]
+    fn test_helix_sweep_spring() {
+        let mut wb = Workbench::new("wb");
+        let mut wire = Sketch::new();
+        wire.add_segments(Segment::link(
+            vec![
+                Point2D::new(4.5, 0.0, "A"),
+                Point2D::new(5.5, 0.0, "B"),
+                Point2D::new(5.5, 1.0, "C"),
+                Point2D::new(4.5, 1.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("wire", wire, "Front");
+        let (origin, up, x) = (
+            Point::new(0.0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(1.0, 0.0, 0.0),
+        );
+        wb.add_helix(
+            "coil",
+            Helix::new(origin, up, x, 5.0, 3.0, 1.0, 0.0).unwrap(),
+        );
+        wb.add_helix(
+            "cone",
+            Helix::new(origin, up, x, 5.0, 3.0, 1.0, 0.2).unwrap(),
+        );
+        for (name, path) in [("spring", "coil"), ("conical", "cone")] {
+            wb.add_sweep(
+                name,
+                "wire",
+                vec![0],
+                path,
+                vec![],
+                Sweep::new(SweepOrientation::FollowPath, Operation::New),
+            );
+        }
+
+        let wbv = wb.create_view(100);
+        let helix = wbv.helices.get("coil").unwrap();
+        let end = helix.point(1.0);
+        assert!((end.x - 5.0).abs() < 1e-9 && end.y.abs() < 1e-9);
+        assert!((end.z - 3.0).abs() < 1e-9);
+
+        // a square wire wound once round at a mean radius of 5
+        let spring = wbv.solids.get("spring_0").unwrap();
+        let shells = spring.truck_solid.boundaries();
+        assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+        let volume = signed_volume(spring);
+        assert!((volume - 10.0 * PI).abs() < 0.01 * 10.0 * PI);
+        assert!(spring.get_step_text().contains("B_SPLINE_SURFACE"));
+
+        // the taper winds it in, so it takes less wire
+        let conical = signed_volume(wbv.solids.get("conical_0").unwrap());
+        assert!(conical > 0.0 && conical < volume);
+    }
+
+    #[test]
+    fn test_modelled_thread_on_shaft() {
+        let mut wb = Workbench::new("wb");
+        let mut shaft = Sketch::new();
+        shaft.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(3.0, 0.0, "B"),
+                Point2D::new(3.0, 20.0, "C"),
+                Point2D::new(0.0, 20.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("shaft", shaft, "Front");
+        let up = Vector::new(0.0, 0.0, 1.0);
+        wb.add_revolve(
+            "rev1",
+            "shaft",
+            vec![0],
+            Revolve::new(Point::new(0.0, 0.0, 0.0), up, 2.0 * PI, Operation::New),
+        );
+        let wbv = wb.create_view(100);
+        let face = wbv
+            .solids
+            .get("rev1_0")
+            .unwrap()
+            .cylinders()
+            .iter()
+            .position(|c| !c.internal)
+            .unwrap();
+
+        let metric = ThreadStandard::IsoMetric;
+        let cut = ModelledThread::new(metric, "M6", Some(3.0), Operation::Remove);
+        let ridge = ModelledThread::new(metric, "M6", Some(3.0), Operation::Add);
+        wb.add_thread("groove", "rev1_0", face, cut);
+        wb.add_thread("ridge", "rev1_0", face, ridge);
+        wb.add_thread(
+            "too_big",
+            "rev1_0",
+            face,
+            ModelledThread::new(metric, "M8", None, Operation::Remove),
+        );
 
         let wbv = wb.create_view(100);
+        assert!(!wbv.solids.contains_key("too_big_0"));
+        let groove = wbv.solids.get("groove_0").unwrap();
+        let ridge = wbv.solids.get("ridge_0").unwrap();
+        for solid in [groove, ridge] {
+            let shells = solid.truck_solid.boundaries();
+            assert_eq!(shells[0].shell_condition(), ShellCondition::Closed);
+            assert!(signed_volume(solid) > 0.0);
+            let step = solid.get_step_text();
+            assert!(step.contains("B_SPLINE_SURFACE"));
+            assert!(step.trim_end().ends_with("END-ISO-10303-21;"));
+        }
+
+        // the groove stays within the thread depth below the shaft's surface
+        let minor = 3.0 - 5.0 / 16.0 * 3f64.sqrt();
+        for v in groove.get_mesh().vertices.iter() {
+            let r = (v.x * v.x + v.y * v.y).sqrt();
+            assert!(r > minor - 1e-3 && r < 3.0 + 1e-3);
+            assert!(v.z > -1e-6 && v.z < 3.0 + 1e-6);
+        }
+        // and a ridge and a groove fill a turn between them
+        let turn = PI * (9.0 - minor * minor);
+        let filled = signed_volume(groove) / 2.125 + signed_volume(ridge) / 2.25;
+        assert!((filled - turn).abs() < 0.02 * turn);
+    }
 
+    #[test]
+    fn test_face_names_survive_edits() {
+        let mut wb = Workbench::new("wb");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(10.0, 0.0, "B"),
+                Point2D::new(10.0, 5.0, "C"),
+                Point2D::new(0.0, 5.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+
+        let end = FaceName::End("ext1".to_owned());
+        let side = |segment| FaceName::Side("ext1".to_owned(), segment);
+        let points = |solid: &Solid, name: &FaceName| -> Vec<Point> {
+            let face = solid.find_face(name).unwrap();
+            let points = face
+                .vertex_iter()
+                .map(|v| Point::from_point3(v.get_point()))
+                .collect();
+            points
+        };
+
+        for depth in [4.0, 9.0] {
+            wb.set_step_parameters("ext1", vec!["depth".to_owned()], vec![depth])
+                .unwrap();
+            let wbv = wb.create_view(100);
+            let solid = wbv.solids.get("ext1_0").unwrap();
+            let names = solid.face_names();
+            assert_eq!(names.len(), 6);
+            assert!(names.iter().all(|n| n.is_some()));
+
+            // segment 1 runs from B to C, along x = 10
+            assert!(points(solid, &side(1))
+                .iter()
+                .all(|p| (p.x - 10.0).abs() < 1e-9));
+            assert!(points(solid, &end)
+                .iter()
+                .all(|p| (p.z - depth).abs() < 1e-9));
+            let edge = solid.find_edge(&end, &side(1)).unwrap();
+            let top = Point::from_point3(edge.front().get_point());
+            assert!((top.x - 10.0).abs() < 1e-9 && (top.z - depth).abs() < 1e-9);
+            let (a, b) = solid.edge_name(&edge).unwrap();
+            assert!(a == end && b == side(1) || a == side(1) && b == end);
+            let corner = solid.find_vertex(&[end.clone(), side(0), side(1)]).unwrap();
+            let corner = Point::from_point3(corner.get_point());
+            assert!((corner.x - 10.0).abs() < 1e-9 && corner.y.abs() < 1e-9);
+        }
+
+        // rounding the corner at B leaves the faces it trims with their names
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        wb.add_mirror(
+            "mirror1",
+            vec!["ext1".to_owned()],
+            Mirror::new(PlaneRef::Plane("Right".to_owned()), false),
+        );
+        let wbv = wb.create_view(100);
         let solid = wbv.solids.get("ext1_0").unwrap();
-        let as_mesh = solid.get_mesh();
+        assert_eq!(solid.face_names().iter().filter(|n| n.is_none()).count(), 1);
+        assert!(solid.find_edge(&side(0), &side(1)).is_none());
+        assert!(solid.find_edge(&end, &side(1)).is_some());
 
-        solid.save_as_obj("test1.obj");
-        solid.save_as_step("test1.step");
+        // a mirror image is still made from the same sketch segments
+        let mirrored = wbv.solids.get("mirror1_0").unwrap();
+        assert!(points(mirrored, &side(1))
+            .iter()
+            .all(|p| (p.x + 10.0).abs() < 1e-9));
+        assert!(points(mirrored, &end)
+            .iter()
+            .all(|p| (p.z - 9.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_variables_drive_step_parameters() {
+        let mut wb = Workbench::new("wb");
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(10.0, 0.0, "B"),
+                Point2D::new(10.0, 5.0, "C"),
+                Point2D::new(0.0, 5.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 1.0, vec![0], Operation::New);
+
+        wb.set_variable("wall", "2").unwrap();
+        wb.set_variable("width", "3 * wall + 40").unwrap();
+        assert_eq!(wb.evaluate_variables().unwrap()["width"], 46.0);
+        wb.bind_step_parameter("sketch1", "B.x", "width").unwrap();
+        wb.bind_step_parameter("sketch1", "C.x", "width").unwrap();
+        wb.bind_step_parameter("ext1", "depth", "wall * 5").unwrap();
+        assert!(wb.bind_step_parameter("ext1", "depth", "height").is_err());
+        assert!(wb.bind_step_parameter("sketch1", "E.x", "wall").is_err());
+
+        let extent = |wb: &Workbench| {
+            let mesh = wb.create_view(100).solids.get("ext1_0").unwrap().get_mesh();
+            let max_x = mesh.vertices.iter().map(|v| v.x).fold(f64::MIN, f64::max);
+            let max_z = mesh.vertices.iter().map(|v| v.z).fold(f64::MIN, f64::max);
+            (max_x, max_z)
+        };
+        assert_eq!(extent(&wb), (46.0, 10.0));
+        wb.set_variable("wall", "3").unwrap();
+        assert_eq!(extent(&wb), (49.0, 15.0));
+
+        // a loop is refused and leaves everything as it was
+        let err = wb
+            .set_variable("wall", "width - 40")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("wall -> width -> wall"));
+        assert_eq!(wb.evaluate_variables().unwrap()["wall"], 3.0);
+        assert!(wb.set_variable("pi", "3").is_err());
+        assert!(wb.set_variable("2x", "3").is_err());
+
+        let err = wb.remove_variable("wall").unwrap_err().to_string();
+        assert!(err.contains("width") && err.contains("ext1.depth") && err.contains("sketch1.B.x"));
+
+        // typing in a value drops the formula
+        wb.set_step_parameters("ext1", vec!["depth".to_owned()], vec![7.0])
+            .unwrap();
+        wb.set_variable("wall", "4").unwrap();
+        assert_eq!(extent(&wb), (52.0, 7.0));
+        wb.unbind_step_parameter("sketch1", "B.x");
+        wb.unbind_step_parameter("sketch1", "C.x");
+        wb.remove_variable("width").unwrap();
+        assert_eq!(wb.variables.len(), 1);
+
+        // each parameter takes its own value, and a count that doesn't match
+        // is turned away with the formulas left as they were
+        wb.add_thin_extrusion(
+            "ext2",
+            "sketch1",
+            1.0,
+            vec![0],
+            Thin::new(1.0, ThinSide::Mid),
+            Operation::New,
+        );
+        let names = vec!["depth".to_owned(), "thickness".to_owned()];
+        wb.set_step_parameters("ext2", names.clone(), vec![8.0, 0.5])
+            .unwrap();
+        let Step::Extrusion { extrusion, .. } = &wb.steps[wb.step_index("ext2").unwrap()] else {
+            unreachable!()
+        };
+        assert_eq!(extrusion.depth, 8.0);
+        assert_eq!(extrusion.thin.as_ref().unwrap().thickness, 0.5);
+        wb.bind_step_parameter("ext1", "depth", "wall").unwrap();
+        for values in [vec![], vec![3.0], vec![3.0, 4.0, 5.0]] {
+            assert!(matches!(
+                wb.set_step_parameters("ext2", names.clone(), values),
+                Err(Error::InvalidParameter(_))
+            ));
+        }
+        assert!(wb
+            .set_step_parameters("ext1", vec!["depth".to_owned()], vec![])
+            .is_err());
+        assert_eq!(wb.bindings.len(), 1);
+    }
+
+    #[test]
+    fn test_units_for_input_and_export() {
+        let mut wb = Workbench::new("wb");
+        wb.set_units(Units::parse("in", "deg").unwrap()).unwrap();
+        let mut sketch = Sketch::new();
+        sketch.add_segments(Segment::link(
+            vec![
+                Point2D::new(0.0, 0.0, "A"),
+                Point2D::new(2.0, 0.0, "B"),
+                Point2D::new(2.0, 1.0, "C"),
+                Point2D::new(0.0, 1.0, "D"),
+            ],
+            true,
+        ));
+        wb.add_sketch("sketch1", sketch, "Top");
+        wb.add_extrusion("ext1", "sketch1", 1.0, vec![0], Operation::New);
+        wb.bind_step_parameter("ext1", "depth", "1/4 in + 6.35mm")
+            .unwrap();
+
+        let view = wb.create_view(100);
+        let solid = view.solids.get("ext1_0").unwrap();
+        let max_z = solid
+            .get_mesh()
+            .vertices
+            .iter()
+            .map(|v| v.z)
+            .fold(f64::MIN, f64::max);
+        assert!((max_z - 0.5).abs() < 1e-9);
+
+        let step = solid.get_step_text();
+        assert!(step.contains("CONVERSION_BASED_UNIT('INCH'"));
+        assert!(step.contains("LENGTH_MEASURE(25.4)"));
+        assert!(solid.get_obj_text().starts_with("# units: in\n"));
+        let stl_extent = |solid: &Solid| {
+            let stl = solid.get_stl_text();
+            assert!(stl.starts_with("solid ext1_0 units=mm\n"));
+            let mut max = [f64::MIN; 3];
+            for vertex in stl.lines().filter_map(|l| l.trim().strip_prefix("vertex ")) {
+                for (m, v) in max.iter_mut().zip(vertex.split_whitespace()) {
+                    *m = m.max(v.parse::<f64>().unwrap());
+                }
+            }
+            max
+        };
+        let [max_x, max_y, max_z] = stl_extent(solid);
+        assert!((max_x - 50.8).abs() < 1e-3);
+
+        // The model keeps its size when the workbench changes unit, both where
+        // it was given as plain numbers and where a formula gives it
+        wb.set_units(Units::default()).unwrap();
+        let view = wb.create_view(100);
+        let solid = view.solids.get("ext1_0").unwrap();
+        let mesh_z = solid
+            .get_mesh()
+            .vertices
+            .iter()
+            .map(|v| v.z)
+            .fold(f64::MIN, f64::max);
+        assert!((mesh_z - 12.7).abs() < 1e-9);
+        let after = stl_extent(solid);
+        for (a, b) in after.iter().zip([max_x, max_y, max_z]) {
+            assert!((a - b).abs() < 1e-3);
+        }
+        assert!(solid.get_step_text().contains("SI_UNIT(.MILLI.,.METRE.)"));
+        assert!(!solid.get_step_text().contains("CONVERSION_BASED_UNIT"));
+
+        // and a literal depth is converted along with the sketch
+        wb.set_step_parameters("ext1", vec!["depth".to_owned()], vec![25.4])
+            .unwrap();
+        wb.set_units(Units::parse("cm", "deg").unwrap()).unwrap();
+        let Step::Extrusion { extrusion, .. } = &wb.steps[wb.step_index("ext1").unwrap()] else {
+            unreachable!()
+        };
+        assert!((extrusion.depth - 2.54).abs() < 1e-9);
+        let view = wb.create_view(100);
+        let [x, _, z] = stl_extent(view.solids.get("ext1_0").unwrap());
+        assert!((x - 50.8).abs() < 1e-3 && (z - 25.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_reorder_suppress_and_rename_steps() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        wb.bind_step_parameter("ext1", "depth", "2 * 3").unwrap();
+        assert_eq!(wb.step_dependents("sketch1"), vec!["ext1", "fillet1"]);
+
+        // steps can't be moved ahead of what they use
+        let err = wb.move_step("fillet1", 4).unwrap_err().to_string();
+        assert!(err.contains("fillet1 uses ext1_0"));
+        assert_eq!(wb.step_index("fillet1"), Some(6));
+        assert!(wb.move_step("ext1", 6).is_err());
+        assert!(wb.move_step("sketch1", 0).is_err());
+        let point = Step::Point {
+            name: "datum".to_owned(),
+            point: Point::new(1.0, 2.0, 3.0),
+        };
+        wb.insert_step(0, point.clone()).unwrap();
+        assert!(wb.insert_step(1, point).is_err());
+        wb.move_step("datum", 7).unwrap();
+        assert_eq!(wb.steps.last().unwrap().name(), "datum");
+
+        // renaming carries the references along, solid names included
+        assert!(wb.rename_step("sketch1", "ext1").is_err());
+        assert!(wb.rename_step("sketch1", "ext1_3").is_err());
+        assert!(wb.rename_step("sketch1", " ").is_err());
+        wb.rename_step("sketch1", "Sketch 2").unwrap();
+        wb.rename_step("Sketch 2", "base").unwrap();
+        wb.rename_step("ext1", "body").unwrap();
+        assert_eq!(wb.bindings[0].step, "body");
+        match &wb.steps[6] {
+            Step::Fillet { edges, .. } => assert_eq!(edges[0].solid, "body_0"),
+            _ => panic!("the fillet moved"),
+        }
+        let faces = |wb: &Workbench| {
+            let view = wb.create_view(100);
+            view.solids.get("body_0").map(|solid| {
+                solid
+                    .truck_solid
+                    .boundaries()
+                    .iter()
+                    .map(|shell| shell.len())
+                    .sum::<usize>()
+            })
+        };
+        assert_eq!(faces(&wb), Some(7));
+        let view = wb.create_view(100);
+        let solid = view.solids.get("body_0").unwrap();
+        assert_eq!(
+            solid
+                .face_names()
+                .iter()
+                .flatten()
+                .filter(|n| n.to_string().starts_with("body"))
+                .count(),
+            6
+        );
+
+        // a suppressed step takes the steps that use it out of the view too
+        wb.set_step_suppressed("base", true).unwrap();
+        assert!(wb.create_view(100).solids.is_empty());
+        wb.set_step_suppressed("base", false).unwrap();
+        wb.set_step_suppressed("fillet1", true).unwrap();
+        assert_eq!(faces(&wb), Some(6));
+
+        let err = wb.delete_step("body").unwrap_err().to_string();
+        assert!(err.contains("fillet1"));
+        wb.delete_step("fillet1").unwrap();
+        assert!(wb.suppressed.is_empty());
+        wb.delete_step("body").unwrap();
+        assert!(wb.bindings.is_empty());
+        assert!(wb.create_view(100).solids.is_empty());
+        assert!(wb.delete_step("body").is_err());
+    }
+
+    #[test]
+    fn test_rollback_bar() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        let steps = wb.steps.len();
+        assert_eq!(wb.active_steps(), steps);
+        assert!(wb.roll_back(steps + 1).is_err());
+
+        // with the bar before the extrusion there is nothing to show, and a new
+        // step goes in where the bar is
+        let position = wb.step_index("ext1").unwrap();
+        wb.roll_back(position).unwrap();
+        assert!(wb.view().solids.is_empty());
+        wb.add_extrusion("ext2", "sketch1", 2.0, vec![0], Operation::New);
+        assert_eq!(wb.step_index("ext2"), Some(position));
+        assert_eq!(wb.rollback, Some(position + 1));
+        let view = wb.view();
+        assert_eq!(view.solids.len(), 1);
+        assert!(view.solids.contains_key("ext2_0"));
+
+        // the bar stays with the steps it sits between
+        wb.delete_step("ext2").unwrap();
+        assert_eq!(wb.rollback, Some(position));
+        let datum = Step::Point {
+            name: "datum".to_owned(),
+            point: Point::new(0.0, 0.0, 1.0),
+        };
+        wb.insert_step(wb.steps.len(), datum).unwrap();
+        assert_eq!(wb.rollback, Some(position));
+        wb.move_step("datum", 0).unwrap();
+        assert_eq!(wb.rollback, Some(position + 1));
+        wb.delete_step("datum").unwrap();
+        assert_eq!(wb.rollback, Some(position));
+        wb.set_step_suppressed("fillet1", true).unwrap();
+        wb.release_rollback();
+        let view = wb.view();
+        let solid = view.solids.get("ext1_0").unwrap();
+        let faces: usize = solid
+            .truck_solid
+            .boundaries()
+            .iter()
+            .map(|shell| shell.len())
+            .sum();
+        assert_eq!(faces, 6);
+        assert_eq!(wb.active_steps(), wb.steps.len());
+    }
+
+    #[test]
+    fn test_view_only_reruns_changed_steps() {
+        let build = || {
+            let mut wb = Workbench::new("wb");
+            add_square(&mut wb, "sketch1", "Top", 5.0);
+            wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+            wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+            add_square(&mut wb, "sketch2", "Front", 2.0);
+            wb.add_extrusion("ext2", "sketch2", 3.0, vec![0], Operation::New);
+            wb
+        };
+        let mut wb = build();
+
+        let first_face = |view: &WorkbenchView, solid: &str| {
+            let solid = &view.solids.get(solid).unwrap().truck_solid;
+            solid.boundaries()[0].face_iter().next().unwrap().id()
+        };
+        let depth = |view: &WorkbenchView, solid: &str| {
+            view.solids
+                .get(solid)
+                .unwrap()
+                .get_mesh()
+                .vertices
+                .iter()
+                .map(|v| v.z.max(-v.y))
+                .fold(f64::MIN, f64::max)
+        };
+        let before = wb.create_view(100);
+        let again = wb.create_view(100);
+        assert_eq!(first_face(&before, "ext1_0"), first_face(&again, "ext1_0"));
+
+        // ext2 doesn't touch ext1, so ext1 and its fillet are played back as
+        // they were, down to the very same faces
+        wb.set_step_parameters("ext2", vec!["depth".to_owned()], vec![6.0])
+            .unwrap();
+        let after = wb.create_view(100);
+        assert_eq!(first_face(&before, "ext1_0"), first_face(&after, "ext1_0"));
+        assert_ne!(first_face(&before, "ext2_0"), first_face(&after, "ext2_0"));
+        assert!((depth(&after, "ext2_0") - 6.0).abs() < 1e-9);
+
+        // while a change to the sketch carries through the extrusion to the
+        // fillet after it
+        wb.set_step_parameters("sketch1", vec!["B.y".to_owned()], vec![-8.0])
+            .unwrap();
+        let after = wb.create_view(100);
+        assert_ne!(first_face(&before, "ext1_0"), first_face(&after, "ext1_0"));
+        let mut fresh = build();
+        fresh
+            .set_step_parameters("ext2", vec!["depth".to_owned()], vec![6.0])
+            .unwrap();
+        fresh
+            .set_step_parameters("sketch1", vec!["B.y".to_owned()], vec![-8.0])
+            .unwrap();
+        let expected = fresh.create_view(100);
+        for name in ["ext1_0", "ext2_0"] {
+            let mut a = after.solids[name].get_mesh().vertices;
+            let mut b = expected.solids[name].get_mesh().vertices;
+            assert_eq!(a.len(), b.len());
+            let key = |p: &Point| (p.x, p.y, p.z);
+            a.sort_by(|p, q| key(p).partial_cmp(&key(q)).unwrap());
+            b.sort_by(|p, q| key(p).partial_cmp(&key(q)).unwrap());
+            assert!(a.iter().zip(b.iter()).all(|(p, q)| p
+                .to_vector()
+                .subtract(q.to_vector())
+                .length()
+                < 1e-9));
+        }
+        let faces: usize = after.solids["ext1_0"]
+            .truck_solid
+            .boundaries()
+            .iter()
+            .map(|shell| shell.len())
+            .sum();
+        assert_eq!(faces, 7);
+
+        // a view as of an earlier step gives the same result as a fresh one
+        let rolled = wb.create_view(wb.step_index("ext2").unwrap());
+        assert!(!rolled.solids.contains_key("ext2_0"));
+        assert!(rolled.solids.contains_key("ext1_0"));
+    }
+
+    #[test]
+    fn test_views_of_different_lengths_share_the_cache() {
+        // flipping between the whole model and a rolled back view of it, as
+        // a preview does, reruns nothing after the first of each
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 5.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        add_square(&mut wb, "sketch2", "Front", 2.0);
+        wb.add_extrusion("ext2", "sketch2", 3.0, vec![0], Operation::New);
+        let short = wb.step_index("fillet1").unwrap();
+
+        let faces = |view: &WorkbenchView| {
+            let face = |solid: &str| {
+                let solid = &view.solids.get(solid).unwrap().truck_solid;
+                solid.boundaries()[0].face_iter().next().unwrap().id()
+            };
+            (
+                face("ext1_0"),
+                view.solids.get("ext2_0").map(|_| face("ext2_0")),
+            )
+        };
+        let full = faces(&wb.create_view(100));
+        let rolled = faces(&wb.create_view(short));
+        assert!(rolled.1.is_none());
+        for _ in 0..3 {
+            assert_eq!(faces(&wb.create_view(100)), full);
+            assert_eq!(faces(&wb.create_view(short)), rolled);
+        }
+    }
+
+    #[test]
+    fn test_views_build_on_other_threads() {
+        // a workbench, its views and the project holding it can all be
+        // handed to other threads, caches and all
+        fn shared<T: Send + Sync>(_: &T) {}
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 5.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+        shared(&wb);
+        shared(&crate::project::Project::new("p", true));
+
+        let meshed: Vec<usize> = std::thread::scope(|s| {
+            let builds: Vec<_> = (0..2)
+                .map(|_| {
+                    s.spawn(|| {
+                        let view = wb.create_view(100);
+                        shared(&view);
+                        view.solids["ext1_0"].get_mesh().indices.len()
+                    })
+                })
+                .collect();
+            builds.into_iter().map(|b| b.join().unwrap()).collect()
+        });
+        assert!(meshed[0] > 0);
+        assert_eq!(meshed[0], meshed[1]);
+    }
+
+    #[test]
+    fn test_moves_keep_in_place_edits_in_order() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![0], Operation::New);
+        wb.add_point("datum", Point::new(0.0, 0.0, 0.0));
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        wb.add_mirror(
+            "mirror1",
+            vec!["ext1_0".to_owned()],
+            Mirror::new(PlaneRef::Plane("Front".to_owned()), false),
+        );
+        let fillet = wb.step_index("fillet1").unwrap();
+        let mirror = wb.step_index("mirror1").unwrap();
+
+        // the mirror copies the filleted body, whichever of them is moved
+        let err = wb.move_step("fillet1", mirror).unwrap_err().to_string();
+        assert!(err.contains("fillet1 can't be moved past mirror1"));
+        assert!(wb.move_step("mirror1", fillet).is_err());
+        assert_eq!(wb.step_index("fillet1"), Some(fillet));
+
+        // steps that only read the body, or don't touch it, pass freely
+        wb.move_step("datum", mirror).unwrap();
+        wb.move_step("datum", fillet - 1).unwrap();
+        let shell = Step::Shell {
+            name: "shell1".to_owned(),
+            shell: Shell::new(0.5, ShellDirection::Inward),
+            solid: "ext1_0".to_owned(),
+            faces: vec![PrismFace::Top],
+        };
+        wb.insert_step(fillet + 1, shell).unwrap();
+        assert!(wb.move_step("shell1", fillet).is_err());
+    }
+
+    #[test]
+    fn test_broken_steps_are_reported() {
+        let mut wb = Workbench::new("wb");
+        add_square(&mut wb, "sketch1", "Top", 10.0);
+        wb.add_extrusion("ext1", "sketch1", 4.0, vec![3], Operation::New);
+        wb.add_fillet("fillet1", vec![EdgeId::new("ext1_0", 0, 1)], 1.0);
+        wb.add_extrusion("ext2", "sketch1", 2.0, vec![0], Operation::New);
+        let mut orphan = wb.steps.last().unwrap().clone();
+        *orphan.name_mut() = "ext3".to_owned();
+        if let Step::Extrusion { sketch, .. } = &mut orphan {
+            *sketch = "missing".to_owned();
+        }
+        wb.push_step(orphan);
+
+        // the view is built around the broken steps, and played back the same
+        // from the cache
+        for _ in 0..2 {
+            let view = wb.create_view(100);
+            assert!(view.solids.contains_key("ext2_0"));
+            assert!(!view.solids.contains_key("ext1_0"));
+            assert_eq!(view.diagnostics.len(), 3);
+            assert_eq!(
+                view.errors("ext1"),
+                vec![&Error::InvalidParameter("sketch1 has no face 3".to_owned())]
+            );
+            assert!(matches!(view.errors("fillet1")[0], Error::Dependency(_)));
+            assert_eq!(
+                view.errors("ext3"),
+                vec![&Error::not_found("sketch", "missing")]
+            );
+            assert!(view.errors("ext2").is_empty());
+        }
+
+        wb.set_selected_for_operation("ext1", "faces", "sketch1", vec![0])
+            .unwrap();
+        let view = wb.create_view(100);
+        assert!(view.solids.contains_key("ext1_0"));
+        assert!(view.errors("ext1").is_empty() && view.errors("fillet1").is_empty());
+
+        assert_eq!(
+            wb.delete_step("nothing"),
+            Err(Error::not_found("step", "nothing"))
+        );
+        assert!(matches!(
+            wb.set_step_parameters("ext2", vec!["color".to_owned()], vec![3.0]),
+            Err(Error::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            wb.set_variable("wall", "2 +"),
+            Err(Error::InvalidExpression(_))
+        ));
     }
 }